@@ -1,12 +1,15 @@
 mod screenshot;
 mod app_monitor;
+mod recording;
 
-use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tauri::Manager;
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
 
+// Shift+F8 延时截图的默认倒计时（秒）
+const DEFAULT_CAPTURE_DELAY_SECS: u32 = 3;
+
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
 fn greet(name: &str) -> String {
@@ -67,10 +70,18 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
-        .manage(screenshot::ScreenshotStore {
-            data: Mutex::new(HashMap::new()),
-        })
+        .register_asynchronous_uri_scheme_protocol(
+            screenshot::protocol::SCHEME,
+            screenshot::protocol::handle,
+        )
+        .manage(screenshot::ScreenshotStore::new())
         .manage(app_monitor::AppMonitor::new())
+        .manage(screenshot::timed::TimedCapture::new())
+        .manage(screenshot::retention::RetentionService::new())
+        .manage(screenshot::delay::DelayedCapture::new())
+        .manage(screenshot::scroll::ScrollCapture::new())
+        .manage(screenshot::region::RegionSelector::new())
+        .manage(recording::ScreenRecorder::new())
         .invoke_handler(tauri::generate_handler![
             greet,
             set_window_opacity,
@@ -80,45 +91,99 @@ pub fn run() {
             screenshot::open_screenshot_window,
             screenshot::get_screenshot_data,
             screenshot::capture_and_show,
+            screenshot::monitors::list_monitors,
+            screenshot::backend::get_capture_backend,
+            screenshot::delay::cancel_delayed_capture,
+            screenshot::delay::is_delayed_capture_pending,
+            screenshot::scroll::start_scroll_capture,
+            screenshot::scroll::stop_scroll_capture,
+            screenshot::region::region_capture_and_show,
+            screenshot::region::submit_region_selection,
+            screenshot::region::cancel_region_selection,
+            screenshot::window_list::list_windows,
+            screenshot::window_capture::window_capture_and_show,
             screenshot::save_screenshot_to_file,
+            screenshot::read_image_metadata,
+            screenshot::report::export_screenshot_report,
+            screenshot::diff::diff_screenshots,
+            screenshot::barcode::scan_codes,
+            screenshot::color_picker::pick_color,
+            screenshot::edit::edit_screenshot,
+            screenshot::store::release_screenshot,
+            screenshot::store::list_screenshot_store,
+            screenshot::timed::start_timed_capture,
+            screenshot::timed::stop_timed_capture,
+            screenshot::timed::get_timed_capture_status,
+            screenshot::library::trash_screenshots,
+            screenshot::dedup::find_duplicate_screenshots,
+            screenshot::dedup::remove_duplicate_screenshots,
+            screenshot::retention::get_retention_policy,
+            screenshot::retention::set_retention_policy,
+            screenshot::retention::run_retention_now,
+            screenshot::retention::get_last_retention_report,
+            screenshot::retention::set_screenshot_pinned,
+            screenshot::retention::set_screenshot_tags,
+            recording::start_recording,
+            recording::pause_recording,
+            recording::resume_recording,
+            recording::stop_recording,
+            recording::set_recording_audio,
+            recording::check_ffmpeg,
+            recording::timelapse::create_timelapse,
+            recording::library::list_recordings,
+            recording::library::delete_recording,
             app_monitor::start_app_monitoring,
             app_monitor::stop_app_monitoring,
             app_monitor::get_current_app_info,
             app_monitor::get_app_icon_by_path
         ])
         .setup(|app| {
-            // 注册全局快捷键 F8 用于截图
-            let app_handle = app.handle().clone();
-
+            // 注册全局快捷键：F8 区域截图，Shift+F8 延时后区域截图（倒计时或框选中再次按下则取消）
             // 简单防抖：避免按一次键同时触发按下/抬起两次事件导致打开两个窗口
             // 若在 400ms 内重复触发，则忽略第二次
             let last_trigger = Arc::new(Mutex::new(Instant::now() - Duration::from_secs(1)));
-            let last_trigger_cloned = last_trigger.clone();
 
-            app.global_shortcut().on_shortcut("F8", move |_app, _shortcut, event| {
-                // 仅在按下时触发，忽略抬起事件
-                if event.state() != ShortcutState::Pressed {
-                    return;
-                }
-                let mut last = last_trigger_cloned.lock().unwrap();
-                let now = Instant::now();
-                if now.duration_since(*last) < Duration::from_millis(400) {
-                    // 忽略过于频繁的触发（如按下与抬起各触发一次，或键盘连发）
-                    return;
-                }
-                *last = now;
+            for (shortcut, delay_secs) in [("F8", 0), ("Shift+F8", DEFAULT_CAPTURE_DELAY_SECS)] {
+                let app_handle = app.handle().clone();
+                let last_trigger_cloned = last_trigger.clone();
 
-                let app_handle = app_handle.clone();
-                tauri::async_runtime::spawn(async move {
-                    match screenshot::trigger_screenshot(app_handle).await {
-                        Ok(_) => println!("Screenshot triggered successfully"),
-                        Err(e) => eprintln!("Failed to trigger screenshot: {}", e),
+                app.global_shortcut().on_shortcut(shortcut, move |_app, _shortcut, event| {
+                    // 仅在按下时触发，忽略抬起事件
+                    if event.state() != ShortcutState::Pressed {
+                        return;
+                    }
+                    let mut last = last_trigger_cloned.lock().unwrap();
+                    let now = Instant::now();
+                    if now.duration_since(*last) < Duration::from_millis(400) {
+                        // 忽略过于频繁的触发（如按下与抬起各触发一次，或键盘连发）
+                        return;
                     }
-                });
-            })?;
+                    *last = now;
+
+                    let app_handle = app_handle.clone();
+                    tauri::async_runtime::spawn(async move {
+                        match screenshot::trigger_screenshot(app_handle, delay_secs).await {
+                            Ok(_) => println!("Screenshot triggered successfully"),
+                            Err(e) => eprintln!("Failed to trigger screenshot: {}", e),
+                        }
+                    });
+                })?;
+            }
+
+            // 按已保存的保留策略启动截图定期清理
+            app.state::<screenshot::retention::RetentionService>()
+                .start(app.handle().clone());
             
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            // 定时截图运行期间，关闭所有窗口后保持后台运行
+            if let tauri::RunEvent::ExitRequested { code: None, api, .. } = event {
+                if app_handle.state::<screenshot::timed::TimedCapture>().is_running() {
+                    api.prevent_exit();
+                }
+            }
+        });
 }
@@ -0,0 +1,669 @@
+// 录屏：按固定帧率连续截取显示器或其中的区域，编码为动画 GIF / APNG，或通过本机 ffmpeg 编码为 MP4 / WebM
+// 截图复用 screenshot 模块的截图后端（X11 下为 MIT-SHM），录屏文件保存在截图目录旁并写入 recordings 表
+// 视频可同时录制系统声音和麦克风（PulseAudio / PipeWire）；定时截图可合成为延时视频（timelapse）
+pub mod animation;
+pub mod audio;
+pub mod library;
+pub mod overlay;
+pub mod timelapse;
+pub mod video;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use image::{imageops, Rgba, RgbaImage};
+use screenshots::Screen;
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle, Emitter, Manager, State};
+
+use crate::screenshot::monitors::{self, CaptureRegion};
+use crate::screenshot::{backend, get_cursor_screen, path_to_string, pipeline};
+use animation::{AnimationFrame, FrameDiffer};
+use audio::{AudioSource, AudioSourceKind};
+use video::{VideoContainer, VideoEncoder, VideoOptions};
+
+// 录屏文件保存在 app_data_dir/recordings
+const RECORDINGS_DIR: &str = "recordings";
+const MAX_FPS: u32 = 30;
+const MAX_VIDEO_FPS: u32 = 60;
+// 未指定时长时的录制上限，视频直接写入 ffmpeg，不占用内存，上限更长
+const MAX_DURATION_SECS: u32 = 600;
+const MAX_VIDEO_DURATION_SECS: u32 = 4 * 3600;
+// 缓存的变化区域超过该大小时自动停止，避免长时间录制占满内存
+const MAX_BUFFERED_BYTES: usize = 1024 * 1024 * 1024;
+// recording-progress 事件的最小间隔
+const PROGRESS_INTERVAL_MS: u64 = 250;
+// 暂停时检查状态的间隔
+const PAUSE_POLL_MS: u64 = 50;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordingFormat {
+    #[default]
+    Gif,
+    Apng,
+    Mp4,
+    Webm,
+}
+
+impl RecordingFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            RecordingFormat::Gif => "gif",
+            RecordingFormat::Apng => "png",
+            RecordingFormat::Mp4 => "mp4",
+            RecordingFormat::Webm => "webm",
+        }
+    }
+
+    fn is_video(self) -> bool {
+        matches!(self, RecordingFormat::Mp4 | RecordingFormat::Webm)
+    }
+
+    fn container(self) -> VideoContainer {
+        match self {
+            RecordingFormat::Webm => VideoContainer::Webm,
+            _ => VideoContainer::Mp4,
+        }
+    }
+
+    // 未指定帧率时动画为 10，视频为 30
+    fn fps(self, fps: Option<u32>) -> u32 {
+        let (default, max) = if self.is_video() { (30, MAX_VIDEO_FPS) } else { (10, MAX_FPS) };
+        fps.unwrap_or(default).clamp(1, max)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingConfig {
+    #[serde(default)]
+    pub format: RecordingFormat,
+    pub monitor: Option<usize>,        // 为空时使用鼠标所在的显示器
+    pub region: Option<CaptureRegion>, // 为空时录制整个显示器
+    pub fps: Option<u32>,              // 为空时动画为 10，视频为 30
+    pub duration_secs: Option<u32>,    // 为空时录制到 stop_recording 为止
+    #[serde(default = "default_gif_speed")]
+    pub gif_speed: i32,                // GIF 调色板量化速度 1-30，越小质量越好
+    pub ffmpeg_path: Option<String>,   // 为空时使用 FFMPEG_PATH 环境变量或 PATH 中的 ffmpeg
+    pub codec: Option<String>,         // 视频编码器，为空时 MP4 使用 libx264，WebM 使用 libvpx-vp9
+    pub crf: Option<u8>,               // 视频质量，越小质量越好，为空时使用编码器的推荐值
+    #[serde(default)]
+    pub audio: Vec<AudioSource>,       // 录制的音源，仅支持 MP4 / WebM
+}
+
+fn default_gif_speed() -> i32 {
+    10
+}
+
+impl Default for RecordingConfig {
+    fn default() -> Self {
+        Self {
+            format: RecordingFormat::default(),
+            monitor: None,
+            region: None,
+            fps: None,
+            duration_secs: None,
+            gif_speed: default_gif_speed(),
+            ffmpeg_path: None,
+            codec: None,
+            crf: None,
+            audio: Vec::new(),
+        }
+    }
+}
+
+impl RecordingConfig {
+    fn fps(&self) -> u32 {
+        self.format.fps(self.fps)
+    }
+
+    fn max_duration_ms(&self) -> u64 {
+        let max = if self.format.is_video() { MAX_VIDEO_DURATION_SECS } else { MAX_DURATION_SECS };
+        self.duration_secs.unwrap_or(max).min(max) as u64 * 1000
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingPhase {
+    Recording,
+    Paused,
+    Encoding,
+    Finished,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingStopReason {
+    Stopped,     // 用户停止
+    Duration,    // 达到设定的时长
+    MemoryLimit, // 缓存的帧过多
+    FfmpegError, // ffmpeg 异常退出，已保存之前完成的分段
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingProgressEvent {
+    pub phase: RecordingPhase,
+    pub elapsed_ms: u64,        // 已录制时长（不含暂停时间）
+    pub captured_frames: u32,   // 已截取的帧数
+    pub stored_frames: u32,     // 动画为画面有变化、需要编码的帧数，视频为已写入 ffmpeg 的帧数
+    pub encoded_frames: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingResult {
+    pub id: Option<i64>, // recordings 表中的记录，写入数据库失败时为空
+    pub file_path: String,
+    pub format: RecordingFormat,
+    pub width: u32,
+    pub height: u32,
+    pub frames: u32,
+    pub duration_ms: u64,
+    pub size_bytes: u64,
+    pub stop_reason: RecordingStopReason,
+    pub error: Option<String>, // ffmpeg 出错时的错误信息
+    pub ffmpeg: Option<String>, // 使用的 ffmpeg 路径和版本，GIF/APNG 录制时为空
+}
+
+#[derive(Default)]
+struct RecorderState {
+    session: u64,
+    running: bool,
+    paused: bool,
+    audio: Vec<AudioSource>,
+    audio_revision: u64, // 录制中修改音量或静音时递增
+}
+
+pub struct ScreenRecorder {
+    // 每次开始或停止都递增会话号，录制线程发现会话号变化后结束并编码
+    state: Arc<Mutex<RecorderState>>,
+}
+
+impl ScreenRecorder {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(RecorderState::default())),
+        }
+    }
+
+    fn begin(&self, audio: Vec<AudioSource>) -> Result<u64, String> {
+        let mut state = self.state.lock().unwrap();
+        if state.running {
+            return Err("Recording is already running".to_string());
+        }
+        state.running = true;
+        state.paused = false;
+        state.audio = audio;
+        state.audio_revision = 0;
+        state.session += 1;
+        Ok(state.session)
+    }
+
+    fn finish(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.running = false;
+        state.paused = false;
+        state.audio.clear();
+    }
+
+    // 音源设置在 revision 之后有修改时返回新设置
+    fn audio_changes(&self, revision: &mut u64) -> Option<Vec<AudioSource>> {
+        let state = self.state.lock().unwrap();
+        if state.audio_revision == *revision {
+            return None;
+        }
+        *revision = state.audio_revision;
+        Some(state.audio.clone())
+    }
+
+    fn set_audio(&self, kind: AudioSourceKind, volume: Option<f32>, muted: Option<bool>) -> Result<bool, String> {
+        let mut state = self.state.lock().unwrap();
+        if !state.running {
+            return Ok(false);
+        }
+        let source = state
+            .audio
+            .iter_mut()
+            .find(|source| source.kind == kind)
+            .ok_or_else(|| format!("Audio source {:?} is not being recorded", kind))?;
+        if let Some(volume) = volume.filter(|volume| volume.is_finite()) {
+            source.volume = volume.clamp(0.0, audio::MAX_VOLUME);
+        }
+        if let Some(muted) = muted {
+            source.muted = muted;
+        }
+        state.audio_revision += 1;
+        Ok(true)
+    }
+
+    fn is_current(&self, session_id: u64) -> bool {
+        self.state.lock().unwrap().session == session_id
+    }
+
+    fn is_paused(&self) -> bool {
+        self.state.lock().unwrap().paused
+    }
+
+    fn set_paused(&self, paused: bool) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if !state.running || state.paused == paused {
+            return false;
+        }
+        state.paused = paused;
+        true
+    }
+
+    fn stop(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if !state.running {
+            return false;
+        }
+        state.session += 1;
+        true
+    }
+}
+
+// 获取录屏保存目录（app_data_dir/recordings），不存在时自动创建
+pub fn recordings_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app.path().app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let dir = app_data_dir.join(RECORDINGS_DIR);
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create recordings directory: {}", e))?;
+    Ok(dir)
+}
+
+fn emit_progress(app: &AppHandle, phase: RecordingPhase, elapsed_ms: u64, captured: u32, stored: u32, encoded: u32) {
+    let _ = app.emit(
+        "recording-progress",
+        RecordingProgressEvent {
+            phase,
+            elapsed_ms,
+            captured_frames: captured,
+            stored_frames: stored,
+            encoded_frames: encoded,
+        },
+    );
+}
+
+// 录制的显示器或区域，连续截图时复用同一块缓冲区
+struct FrameSource {
+    screen: Screen,
+    region: Option<CaptureRegion>,
+    size: Option<(u32, u32)>,
+    buffer: RgbaImage,
+}
+
+impl FrameSource {
+    fn open(config: &RecordingConfig) -> Result<Self, String> {
+        let screens = monitors::list_screens()?;
+        let index = match config.monitor {
+            Some(index) if index < screens.len() => index,
+            Some(index) => return Err(format!("Monitor {} not found", index)),
+            None => get_cursor_screen(&screens).unwrap_or(0),
+        };
+        Ok(Self {
+            screen: screens[index],
+            region: config.region,
+            size: None,
+            buffer: RgbaImage::new(0, 0),
+        })
+    }
+
+    fn grab(&mut self) -> Result<&RgbaImage, String> {
+        backend::capture_into(&self.screen, self.region.as_ref(), &mut self.buffer)?;
+        // 画面尺寸以第一帧为准（显示器分辨率在录制中改变时裁剪或补齐）
+        let (width, height) = *self.size.get_or_insert(self.buffer.dimensions());
+        if self.buffer.dimensions() != (width, height) {
+            let mut resized = RgbaImage::from_pixel(width, height, Rgba([0, 0, 0, 255]));
+            imageops::replace(&mut resized, &self.buffer, 0, 0);
+            self.buffer = resized;
+        }
+        Ok(&self.buffer)
+    }
+}
+
+// 录制循环输出的帧，由动画或视频编码实现
+trait FrameSink {
+    // 写入一帧，需要提前结束录制时返回结束原因，captured_at 为截取该帧的系统时间
+    fn push(&mut self, frame: &RgbaImage, elapsed_ms: u64, captured_at: SystemTime) -> Result<Option<RecordingStopReason>, String>;
+    // 进入暂停状态
+    fn pause(&mut self) -> Option<RecordingStopReason>;
+    // 录制中修改了音量或静音
+    fn set_audio(&mut self, _audio: Vec<AudioSource>) -> Option<RecordingStopReason> {
+        None
+    }
+    fn stored_frames(&self) -> u32;
+}
+
+// 动画：在内存中缓存相邻帧的变化区域，录制结束后统一编码
+#[derive(Default)]
+struct AnimationSink {
+    differ: FrameDiffer,
+    frames: Vec<AnimationFrame>,
+    buffered_bytes: usize,
+}
+
+impl FrameSink for AnimationSink {
+    fn push(&mut self, frame: &RgbaImage, elapsed_ms: u64, _captured_at: SystemTime) -> Result<Option<RecordingStopReason>, String> {
+        if let Some(frame) = self.differ.push(frame.clone(), elapsed_ms) {
+            self.buffered_bytes += frame.byte_size();
+            self.frames.push(frame);
+        }
+        Ok((self.buffered_bytes >= MAX_BUFFERED_BYTES).then_some(RecordingStopReason::MemoryLimit))
+    }
+
+    fn pause(&mut self) -> Option<RecordingStopReason> {
+        None
+    }
+
+    fn stored_frames(&self) -> u32 {
+        self.frames.len() as u32
+    }
+}
+
+// 视频：边录制边写入 ffmpeg，按时间重复写入帧以保持恒定帧率
+struct VideoSink {
+    options: VideoOptions,
+    segments_dir: PathBuf,
+    encoder: Option<VideoEncoder>,
+    error: Option<String>,
+}
+
+impl FrameSink for VideoSink {
+    fn push(&mut self, frame: &RgbaImage, elapsed_ms: u64, captured_at: SystemTime) -> Result<Option<RecordingStopReason>, String> {
+        let encoder = match &mut self.encoder {
+            Some(encoder) => encoder,
+            None => self.encoder.insert(VideoEncoder::new(
+                self.options.clone(),
+                self.segments_dir.clone(),
+                frame.width(),
+                frame.height(),
+            )?),
+        };
+        // 截图慢于帧率时重复写入当前帧，快于帧率时跳过
+        let target = elapsed_ms * self.options.fps as u64 / 1000 + 1;
+        let copies = target.saturating_sub(encoder.frames());
+        if copies == 0 {
+            return Ok(None);
+        }
+        match encoder.write_frame(frame, copies, captured_at) {
+            Ok(_) => Ok(None),
+            Err(e) => Ok(self.fail(e)),
+        }
+    }
+
+    fn pause(&mut self) -> Option<RecordingStopReason> {
+        match self.encoder.as_mut()?.end_segment() {
+            Ok(_) => None,
+            Err(e) => self.fail(e),
+        }
+    }
+
+    fn set_audio(&mut self, audio: Vec<AudioSource>) -> Option<RecordingStopReason> {
+        let Some(encoder) = self.encoder.as_mut() else {
+            // 还没有写入帧，第一个分段直接使用新设置
+            self.options.audio = audio;
+            return None;
+        };
+        match encoder.set_audio(audio) {
+            Ok(_) => None,
+            Err(e) => self.fail(e),
+        }
+    }
+
+    fn stored_frames(&self) -> u32 {
+        self.encoder.as_ref().map(|encoder| encoder.frames() as u32).unwrap_or(0)
+    }
+}
+
+struct RecordingOutcome {
+    width: u32,
+    height: u32,
+    duration_ms: u64,
+    captured: u32,
+    reason: RecordingStopReason,
+}
+
+impl VideoSink {
+    fn fail(&mut self, error: String) -> Option<RecordingStopReason> {
+        eprintln!("ffmpeg 编码失败，结束录制: {}", error);
+        self.error = Some(error);
+        Some(RecordingStopReason::FfmpegError)
+    }
+}
+
+// 按帧率截取画面写入 sink，直到停止或达到上限（阻塞）
+fn record(app: &AppHandle, session_id: u64, config: &RecordingConfig, sink: &mut impl FrameSink) -> Result<RecordingOutcome, String> {
+    let recorder = app.state::<ScreenRecorder>();
+    let mut source = FrameSource::open(config)?;
+    let interval = Duration::from_millis(1000 / config.fps() as u64);
+    let max_duration_ms = config.max_duration_ms();
+    let mut captured = 0u32;
+    let mut audio_revision = 0;
+
+    // 录制时长不含暂停时间
+    let start = Instant::now();
+    let mut paused_total = Duration::ZERO;
+    let mut last_progress: Option<Instant> = None;
+    let recorded_ms = |paused_total: Duration| (start.elapsed().saturating_sub(paused_total)).as_millis() as u64;
+
+    let reason = loop {
+        if !recorder.is_current(session_id) {
+            break RecordingStopReason::Stopped;
+        }
+
+        if recorder.is_paused() {
+            let pause_start = Instant::now();
+            emit_progress(app, RecordingPhase::Paused, recorded_ms(paused_total), captured, sink.stored_frames(), 0);
+            if let Some(reason) = sink.pause() {
+                break reason;
+            }
+            while recorder.is_paused() && recorder.is_current(session_id) {
+                thread::sleep(Duration::from_millis(PAUSE_POLL_MS));
+            }
+            paused_total += pause_start.elapsed();
+            continue;
+        }
+
+        if let Some(audio) = recorder.audio_changes(&mut audio_revision) {
+            if let Some(reason) = sink.set_audio(audio) {
+                break reason;
+            }
+        }
+
+        let elapsed_ms = recorded_ms(paused_total);
+        if elapsed_ms >= max_duration_ms {
+            break RecordingStopReason::Duration;
+        }
+
+        let frame_start = Instant::now();
+        let captured_at = SystemTime::now();
+        let frame = source.grab()?;
+        captured += 1;
+        if let Some(reason) = sink.push(frame, elapsed_ms, captured_at)? {
+            break reason;
+        }
+
+        if last_progress.is_none_or(|last| last.elapsed() >= Duration::from_millis(PROGRESS_INTERVAL_MS)) {
+            emit_progress(app, RecordingPhase::Recording, elapsed_ms, captured, sink.stored_frames(), 0);
+            last_progress = Some(Instant::now());
+        }
+
+        // 截图本身的耗时计入帧间隔
+        if let Some(remaining) = interval.checked_sub(frame_start.elapsed()) {
+            thread::sleep(remaining);
+        }
+    };
+
+    let (width, height) = source.size.ok_or("No frames were captured")?;
+    Ok(RecordingOutcome {
+        width,
+        height,
+        duration_ms: recorded_ms(paused_total),
+        captured,
+        reason,
+    })
+}
+
+fn record_animation(app: &AppHandle, session_id: u64, config: &RecordingConfig, path: &Path) -> Result<RecordingResult, String> {
+    let mut sink = AnimationSink::default();
+    let RecordingOutcome { width, height, duration_ms, captured, reason } = record(app, session_id, config, &mut sink)?;
+
+    let frames = sink.frames;
+    let stored = frames.len() as u32;
+    emit_progress(app, RecordingPhase::Encoding, duration_ms, captured, stored, 0);
+    let on_progress = |encoded: usize| {
+        emit_progress(app, RecordingPhase::Encoding, duration_ms, captured, stored, encoded as u32);
+    };
+    let encoded = match config.format {
+        RecordingFormat::Apng => animation::encode_apng(path, &frames, width, height, duration_ms, on_progress),
+        _ => animation::encode_gif(path, &frames, width, height, duration_ms, config.gif_speed, on_progress),
+    };
+    if let Err(e) = encoded {
+        let _ = fs::remove_file(path);
+        return Err(e);
+    }
+    emit_progress(app, RecordingPhase::Finished, duration_ms, captured, stored, stored);
+
+    Ok(RecordingResult {
+        id: None,
+        file_path: path_to_string(path),
+        format: config.format,
+        width,
+        height,
+        frames: stored,
+        duration_ms,
+        size_bytes: 0,
+        stop_reason: reason,
+        error: None,
+        ffmpeg: None,
+    })
+}
+
+fn record_video(app: &AppHandle, session_id: u64, config: &RecordingConfig, path: &Path) -> Result<RecordingResult, String> {
+    // 开始录制前确认 ffmpeg 可用
+    let (ffmpeg, version) = video::resolve_ffmpeg(config.ffmpeg_path.as_deref())?;
+    let ffmpeg_info = format!("{} ({})", ffmpeg.display(), version);
+    if !config.audio.is_empty() {
+        audio::validate_sources(&config.audio)?;
+        video::check_audio_support(&ffmpeg)?;
+    }
+    let fps = config.fps();
+    let file_stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let mut sink = VideoSink {
+        options: VideoOptions::new(ffmpeg, config.format.container(), config.codec.clone(), config.crf, fps, config.audio.clone()),
+        segments_dir: path.with_file_name(format!(".segments_{}", file_stem)),
+        encoder: None,
+        error: None,
+    };
+    let RecordingOutcome { width, height, duration_ms, captured, reason } = record(app, session_id, config, &mut sink)?;
+
+    // 结束最后一个分段并拼接
+    emit_progress(app, RecordingPhase::Encoding, duration_ms, captured, sink.stored_frames(), 0);
+    let VideoSink { encoder, error, .. } = sink;
+    let mut encoder = encoder.ok_or_else(|| error.clone().unwrap_or_else(|| "No frames were captured".to_string()))?;
+    let lost = encoder
+        .finish(path)
+        .map_err(|e| error.clone().unwrap_or(e))?;
+    let error = error.or(lost);
+
+    // 视频时长以实际写入的帧数为准（ffmpeg 出错时丢弃的分段不计入）
+    let frames = encoder.frames() as u32;
+    let duration_ms = frames as u64 * 1000 / fps as u64;
+    emit_progress(app, RecordingPhase::Finished, duration_ms, captured, frames, frames);
+
+    Ok(RecordingResult {
+        id: None,
+        file_path: path_to_string(path),
+        format: config.format,
+        width,
+        height,
+        frames,
+        duration_ms,
+        size_bytes: 0,
+        stop_reason: if error.is_some() { RecordingStopReason::FfmpegError } else { reason },
+        error,
+        ffmpeg: Some(ffmpeg_info),
+    })
+}
+
+fn run_recording(app: &AppHandle, session_id: u64, config: &RecordingConfig) -> Result<RecordingResult, String> {
+    if !config.audio.is_empty() && !config.format.is_video() {
+        return Err("Audio can only be recorded to MP4 or WebM".to_string());
+    }
+    let file_name = format!(
+        "recording_{}.{}",
+        chrono::Local::now().format("%Y%m%d_%H%M%S"),
+        config.format.extension()
+    );
+    let path = recordings_dir(app)?.join(file_name);
+    let mut result = if config.format.is_video() {
+        record_video(app, session_id, config, &path)?
+    } else {
+        record_animation(app, session_id, config, &path)?
+    };
+
+    result.size_bytes = fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0);
+    // 数据库写入失败不影响已保存的文件
+    result.id = library::insert_recording(
+        app,
+        &result.file_path,
+        result.format,
+        result.width,
+        result.height,
+        result.duration_ms,
+        result.size_bytes,
+    )
+    .inspect_err(|e| eprintln!("保存录屏记录失败: {}", e))
+    .ok();
+    Ok(result)
+}
+
+// 开始录制，停止或达到时长后编码保存并返回结果
+#[command]
+pub async fn start_recording(app: AppHandle, config: Option<RecordingConfig>) -> Result<RecordingResult, String> {
+    let config = config.unwrap_or_default();
+    let session_id = app.state::<ScreenRecorder>().begin(config.audio.clone())?;
+    let worker_app = app.clone();
+    let result = pipeline::run_blocking(move || run_recording(&worker_app, session_id, &config)).await;
+    app.state::<ScreenRecorder>().finish();
+    result
+}
+
+// 暂停录制，返回状态是否改变
+#[command]
+pub fn pause_recording(recorder: State<ScreenRecorder>) -> Result<bool, String> {
+    Ok(recorder.set_paused(true))
+}
+
+#[command]
+pub fn resume_recording(recorder: State<ScreenRecorder>) -> Result<bool, String> {
+    Ok(recorder.set_paused(false))
+}
+
+// 停止录制，返回是否有正在进行的录制
+#[command]
+pub fn stop_recording(recorder: State<ScreenRecorder>) -> Result<bool, String> {
+    Ok(recorder.stop())
+}
+
+// 录制中调节音源的音量（0-2）或静音，返回是否有正在进行的录制
+// 修改后开始新的分段，分段切换时该音源有约 0.1 秒的空白
+#[command]
+pub fn set_recording_audio(
+    recorder: State<ScreenRecorder>,
+    kind: AudioSourceKind,
+    volume: Option<f32>,
+    muted: Option<bool>,
+) -> Result<bool, String> {
+    recorder.set_audio(kind, volume, muted)
+}
+
+// 检查 ffmpeg 是否可用，返回版本信息
+#[command]
+pub async fn check_ffmpeg(ffmpeg_path: Option<String>) -> Result<String, String> {
+    pipeline::run_blocking(move || video::resolve_ffmpeg(ffmpeg_path.as_deref()).map(|(_, version)| version)).await
+}
@@ -0,0 +1,403 @@
+// 动画编码：相邻两帧做差只保留变化的矩形区域（未变化的像素设为透明），
+// GIF 每帧单独量化调色板，APNG 无损保存
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::thread;
+use image::{imageops, RgbaImage};
+
+// GIF 调色板最多 256 色，保留一个索引作为透明色
+const GIF_MAX_COLORS: usize = 255;
+// 浏览器会把小于 2 厘秒的 GIF 帧延时当作 10 厘秒处理
+const GIF_MIN_DELAY_CS: u16 = 2;
+
+// 一帧中发生变化的区域，left / top 为在整个画面中的位置
+pub struct AnimationFrame {
+    pub left: u32,
+    pub top: u32,
+    pub image: RgbaImage,
+    pub timestamp_ms: u64, // 相对于录制开始（不含暂停时间）
+}
+
+impl AnimationFrame {
+    pub fn byte_size(&self) -> usize {
+        self.image.as_raw().len()
+    }
+}
+
+// 与上一帧比较，输出变化区域
+#[derive(Default)]
+pub struct FrameDiffer {
+    previous: Option<RgbaImage>,
+}
+
+impl FrameDiffer {
+    // 画面没有变化时返回 None，上一帧的显示时间会自然延长到下一帧
+    pub fn push(&mut self, frame: RgbaImage, timestamp_ms: u64) -> Option<AnimationFrame> {
+        let previous = match self.previous.take() {
+            Some(previous) if previous.dimensions() == frame.dimensions() => previous,
+            _ => {
+                // 第一帧（或尺寸变化）保存完整画面
+                let full = AnimationFrame { left: 0, top: 0, image: frame.clone(), timestamp_ms };
+                self.previous = Some(frame);
+                return Some(full);
+            }
+        };
+
+        let result = changed_bounds(&previous, &frame).map(|(left, top, width, height)| {
+            let mut image = imageops::crop_imm(&frame, left, top, width, height).to_image();
+            // 与上一帧相同的像素设为透明，GIF / APNG 叠加显示时保留上一帧的内容
+            for (x, y, pixel) in image.enumerate_pixels_mut() {
+                if previous.get_pixel(left + x, top + y) == pixel {
+                    pixel.0 = [0, 0, 0, 0];
+                }
+            }
+            AnimationFrame { left, top, image, timestamp_ms }
+        });
+        self.previous = Some(frame);
+        result
+    }
+}
+
+// 两帧中不同像素的外接矩形
+fn changed_bounds(previous: &RgbaImage, current: &RgbaImage) -> Option<(u32, u32, u32, u32)> {
+    let width = current.width() as usize;
+    let row_bytes = width * 4;
+    let (mut left, mut top, mut right, mut bottom) = (usize::MAX, usize::MAX, 0, 0);
+
+    for (y, (prev_row, row)) in previous
+        .as_raw()
+        .chunks_exact(row_bytes)
+        .zip(current.as_raw().chunks_exact(row_bytes))
+        .enumerate()
+    {
+        if prev_row == row {
+            continue;
+        }
+        let first = prev_row.chunks_exact(4).zip(row.chunks_exact(4)).position(|(a, b)| a != b).unwrap();
+        let last = width - 1
+            - prev_row.chunks_exact(4).rev().zip(row.chunks_exact(4).rev()).position(|(a, b)| a != b).unwrap();
+        left = left.min(first);
+        right = right.max(last);
+        top = top.min(y);
+        bottom = y;
+    }
+
+    (top != usize::MAX).then(|| (left as u32, top as u32, (right - left + 1) as u32, (bottom - top + 1) as u32))
+}
+
+// 每帧的显示时长（毫秒），最后一帧持续到录制结束
+fn frame_durations(frames: &[AnimationFrame], duration_ms: u64) -> Vec<u64> {
+    frames
+        .iter()
+        .enumerate()
+        .map(|(index, frame)| {
+            let end = frames.get(index + 1).map(|next| next.timestamp_ms).unwrap_or(duration_ms);
+            end.saturating_sub(frame.timestamp_ms)
+        })
+        .collect()
+}
+
+// GIF 延时单位为厘秒，按累计时间取整避免误差累积；
+// 单帧最长 u16::MAX 厘秒，超出部分由之后的帧补上
+fn gif_delays(durations: &[u64]) -> Vec<u16> {
+    let mut elapsed_cs = 0u64;
+    let mut elapsed_ms = 0u64;
+    durations
+        .iter()
+        .map(|duration| {
+            elapsed_ms += duration;
+            let end_cs = (elapsed_ms + 5) / 10;
+            let delay = (end_cs.saturating_sub(elapsed_cs).min(u16::MAX as u64) as u16).max(GIF_MIN_DELAY_CS);
+            elapsed_cs += delay as u64;
+            delay
+        })
+        .collect()
+}
+
+// APNG 帧延时（分子，分母）：默认以毫秒为单位，超出 u16 范围时改用厘秒
+fn apng_delay(duration_ms: u64) -> (u16, u16) {
+    if duration_ms <= u16::MAX as u64 {
+        (duration_ms as u16, 1000)
+    } else {
+        ((duration_ms / 10).min(u16::MAX as u64) as u16, 100)
+    }
+}
+
+struct IndexedFrame {
+    palette: Vec<u8>,
+    indices: Vec<u8>,
+    transparent: Option<u8>,
+}
+
+// 量化为不超过 255 色的调色板：颜色数不超过上限时使用精确调色板，否则使用 NeuQuant
+fn quantize(image: &RgbaImage, speed: i32) -> IndexedFrame {
+    let has_transparency = image.pixels().any(|pixel| pixel[3] == 0);
+
+    let mut exact: HashMap<[u8; 3], u8> = HashMap::new();
+    let mut palette = Vec::new();
+    for pixel in image.pixels().filter(|pixel| pixel[3] != 0) {
+        let color = [pixel[0], pixel[1], pixel[2]];
+        if exact.contains_key(&color) {
+            continue;
+        }
+        if exact.len() == GIF_MAX_COLORS {
+            palette.clear();
+            break;
+        }
+        exact.insert(color, exact.len() as u8);
+        palette.extend_from_slice(&color);
+    }
+
+    let (mut palette, indices) = if !palette.is_empty() || exact.is_empty() {
+        let transparent_index = exact.len() as u8;
+        let indices = image
+            .pixels()
+            .map(|pixel| match pixel[3] {
+                0 => transparent_index,
+                _ => exact[&[pixel[0], pixel[1], pixel[2]]],
+            })
+            .collect();
+        (palette, indices)
+    } else {
+        // 只用不透明像素训练，透明像素使用单独的索引
+        let opaque: Vec<u8> = image
+            .pixels()
+            .filter(|pixel| pixel[3] != 0)
+            .flat_map(|pixel| pixel.0)
+            .collect();
+        let quantizer = color_quant::NeuQuant::new(speed, GIF_MAX_COLORS, &opaque);
+        let transparent_index = GIF_MAX_COLORS as u8;
+        let indices = image
+            .pixels()
+            .map(|pixel| match pixel[3] {
+                0 => transparent_index,
+                _ => quantizer.index_of(&pixel.0) as u8,
+            })
+            .collect();
+        (quantizer.color_map_rgb(), indices)
+    };
+
+    let transparent = has_transparency.then(|| {
+        let index = (palette.len() / 3) as u8;
+        palette.extend_from_slice(&[0, 0, 0]);
+        index
+    });
+    IndexedFrame { palette, indices, transparent }
+}
+
+// 编码为 GIF，每完成一帧调用一次 on_progress（阻塞）
+pub fn encode_gif(
+    path: &Path,
+    frames: &[AnimationFrame],
+    width: u32,
+    height: u32,
+    duration_ms: u64,
+    speed: i32,
+    mut on_progress: impl FnMut(usize),
+) -> Result<(), String> {
+    if width > u16::MAX as u32 || height > u16::MAX as u32 {
+        return Err(format!("GIF size {}x{} is too large", width, height));
+    }
+    let file = File::create(path).map_err(|e| format!("Failed to create GIF file: {}", e))?;
+    let mut encoder = gif::Encoder::new(BufWriter::new(file), width as u16, height as u16, &[])
+        .map_err(|e| format!("Failed to create GIF encoder: {}", e))?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(|e| format!("Failed to write GIF header: {}", e))?;
+
+    let delays = gif_delays(&frame_durations(frames, duration_ms));
+
+    // 量化最耗时，按 CPU 核数分批并行量化，再按顺序写入
+    let batch_size = thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+    let speed = speed.clamp(1, 30);
+    let mut written = 0;
+    for batch in frames.chunks(batch_size) {
+        let indexed = thread::scope(|scope| {
+            let workers: Vec<_> = batch
+                .iter()
+                .map(|frame| scope.spawn(move || quantize(&frame.image, speed)))
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().map_err(|_| "GIF quantizer panicked".to_string()))
+                .collect::<Result<Vec<_>, String>>()
+        })?;
+
+        for (frame, indexed) in batch.iter().zip(indexed) {
+            let delay = delays[written];
+            let gif_frame = gif::Frame {
+                left: frame.left as u16,
+                top: frame.top as u16,
+                width: frame.image.width() as u16,
+                height: frame.image.height() as u16,
+                delay,
+                dispose: gif::DisposalMethod::Keep,
+                transparent: indexed.transparent,
+                palette: Some(indexed.palette),
+                buffer: Cow::Owned(indexed.indices),
+                ..gif::Frame::default()
+            };
+            encoder
+                .write_frame(&gif_frame)
+                .map_err(|e| format!("Failed to write GIF frame: {}", e))?;
+            written += 1;
+            on_progress(written);
+        }
+    }
+
+    Ok(())
+}
+
+// 编码为 APNG（无损），每完成一帧调用一次 on_progress（阻塞）
+pub fn encode_apng(
+    path: &Path,
+    frames: &[AnimationFrame],
+    width: u32,
+    height: u32,
+    duration_ms: u64,
+    mut on_progress: impl FnMut(usize),
+) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("Failed to create APNG file: {}", e))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_compression(png::Compression::Fast);
+    encoder
+        .set_animated(frames.len() as u32, 0)
+        .map_err(|e| format!("Failed to configure APNG: {}", e))?;
+    let mut writer = encoder
+        .write_header()
+        .map_err(|e| format!("Failed to write APNG header: {}", e))?;
+
+    let durations = frame_durations(frames, duration_ms);
+    for (index, (frame, duration)) in frames.iter().zip(durations).enumerate() {
+        let configure = |writer: &mut png::Writer<BufWriter<File>>| -> Result<(), png::EncodingError> {
+            // 先移到原点再设置尺寸和位置，避免中间状态越界
+            writer.reset_frame_position()?;
+            writer.set_frame_dimension(frame.image.width(), frame.image.height())?;
+            writer.set_frame_position(frame.left, frame.top)?;
+            let (numerator, denominator) = apng_delay(duration);
+            writer.set_frame_delay(numerator, denominator)?;
+            writer.set_dispose_op(png::DisposeOp::None)?;
+            // 第一帧必须完整覆盖画布，之后的帧叠加在上一帧上（透明像素保留上一帧的内容）
+            writer.set_blend_op(if index == 0 { png::BlendOp::Source } else { png::BlendOp::Over })
+        };
+        configure(&mut writer).map_err(|e| format!("Failed to configure APNG frame: {}", e))?;
+        writer
+            .write_image_data(frame.image.as_raw())
+            .map_err(|e| format!("Failed to write APNG frame: {}", e))?;
+        on_progress(index + 1);
+    }
+
+    writer.finish().map_err(|e| format!("Failed to finish APNG: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn frame_at(timestamp_ms: u64) -> AnimationFrame {
+        AnimationFrame { left: 0, top: 0, image: RgbaImage::new(1, 1), timestamp_ms }
+    }
+
+    #[test]
+    fn differ_keeps_only_changed_region() {
+        let mut differ = FrameDiffer::default();
+        let first = RgbaImage::from_pixel(8, 6, Rgba([10, 20, 30, 255]));
+        let full = differ.push(first.clone(), 0).unwrap();
+        assert_eq!((full.left, full.top, full.image.dimensions()), (0, 0, (8, 6)));
+
+        // 画面不变时没有新帧
+        assert!(differ.push(first.clone(), 100).is_none());
+
+        // 修改 (2,1) 和 (5,3) 两个像素，输出二者的外接矩形，其余像素透明
+        let mut second = first.clone();
+        second.put_pixel(2, 1, Rgba([255, 0, 0, 255]));
+        second.put_pixel(5, 3, Rgba([0, 255, 0, 255]));
+        let diff = differ.push(second, 200).unwrap();
+        assert_eq!((diff.left, diff.top, diff.image.dimensions()), (2, 1, (4, 3)));
+        assert_eq!(diff.timestamp_ms, 200);
+        assert_eq!(diff.image.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+        assert_eq!(diff.image.get_pixel(3, 2), &Rgba([0, 255, 0, 255]));
+        assert_eq!(diff.image.get_pixel(1, 1), &Rgba([0, 0, 0, 0]));
+
+        // 尺寸变化时重新输出完整画面
+        let resized = differ.push(RgbaImage::new(4, 4), 300).unwrap();
+        assert_eq!((resized.left, resized.top, resized.image.dimensions()), (0, 0, (4, 4)));
+    }
+
+    #[test]
+    fn changed_bounds_of_identical_and_edge_pixels() {
+        let base = RgbaImage::from_pixel(5, 4, Rgba([1, 2, 3, 255]));
+        assert_eq!(changed_bounds(&base, &base), None);
+
+        let mut corners = base.clone();
+        corners.put_pixel(0, 0, Rgba([9, 9, 9, 255]));
+        corners.put_pixel(4, 3, Rgba([9, 9, 9, 255]));
+        assert_eq!(changed_bounds(&base, &corners), Some((0, 0, 5, 4)));
+
+        let mut single = base.clone();
+        single.put_pixel(3, 2, Rgba([1, 2, 3, 0]));
+        assert_eq!(changed_bounds(&base, &single), Some((3, 2, 1, 1)));
+    }
+
+    #[test]
+    fn quantize_uses_exact_palette_and_transparent_index() {
+        let mut image = RgbaImage::from_pixel(4, 1, Rgba([255, 0, 0, 255]));
+        image.put_pixel(1, 0, Rgba([0, 0, 255, 255]));
+        image.put_pixel(2, 0, Rgba([0, 0, 0, 0]));
+        let indexed = quantize(&image, 10);
+        assert_eq!(indexed.palette, vec![255, 0, 0, 0, 0, 255, 0, 0, 0]);
+        assert_eq!(indexed.transparent, Some(2));
+        assert_eq!(indexed.indices, vec![0, 1, 2, 0]);
+
+        // 没有透明像素时不保留透明索引
+        let opaque = RgbaImage::from_pixel(2, 2, Rgba([7, 8, 9, 255]));
+        let indexed = quantize(&opaque, 10);
+        assert_eq!(indexed.palette, vec![7, 8, 9]);
+        assert_eq!(indexed.transparent, None);
+    }
+
+    #[test]
+    fn quantize_falls_back_to_neuquant_for_many_colors() {
+        let mut image = RgbaImage::from_fn(32, 32, |x, y| Rgba([(x * 8) as u8, (y * 8) as u8, 128, 255]));
+        image.put_pixel(0, 0, Rgba([0, 0, 0, 0]));
+        let indexed = quantize(&image, 10);
+        assert_eq!(indexed.palette.len(), (GIF_MAX_COLORS + 1) * 3);
+        assert_eq!(indexed.transparent, Some(GIF_MAX_COLORS as u8));
+        assert_eq!(indexed.indices[0], GIF_MAX_COLORS as u8);
+        assert!(indexed.indices[1..].iter().all(|&index| (index as usize) < GIF_MAX_COLORS));
+    }
+
+    #[test]
+    fn frame_durations_extend_last_frame_to_end() {
+        let frames = [frame_at(0), frame_at(120), frame_at(500)];
+        assert_eq!(frame_durations(&frames, 2000), vec![120, 380, 1500]);
+        // 结束时间早于最后一帧时不下溢
+        assert_eq!(frame_durations(&frames[2..], 400), vec![0]);
+    }
+
+    #[test]
+    fn gif_delays_round_cumulatively_and_carry_overflow() {
+        // 33ms 一帧时按累计时间取整，总时长不漂移
+        let delays = gif_delays(&[33, 33, 33, 33, 33, 33]);
+        assert_eq!(delays.iter().map(|&d| d as u64).sum::<u64>(), 20);
+        // 过短的帧使用最小延时
+        assert_eq!(gif_delays(&[5]), vec![GIF_MIN_DELAY_CS]);
+        // 超过 u16 范围时截断，剩余部分由下一帧补上
+        let long = (u16::MAX as u64 + 100) * 10;
+        assert_eq!(gif_delays(&[long, 1000]), vec![u16::MAX, 200]);
+    }
+
+    #[test]
+    fn apng_delay_switches_to_centiseconds_for_long_frames() {
+        assert_eq!(apng_delay(40), (40, 1000));
+        assert_eq!(apng_delay(u16::MAX as u64), (u16::MAX, 1000));
+        assert_eq!(apng_delay(100_000), (10_000, 100));
+        assert_eq!(apng_delay(u64::MAX), (u16::MAX, 100));
+    }
+}
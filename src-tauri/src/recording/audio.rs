@@ -0,0 +1,142 @@
+// 录屏音频：通过 ffmpeg 的 pulse 输入录制系统声音（输出设备的 monitor）和麦克风，
+// PulseAudio 与 PipeWire（pipewire-pulse）均可使用，每个音源单独调节音量和静音后混音
+use serde::{Deserialize, Serialize};
+
+// 音量上限（2 为放大一倍）
+pub const MAX_VOLUME: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioSourceKind {
+    System,     // 系统声音
+    Microphone, // 麦克风
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AudioSource {
+    pub kind: AudioSourceKind,
+    pub device: Option<String>, // PulseAudio 设备名，为空时使用默认输出的 monitor / 默认输入
+    #[serde(default = "default_volume")]
+    pub volume: f32,            // 0-2
+    #[serde(default)]
+    pub muted: bool,            // 静音时仍录制该音轨（静音），便于录制中取消静音后拼接
+}
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+impl AudioSource {
+    pub fn device_name(&self) -> &str {
+        match self.device.as_deref().map(str::trim) {
+            Some(device) if !device.is_empty() => device,
+            _ => match self.kind {
+                AudioSourceKind::System => "@DEFAULT_MONITOR@",
+                AudioSourceKind::Microphone => "@DEFAULT_SOURCE@",
+            },
+        }
+    }
+
+    fn gain(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.volume.clamp(0.0, MAX_VOLUME)
+        }
+    }
+}
+
+// 检查音源配置：每种音源最多一个
+pub fn validate_sources(sources: &[AudioSource]) -> Result<(), String> {
+    for (index, source) in sources.iter().enumerate() {
+        if sources[..index].iter().any(|other| other.kind == source.kind) {
+            return Err(format!("Audio source {:?} is configured more than once", source.kind));
+        }
+        if !source.volume.is_finite() {
+            return Err(format!("Invalid volume for audio source {:?}", source.kind));
+        }
+    }
+    Ok(())
+}
+
+// ffmpeg 输入参数，每个音源一个 pulse 输入
+// pulse 输入的时间戳为系统时间（已减去缓冲延迟），与视频输入的 -itsoffset 对齐
+pub fn input_args(sources: &[AudioSource]) -> Vec<String> {
+    sources
+        .iter()
+        .flat_map(|source| {
+            [
+                "-f".to_string(),
+                "pulse".to_string(),
+                "-thread_queue_size".to_string(),
+                "1024".to_string(),
+                "-i".to_string(),
+                source.device_name().to_string(),
+            ]
+        })
+        .collect()
+}
+
+// 调节各音源音量并混音为 [label]，first_input 为第一个音源的输入序号
+// 需要 ffmpeg 5.0 以上（amix 的 normalize 参数）
+pub fn filter_graph(sources: &[AudioSource], first_input: usize, label: &str) -> String {
+    if let [source] = sources {
+        return format!("[{}:a]volume={:.3}[{}]", first_input, source.gain(), label);
+    }
+    let mut graph: Vec<String> = sources
+        .iter()
+        .enumerate()
+        .map(|(index, source)| format!("[{}:a]volume={:.3}[a{}]", first_input + index, source.gain(), index))
+        .collect();
+    let inputs: String = (0..sources.len()).map(|index| format!("[a{}]", index)).collect();
+    // 不按输入数量降低音量，两路都有声音时可能削波，由用户调节音量
+    graph.push(format!("{}amix=inputs={}:duration=longest:normalize=0[{}]", inputs, sources.len(), label));
+    graph.join(";")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(kind: AudioSourceKind, device: Option<&str>) -> AudioSource {
+        AudioSource { kind, device: device.map(str::to_string), volume: 1.0, muted: false }
+    }
+
+    #[test]
+    fn default_devices() {
+        assert_eq!(source(AudioSourceKind::System, None).device_name(), "@DEFAULT_MONITOR@");
+        assert_eq!(source(AudioSourceKind::Microphone, Some("  ")).device_name(), "@DEFAULT_SOURCE@");
+        assert_eq!(source(AudioSourceKind::Microphone, Some("mic.monitor")).device_name(), "mic.monitor");
+    }
+
+    #[test]
+    fn validates_sources() {
+        let system = source(AudioSourceKind::System, None);
+        let mic = source(AudioSourceKind::Microphone, None);
+        assert!(validate_sources(&[system.clone(), mic]).is_ok());
+        assert!(validate_sources(&[system.clone(), system.clone()]).is_err());
+        let invalid = AudioSource { volume: f32::NAN, ..system };
+        assert!(validate_sources(&[invalid]).is_err());
+    }
+
+    #[test]
+    fn one_pulse_input_per_source() {
+        let sources = [source(AudioSourceKind::System, Some("out.monitor")), source(AudioSourceKind::Microphone, None)];
+        let args = input_args(&sources);
+        assert_eq!(args.len(), 12);
+        assert_eq!(&args[4..6], ["-i", "out.monitor"]);
+        assert_eq!(&args[10..12], ["-i", "@DEFAULT_SOURCE@"]);
+    }
+
+    #[test]
+    fn filter_graph_applies_gain_and_mixes() {
+        let system = AudioSource { volume: 5.0, ..source(AudioSourceKind::System, None) };
+        assert_eq!(filter_graph(std::slice::from_ref(&system), 1, "a"), "[1:a]volume=2.000[a]");
+
+        let mic = AudioSource { muted: true, ..source(AudioSourceKind::Microphone, None) };
+        assert_eq!(
+            filter_graph(&[system, mic], 1, "a"),
+            "[1:a]volume=2.000[a0];[2:a]volume=0.000[a1];[a0][a1]amix=inputs=2:duration=longest:normalize=0[a]"
+        );
+    }
+}
@@ -0,0 +1,122 @@
+// 录屏记录：与截图共用前端维护的数据库文件，录制完成后由 Rust 端写入 recordings 表
+use std::fs;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use tauri::{command, AppHandle};
+
+use crate::screenshot::library::open_db;
+use crate::screenshot::pipeline;
+use super::RecordingFormat;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingRecord {
+    pub id: i64,
+    pub file_path: String,
+    pub format: String,
+    pub width: u32,
+    pub height: u32,
+    pub duration: f64, // 秒
+    pub size_bytes: u64,
+    pub created_at: String,
+}
+
+// 打开数据库并确保 recordings 表存在（结构与前端 initDb.ts 一致）
+fn open_recordings_db(app: &AppHandle) -> Result<Connection, String> {
+    let conn = open_db(app)?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS recordings (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            file_path TEXT NOT NULL,
+            format TEXT NOT NULL,
+            width INTEGER NOT NULL,
+            height INTEGER NOT NULL,
+            duration REAL NOT NULL,
+            size_bytes INTEGER NOT NULL DEFAULT 0,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )",
+    ).map_err(|e| format!("Failed to create recordings table: {}", e))?;
+    Ok(conn)
+}
+
+pub fn insert_recording(
+    app: &AppHandle,
+    file_path: &str,
+    format: RecordingFormat,
+    width: u32,
+    height: u32,
+    duration_ms: u64,
+    size_bytes: u64,
+) -> Result<i64, String> {
+    let conn = open_recordings_db(app)?;
+    conn.execute(
+        "INSERT INTO recordings (file_path, format, width, height, duration, size_bytes)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            file_path,
+            format.extension(),
+            width,
+            height,
+            duration_ms as f64 / 1000.0,
+            size_bytes as i64
+        ],
+    ).map_err(|e| format!("Failed to insert recording: {}", e))?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn list_recording_rows(conn: &Connection) -> Result<Vec<RecordingRecord>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, file_path, format, width, height, duration, size_bytes, created_at
+             FROM recordings ORDER BY created_at DESC, id DESC",
+        )
+        .map_err(|e| format!("Failed to query recordings: {}", e))?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(RecordingRecord {
+                id: row.get(0)?,
+                file_path: row.get(1)?,
+                format: row.get(2)?,
+                width: row.get(3)?,
+                height: row.get(4)?,
+                duration: row.get(5)?,
+                size_bytes: row.get::<_, i64>(6)?.max(0) as u64,
+                created_at: row.get::<_, Option<String>>(7)?.unwrap_or_default(),
+            })
+        })
+        .map_err(|e| format!("Failed to query recordings: {}", e))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read recording row: {}", e))
+}
+
+// 列出所有录屏记录，按时间倒序
+#[command]
+pub async fn list_recordings(app: AppHandle) -> Result<Vec<RecordingRecord>, String> {
+    pipeline::run_blocking(move || {
+        let conn = open_recordings_db(&app)?;
+        list_recording_rows(&conn)
+    })
+    .await
+}
+
+// 删除录屏文件及其记录，文件已不存在时只删除记录
+#[command]
+pub async fn delete_recording(app: AppHandle, id: i64) -> Result<(), String> {
+    pipeline::run_blocking(move || {
+        let conn = open_recordings_db(&app)?;
+        let file_path: String = conn
+            .query_row("SELECT file_path FROM recordings WHERE id = ?1", params![id], |row| row.get(0))
+            .map_err(|e| format!("Recording {} not found: {}", id, e))?;
+
+        match fs::remove_file(&file_path) {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("Failed to delete {}: {}", file_path, e)),
+        }
+        conn.execute("DELETE FROM recordings WHERE id = ?1", params![id])
+            .map_err(|e| format!("Failed to delete recording {}: {}", id, e))?;
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,314 @@
+// 延时视频：把一段时间内的定时截图（screenshots/timed）按时间顺序合成为视频或动画，
+// 可在左下角叠加截图时间和当时使用的应用（来自 app_usage 表的使用记录）
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+use chrono::TimeZone;
+use image::{imageops, RgbaImage};
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle, Emitter, Manager};
+
+use crate::app_monitor::AppMonitor;
+use crate::screenshot::library::open_db;
+use crate::screenshot::{path_to_string, pipeline, timed};
+use super::animation::{self, AnimationFrame, FrameDiffer};
+use super::video::{self, VideoEncoder, VideoOptions};
+use super::{library, overlay, recordings_dir, RecordingFormat, MAX_BUFFERED_BYTES, PROGRESS_INTERVAL_MS};
+
+// 未指定时缩放到该宽度以内，避免 4K 截图生成的动画过大
+const DEFAULT_MAX_WIDTH: u32 = 1280;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TimelapseConfig {
+    pub start: i64, // 毫秒时间戳，含两端
+    pub end: i64,
+    #[serde(default)]
+    pub monitor: usize,
+    #[serde(default)]
+    pub format: RecordingFormat,
+    pub fps: Option<u32>,        // 为空时动画为 10，视频为 30
+    pub speed: Option<f64>,      // 相对实际时间的倍速（如 600 为 10 分钟压缩到 1 秒），为空时每张截图一帧
+    #[serde(default = "default_true")]
+    pub show_timestamp: bool,
+    #[serde(default)]
+    pub show_app: bool,
+    pub max_width: Option<u32>,
+    #[serde(default = "default_gif_speed")]
+    pub gif_speed: i32,
+    pub ffmpeg_path: Option<String>,
+    pub codec: Option<String>,
+    pub crf: Option<u8>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_gif_speed() -> i32 {
+    10
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelapseProgressEvent {
+    pub processed: u32, // 已处理的截图数
+    pub total: u32,
+    pub encoding: bool, // 截图已全部处理，正在编码（动画）或拼接（视频）
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelapseResult {
+    pub id: Option<i64>, // recordings 表中的记录，写入数据库失败时为空
+    pub file_path: String,
+    pub format: RecordingFormat,
+    pub width: u32,
+    pub height: u32,
+    pub screenshots: u32, // 使用的截图数（画面过短被跳过的不计入）
+    pub frames: u32,
+    pub duration_ms: u64,
+    pub size_bytes: u64,
+}
+
+// 一段时间内使用的应用
+struct AppSpan {
+    start_ms: i64,
+    end_ms: i64,
+    name: String,
+}
+
+// 读取与时间范围重叠的应用使用记录，按开始时间排序；app_usage 表由前端创建，不存在时返回空
+fn load_app_usage(app: &AppHandle, start_ms: i64, end_ms: i64) -> Result<Vec<AppSpan>, String> {
+    let conn = open_db(app)?;
+    let table_exists: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'app_usage')",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to query app_usage table: {}", e))?;
+
+    let mut spans = Vec::new();
+    if table_exists {
+        // 使用记录的时间单位为秒
+        let mut stmt = conn
+            .prepare(
+                "SELECT app_name, start_time, end_time FROM app_usage
+                 WHERE end_time >= ?1 AND start_time <= ?2 ORDER BY start_time",
+            )
+            .map_err(|e| format!("Failed to query app usage: {}", e))?;
+        let rows = stmt
+            .query_map([start_ms / 1000, end_ms / 1000 + 1], |row| {
+                Ok(AppSpan {
+                    name: row.get(0)?,
+                    start_ms: row.get::<_, i64>(1)? * 1000,
+                    end_ms: row.get::<_, i64>(2)? * 1000,
+                })
+            })
+            .map_err(|e| format!("Failed to query app usage: {}", e))?;
+        spans = rows
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read app usage row: {}", e))?;
+    }
+
+    // 当前应用还没有写入使用记录，从开始使用到现在
+    if let Some(current) = app.state::<AppMonitor>().get_current_app() {
+        spans.push(AppSpan {
+            start_ms: current.timestamp as i64 * 1000,
+            end_ms: chrono::Local::now().timestamp_millis(),
+            name: current.name,
+        });
+    }
+    Ok(spans)
+}
+
+fn app_at(spans: &[AppSpan], timestamp: i64) -> Option<&str> {
+    // 记录之间可能重叠（切换时的边界），取最后开始的一条
+    let candidates = spans.partition_point(|span| span.start_ms <= timestamp);
+    spans[..candidates]
+        .iter()
+        .rev()
+        .find(|span| timestamp < span.end_ms)
+        .map(|span| span.name.as_str())
+}
+
+// 编码目标：动画缓存变化区域后统一编码，视频直接写入 ffmpeg
+enum Output {
+    Animation { differ: FrameDiffer, frames: Vec<AnimationFrame>, bytes: usize },
+    Video(VideoEncoder),
+}
+
+fn emit_progress(app: &AppHandle, processed: usize, total: usize, encoding: bool) {
+    let _ = app.emit(
+        "timelapse-progress",
+        TimelapseProgressEvent { processed: processed as u32, total: total as u32, encoding },
+    );
+}
+
+fn load_frame(path: &PathBuf, width: u32, height: u32) -> Result<RgbaImage, String> {
+    let image = image::open(path)
+        .map_err(|e| format!("Failed to decode {}: {}", path_to_string(path), e))?
+        .to_rgba8();
+    // 分辨率在期间改变的截图拉伸到统一尺寸
+    Ok(if image.dimensions() == (width, height) {
+        image
+    } else {
+        imageops::resize(&image, width, height, imageops::FilterType::Triangle)
+    })
+}
+
+fn caption(config: &TimelapseConfig, spans: &[AppSpan], timestamp: i64) -> Vec<String> {
+    let mut lines = Vec::new();
+    if config.show_timestamp {
+        if let Some(time) = chrono::Local.timestamp_millis_opt(timestamp).single() {
+            lines.push(time.format("%Y-%m-%d %H:%M:%S").to_string());
+        }
+    }
+    if config.show_app {
+        if let Some(name) = app_at(spans, timestamp) {
+            lines.push(name.to_string());
+        }
+    }
+    lines
+}
+
+fn run_timelapse(app: &AppHandle, config: &TimelapseConfig) -> Result<TimelapseResult, String> {
+    if config.end < config.start {
+        return Err("End time must not be earlier than start time".to_string());
+    }
+    if config.speed.is_some_and(|speed| !speed.is_finite() || speed <= 0.0) {
+        return Err("Speed must be a positive number".to_string());
+    }
+
+    // 只使用保存了图片的记录，画面未变化的心跳记录用来确定最后一张截图的显示时长
+    let events = timed::read_index(app, config.start, config.end, config.monitor)?;
+    let last_timestamp = events.last().map(|event| event.timestamp).unwrap_or(config.start);
+    let shots: Vec<(i64, PathBuf)> = events
+        .into_iter()
+        .filter(|event| event.changed)
+        .filter_map(|event| Some((event.timestamp, PathBuf::from(event.file_path?))))
+        .filter(|(_, path)| path.exists())
+        .collect();
+    let first_timestamp = shots
+        .first()
+        .map(|(timestamp, _)| *timestamp)
+        .ok_or("No timed screenshots found for this monitor in the selected range")?;
+
+    let fps = config.format.fps(config.fps);
+    // 每张截图在输出中的结束位置（帧数），按倍速换算或每张一帧
+    let frame_end = |index: usize| -> u64 {
+        match config.speed {
+            Some(speed) => {
+                let end = shots.get(index + 1).map(|(timestamp, _)| *timestamp).unwrap_or(last_timestamp);
+                let frames = ((end - first_timestamp) as f64 / speed * fps as f64 / 1000.0).round() as u64;
+                // 最后一张至少显示一帧
+                if index + 1 == shots.len() { frames.max(1) } else { frames }
+            }
+            None => index as u64 + 1,
+        }
+    };
+
+    let spans = if config.show_app {
+        load_app_usage(app, config.start, config.end).unwrap_or_else(|e| {
+            eprintln!("读取应用使用记录失败，不显示应用名: {}", e);
+            Vec::new()
+        })
+    } else {
+        Vec::new()
+    };
+
+    // 输出尺寸以第一张截图为准，按最大宽度等比缩小
+    let first = image::open(&shots[0].1)
+        .map_err(|e| format!("Failed to decode {}: {}", path_to_string(&shots[0].1), e))?;
+    let max_width = config.max_width.unwrap_or(DEFAULT_MAX_WIDTH).max(16);
+    let (width, height) = if first.width() > max_width {
+        (max_width, (first.height() as u64 * max_width as u64 / first.width() as u64).max(1) as u32)
+    } else {
+        (first.width(), first.height())
+    };
+    drop(first);
+
+    let path = recordings_dir(app)?.join(format!(
+        "timelapse_{}.{}",
+        chrono::Local::now().format("%Y%m%d_%H%M%S"),
+        config.format.extension()
+    ));
+    let mut output = if config.format.is_video() {
+        let (ffmpeg, _) = video::resolve_ffmpeg(config.ffmpeg_path.as_deref())?;
+        let options = VideoOptions::new(ffmpeg, config.format.container(), config.codec.clone(), config.crf, fps, Vec::new());
+        let file_stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        Output::Video(VideoEncoder::new(options, path.with_file_name(format!(".segments_{}", file_stem)), width, height)?)
+    } else {
+        Output::Animation { differ: FrameDiffer::default(), frames: Vec::new(), bytes: 0 }
+    };
+
+    let mut written = 0u64;
+    let mut used = 0u32;
+    let mut last_progress: Option<Instant> = None;
+    for (index, (timestamp, shot)) in shots.iter().enumerate() {
+        // 按倍速显示时间不足一帧的截图跳过
+        let copies = frame_end(index).saturating_sub(written);
+        if copies > 0 {
+            let mut frame = load_frame(shot, width, height)?;
+            overlay::draw_caption(&mut frame, &caption(config, &spans, *timestamp));
+            match &mut output {
+                Output::Animation { differ, frames, bytes } => {
+                    if let Some(frame) = differ.push(frame, written * 1000 / fps as u64) {
+                        *bytes += frame.byte_size();
+                        frames.push(frame);
+                    }
+                    if *bytes >= MAX_BUFFERED_BYTES {
+                        return Err("Too many frames for an animated image, use MP4 or WebM instead".to_string());
+                    }
+                }
+                Output::Video(encoder) => encoder.write_frame(&frame, copies, SystemTime::now())?,
+            }
+            written += copies;
+            used += 1;
+        }
+
+        if last_progress.is_none_or(|last| last.elapsed() >= Duration::from_millis(PROGRESS_INTERVAL_MS)) {
+            emit_progress(app, index + 1, shots.len(), false);
+            last_progress = Some(Instant::now());
+        }
+    }
+    emit_progress(app, shots.len(), shots.len(), true);
+
+    let duration_ms = written * 1000 / fps as u64;
+    let encoded = match &mut output {
+        Output::Animation { frames, .. } => match config.format {
+            RecordingFormat::Apng => animation::encode_apng(&path, frames, width, height, duration_ms, |_| {}),
+            _ => animation::encode_gif(&path, frames, width, height, duration_ms, config.gif_speed, |_| {}),
+        },
+        // 没有暂停，只有一个分段，任何错误都意味着生成失败
+        Output::Video(encoder) => match encoder.finish(&path) {
+            Ok(None) => Ok(()),
+            Ok(Some(e)) | Err(e) => Err(e),
+        },
+    };
+    if let Err(e) = encoded {
+        let _ = std::fs::remove_file(&path);
+        return Err(e);
+    }
+
+    let file_path = path_to_string(&path);
+    let size_bytes = std::fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0);
+    let id = library::insert_recording(app, &file_path, config.format, width, height, duration_ms, size_bytes)
+        .inspect_err(|e| eprintln!("保存延时视频记录失败: {}", e))
+        .ok();
+
+    Ok(TimelapseResult {
+        id,
+        file_path,
+        format: config.format,
+        width,
+        height,
+        screenshots: used,
+        frames: written as u32,
+        duration_ms,
+        size_bytes,
+    })
+}
+
+// 把时间范围内的定时截图合成为延时视频或动画，进度通过 timelapse-progress 事件推送
+#[command]
+pub async fn create_timelapse(app: AppHandle, config: TimelapseConfig) -> Result<TimelapseResult, String> {
+    pipeline::run_blocking(move || run_timelapse(&app, &config)).await
+}
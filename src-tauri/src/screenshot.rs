@@ -1,14 +1,47 @@
-use std::io::Cursor;
-use std::sync::Mutex;
-use std::collections::HashMap;
 use std::fs;
-use image::{DynamicImage, ImageBuffer, Rgba};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use image::RgbaImage;
 use screenshots::Screen;
 use serde::{Deserialize, Serialize};
 use tauri::{command, AppHandle, Manager};
 use tauri::webview::WebviewWindowBuilder;
 use base64::{engine::general_purpose, Engine};
 
+use crate::app_monitor::AppMonitor;
+
+pub mod timed;
+pub mod library;
+pub mod dedup;
+pub mod retention;
+pub mod store;
+pub mod metadata;
+pub mod report;
+pub mod diff;
+pub mod barcode;
+pub mod color_picker;
+pub mod edit;
+
+pub mod protocol;
+pub mod pipeline;
+pub mod monitors;
+pub mod cursor;
+pub mod delay;
+pub mod scroll;
+pub mod region;
+pub mod window_list;
+pub mod window_capture;
+pub mod backend;
+#[cfg(target_os = "linux")]
+mod xcomposite;
+#[cfg(target_os = "linux")]
+mod xshm;
+
+pub use monitors::MonitorTarget;
+pub use pipeline::CaptureTimings;
+pub use store::{ScreenshotStore, StoredImage};
+pub use metadata::CaptureMetadata;
+
 #[cfg(windows)]
 use windows::Win32::UI::WindowsAndMessaging::GetCursorPos;
 #[cfg(windows)]
@@ -20,16 +53,17 @@ pub struct ScreenshotResult {
     pub width: u32,
     pub height: u32,
     pub file_path: Option<String>, // 保存的文件路径
-}
-
-// 全局状态存储截图数据
-pub struct ScreenshotStore {
-    pub data: Mutex<HashMap<String, ScreenshotResult>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>, // ScreenshotStore 中的 ID，可通过 screenshot://localhost/<id> 加载
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<CaptureTimings>, // 各阶段耗时
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<region::RegionSelection>, // 区域截图的选区（物理像素）
 }
 
 // 获取鼠标所在的屏幕索引
 #[cfg(windows)]
-fn get_cursor_screen(screens: &[Screen]) -> Option<usize> {
+pub fn get_cursor_screen(screens: &[Screen]) -> Option<usize> {
     unsafe {
         let mut point = POINT { x: 0, y: 0 };
         if GetCursorPos(&mut point).is_ok() {
@@ -48,12 +82,37 @@ fn get_cursor_screen(screens: &[Screen]) -> Option<usize> {
     Some(0) // 默认返回主屏幕
 }
 
-// 非 Windows 平台默认返回主屏幕
-#[cfg(not(windows))]
-fn get_cursor_screen(_screens: &[Screen]) -> Option<usize> {
+// Linux 下通过 X11 查询指针位置，查询失败时返回主屏幕
+#[cfg(target_os = "linux")]
+pub fn get_cursor_screen(screens: &[Screen]) -> Option<usize> {
+    cursor::screen_at_cursor(screens).or(Some(0))
+}
+
+// 其他平台默认返回主屏幕
+#[cfg(not(any(windows, target_os = "linux")))]
+pub fn get_cursor_screen(_screens: &[Screen]) -> Option<usize> {
     Some(0)
 }
 
+// 截取指定屏幕，返回 RGBA 图像
+pub fn capture_screen_image(screen: &Screen) -> Result<RgbaImage, String> {
+    backend::capture_screen(screen)
+}
+
+// 将 RGBA 图像编码为 JPEG
+pub fn encode_jpeg(image: &RgbaImage, quality: u8) -> Result<Vec<u8>, String> {
+    let mut buffer = Vec::new();
+    let mut encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut buffer, quality);
+    encoder.encode(
+        image.as_raw(),
+        image.width(),
+        image.height(),
+        image::ColorType::Rgba8
+    ).map_err(|e| format!("Failed to encode image: {}", e))?;
+    
+    Ok(buffer)
+}
+
 #[derive(Debug, Deserialize)]
 pub enum CaptureMode {
     #[serde(rename = "fullscreen")]
@@ -62,135 +121,193 @@ pub enum CaptureMode {
     Window,
     #[serde(rename = "region")]
     Region,
+    #[serde(rename = "scrolling")]
+    Scrolling,
 }
 
+// monitor 为空时截取鼠标所在的显示器，delay_secs 大于 0 时先显示倒计时
+// scrolling 模式使用 scroll 配置，持续截取直到 stop_scroll_capture 或达到上限
+// window 模式截取 window_id 指定的窗口（list_windows 返回的 id），为空时截取焦点窗口
 #[command]
-pub async fn capture_screenshot(mode: CaptureMode) -> Result<ScreenshotResult, String> {
+pub async fn capture_screenshot(
+    app: AppHandle,
+    mode: CaptureMode,
+    monitor: Option<MonitorTarget>,
+    include_cursor: Option<bool>,
+    delay_secs: Option<u32>,
+    scroll: Option<scroll::ScrollCaptureConfig>,
+    window_id: Option<u64>,
+) -> Result<ScreenshotResult, String> {
+    delay::countdown(&app, delay_secs.unwrap_or(0)).await?;
     match mode {
-        CaptureMode::FullScreen => capture_full_screen().await,
-        CaptureMode::Window => capture_window().await,
-        CaptureMode::Region => capture_region().await,
+        CaptureMode::FullScreen => {
+            capture_full_screen(monitor.unwrap_or_default(), include_cursor.unwrap_or(false)).await
+        }
+        CaptureMode::Window => capture_window(window_id).await,
+        CaptureMode::Region => capture_region(&app).await,
+        CaptureMode::Scrolling => {
+            let total_start = Instant::now();
+            let (image, timings) = scroll::capture_scrolling(&app, scroll.unwrap_or_default()).await?;
+            pipeline::run_blocking(move || encode_base64_result(&image, timings, total_start)).await
+        }
     }
 }
 
-async fn capture_full_screen() -> Result<ScreenshotResult, String> {
-    use std::time::Instant;
-    
-    let total_start = Instant::now();
-    println!("=== 截图性能分析 ===");
-    
-    let start = Instant::now();
-    let screens = Screen::all().map_err(|e| format!("Failed to get screens: {}", e))?;
-    println!("1. 获取屏幕列表: {:?}", start.elapsed());
-    
-    if screens.is_empty() {
-        return Err("No screens found".to_string());
-    }
-    
-    // 获取鼠标所在的屏幕
-    let start = Instant::now();
-    let screen_index = get_cursor_screen(&screens).unwrap_or(0);
-    println!("2. 获取鼠标位置: {:?}", start.elapsed());
-    
-    let start = Instant::now();
-    let screen = &screens[screen_index];
-    let screenshot = screen.capture().map_err(|e| format!("Failed to capture screen: {}", e))?;
-    println!("3. 执行屏幕截图: {:?}", start.elapsed());
-    
-    // 转换screenshots::Image到image::DynamicImage
-    let start = Instant::now();
-    let width = screenshot.width();
-    let height = screenshot.height();
-    let buffer = screenshot.rgba();
-    println!("4. 获取图像数据 ({}x{}): {:?}", width, height, start.elapsed());
-    
-    // 创建ImageBuffer (RGBA格式)
-    let start = Instant::now();
-    let image_buffer = ImageBuffer::<Rgba<u8>, Vec<u8>>::from_raw(
-        width, 
-        height, 
-        buffer.to_vec()
-    ).ok_or("Failed to create image buffer")?;
-    
-    let dynamic_image = DynamicImage::ImageRgba8(image_buffer);
-    println!("5. 创建ImageBuffer: {:?}", start.elapsed());
-    
-    // 转换为base64
-    let start = Instant::now();
-    let mut buffer = Vec::new();
-    let mut cursor = Cursor::new(&mut buffer);
-    
-    // 使用JPEG格式，质量50（预览足够，速度更快）
-    let mut encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut cursor, 50);
-    encoder.encode(
-        dynamic_image.as_bytes(),
-        width,
-        height,
-        dynamic_image.color()
-    ).map_err(|e| format!("Failed to encode image: {}", e))?;
-    println!("6. JPEG编码 质量50 (数据大小: {} KB): {:?}", buffer.len() / 1024, start.elapsed());
+async fn capture_full_screen(target: MonitorTarget, include_cursor: bool) -> Result<ScreenshotResult, String> {
+    pipeline::run_blocking(move || {
+        let total_start = Instant::now();
+        let pipeline::Capture { image, timings, .. } = monitors::capture_target(target, include_cursor)?;
+        encode_base64_result(&image, timings, total_start)
+    })
+    .await
+}
+
+// 编码为 base64 预览结果，不保存文件（阻塞）
+fn encode_base64_result(
+    image: &RgbaImage,
+    mut timings: CaptureTimings,
+    total_start: Instant,
+) -> Result<ScreenshotResult, String> {
+    let outputs = pipeline::encode_outputs(image, false, &mut timings)?;
     
     let start = Instant::now();
-    let base64_data = general_purpose::STANDARD.encode(&buffer);
-    println!("7. Base64编码 (编码后: {} KB): {:?}", base64_data.len() / 1024, start.elapsed());
-    
-    println!("=== 总耗时: {:?} ===\n", total_start.elapsed());
+    let base64_data = general_purpose::STANDARD.encode(&outputs.preview);
+    timings.base64_ms = Some(pipeline::elapsed_ms(start));
+    timings.total_ms = pipeline::elapsed_ms(total_start);
     
     Ok(ScreenshotResult {
         data: base64_data,
-        width,
-        height,
+        width: image.width(),
+        height: image.height(),
         file_path: None,
+        id: None,
+        timings: Some(timings),
+        region: None,
     })
 }
 
-// 保存截图到文件系统
-#[command]
-pub async fn save_screenshot_to_file(
-    app: AppHandle,
-    image_data: String,
-) -> Result<String, String> {
-    // 获取应用数据目录
+// 获取截图保存目录（app_data_dir/screenshots），不存在时自动创建
+pub fn screenshots_dir(app: &AppHandle) -> Result<PathBuf, String> {
     let app_data_dir = app.path().app_data_dir()
         .map_err(|e| format!("Failed to get app data dir: {}", e))?;
     
-    // 创建 screenshots 目录
     let screenshots_dir = app_data_dir.join("screenshots");
     fs::create_dir_all(&screenshots_dir)
         .map_err(|e| format!("Failed to create screenshots directory: {}", e))?;
     
-    // 生成文件名（使用时间戳）
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-    let filename = format!("screenshot_{}.jpg", timestamp);
-    let file_path = screenshots_dir.join(&filename);
-    
+    Ok(screenshots_dir)
+}
+
+// 路径转为字符串（使用正斜杠，适配前端）
+pub fn path_to_string(path: &Path) -> String {
+    path.to_string_lossy().replace("\\", "/")
+}
+
+// 截图上下文：截图时间（本地时区）、显示器、截图模式、当前前台应用及窗口标题、应用版本
+// 在截图前调用，避免记录到截图过程中显示的遮罩或贴图窗口
+pub fn capture_metadata(app: &AppHandle, mode: Option<&str>, monitor: Option<String>) -> CaptureMetadata {
+    let package = app.package_info();
+    let current_app = app.state::<AppMonitor>().get_current_app();
+    CaptureMetadata {
+        captured_at: Some(chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false)),
+        monitor,
+        mode: mode.map(str::to_string),
+        app_name: current_app.as_ref().map(|info| info.name.clone()),
+        window_title: current_app.map(|info| info.window_title).filter(|title| !title.is_empty()),
+        app_version: Some(package.version.to_string()),
+        software: Some(format!("{} {}", package.name, package.version)),
+    }
+}
+
+// 显示器序号转为元数据中的文本，拼接全部显示器时为 all
+pub fn monitor_label(monitor: Option<usize>) -> String {
+    monitor.map_or_else(|| "all".to_string(), |index| index.to_string())
+}
+
+// 保存截图到文件系统，写入截图元数据（mode 与 monitor 由前端提供，为空时不记录）
+#[command]
+pub async fn save_screenshot_to_file(
+    app: AppHandle,
+    image_data: String,
+    mode: Option<String>,
+    monitor: Option<String>,
+) -> Result<String, String> {
     // 解码 base64 数据
     let image_bytes = general_purpose::STANDARD
         .decode(&image_data)
         .map_err(|e| format!("Failed to decode base64: {}", e))?;
+
+    let metadata = capture_metadata(&app, mode.as_deref(), monitor);
+    pipeline::run_blocking(move || write_screenshot_file(&app, &image_bytes, &metadata, None)).await
+}
+
+// 将已编码的 JPEG / PNG 数据写入文件并嵌入元数据，返回文件路径
+// location 为（目录，不含扩展名的文件名），为空时保存到 screenshots 目录并按时间命名
+pub fn write_screenshot_file(
+    app: &AppHandle,
+    image_bytes: &[u8],
+    metadata: &CaptureMetadata,
+    location: Option<(&Path, &str)>,
+) -> Result<String, String> {
+    let (dir, file_stem) = match location {
+        Some((dir, file_stem)) => (dir.to_path_buf(), file_stem.to_string()),
+        None => {
+            let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+            (screenshots_dir(app)?, format!("screenshot_{}", timestamp))
+        }
+    };
+
+    // 扩展名按实际格式
+    let extension = metadata::image_extension(image_bytes).unwrap_or("jpg");
+    let file_path = dir.join(format!("{}.{}", file_stem, extension));
+    
+    // 元数据写入失败时仍保存原图
+    let image_bytes = match metadata::embed(image_bytes, metadata) {
+        Ok(bytes) => std::borrow::Cow::Owned(bytes),
+        Err(e) => {
+            eprintln!("写入截图元数据失败: {}", e);
+            std::borrow::Cow::Borrowed(image_bytes)
+        }
+    };
     
     // 保存文件
     fs::write(&file_path, image_bytes)
         .map_err(|e| format!("Failed to write file: {}", e))?;
     
     // 返回文件路径（使用正斜杠，适配前端）
-    let path_str = file_path
-        .to_string_lossy()
-        .replace("\\", "/");
+    let path_str = path_to_string(&file_path);
     
     println!("截图已保存到: {}", path_str);
     
     Ok(path_str)
 }
 
-async fn capture_window() -> Result<ScreenshotResult, String> {
-    // 目前先实现为全屏截图，后续可以添加窗口选择功能
-    capture_full_screen().await
+// 读取图片文件中的元数据（本应用写入的截图信息及 PNG 文本块、EXIF、XMP 中的文本字段）
+#[command]
+pub async fn read_image_metadata(file_path: String) -> Result<metadata::ImageMetadata, String> {
+    pipeline::run_blocking(move || {
+        let bytes = fs::read(&file_path).map_err(|e| format!("Failed to read {}: {}", file_path, e))?;
+        metadata::read(&bytes)
+    })
+    .await
+}
+
+async fn capture_window(window_id: Option<u64>) -> Result<ScreenshotResult, String> {
+    pipeline::run_blocking(move || {
+        let total_start = Instant::now();
+        let (image, _, timings) = window_capture::capture_window_image(window_id)?;
+        encode_base64_result(&image, timings, total_start)
+    })
+    .await
 }
 
-async fn capture_region() -> Result<ScreenshotResult, String> {
-    // 目前先实现为全屏截图，后续可以添加区域选择功能
-    capture_full_screen().await
+// 冻结画面后由用户框选，返回从冻结画面中裁剪的区域
+async fn capture_region(app: &AppHandle) -> Result<ScreenshotResult, String> {
+    let total_start = Instant::now();
+    let (image, selection, timings) = region::select_region(app).await?;
+    let mut result = pipeline::run_blocking(move || encode_base64_result(&image, timings, total_start)).await?;
+    result.region = Some(selection);
+    Ok(result)
 }
 
 #[command]
@@ -225,9 +342,26 @@ pub async fn open_screenshot_window(
         width,
         height,
         file_path: None,
+        id: None,
+        timings: None,
+        region: None,
     };
     
-    store.data.lock().unwrap().insert(window_id.clone(), screenshot_result);
+    store.insert_base64(window_id.clone(), screenshot_result)?;
+    
+    show_screenshot_window(&app, &window_id, width, height)?;
+    
+    Ok(window_id)
+}
+
+// 为已存入 ScreenshotStore 的截图创建贴图窗口，窗口 label 与存储 ID 相同
+pub fn show_screenshot_window(
+    app: &AppHandle,
+    window_id: &str,
+    width: u32,
+    height: u32,
+) -> Result<(), String> {
+    let store = app.state::<ScreenshotStore>();
     
     // 计算窗口大小，保持图片比例
     let aspect_ratio = width as f64 / height as f64;
@@ -247,7 +381,7 @@ pub async fn open_screenshot_window(
     let url = format!("/screenshot-window?id={}", window_id);
     
     // 创建新窗口，设置固定比例
-    WebviewWindowBuilder::new(&app, &window_id, tauri::WebviewUrl::App(url.into()))
+    let window = WebviewWindowBuilder::new(app, window_id, tauri::WebviewUrl::App(url.into()))
         .title("截图预览")
         .inner_size(window_width, window_height)
         .min_inner_size(200.0, 200.0 / aspect_ratio)
@@ -257,9 +391,23 @@ pub async fn open_screenshot_window(
         .always_on_top(true)
         .center()
         .build()
-        .map_err(|e| format!("Failed to create window: {}", e))?;
+        .map_err(|e| {
+            store.remove(window_id);
+            format!("Failed to create window: {}", e)
+        })?;
+    
+    // 窗口销毁时释放对应的截图数据和编辑结果
+    let app_handle = app.clone();
+    let id = window_id.to_string();
+    window.on_window_event(move |event| {
+        if let tauri::WindowEvent::Destroyed = event {
+            let store = app_handle.state::<ScreenshotStore>();
+            store.remove(&id);
+            edit::release_edits(&store, &id);
+        }
+    });
     
-    Ok(window_id)
+    Ok(())
 }
 
 // 获取截图数据
@@ -269,46 +417,101 @@ pub async fn get_screenshot_data(
     window_id: String,
 ) -> Result<ScreenshotResult, String> {
     let store = app.state::<ScreenshotStore>();
-    let data = store.data.lock().unwrap();
     
-    data.get(&window_id)
-        .cloned()
+    store.get(&window_id)
+        .map(|image| image.to_result(&window_id))
         .ok_or_else(|| "Screenshot data not found".to_string())
 }
 
-// 触发截图(用于全局快捷键)
-pub async fn trigger_screenshot(app: AppHandle) -> Result<(), String> {
-    // 使用统一的 capture_and_show，确保截图被保存并显示
-    let _result = capture_and_show(app).await?;
+// 触发截图(用于全局快捷键)，delay_secs 大于 0 时为延时截图
+// 倒计时或区域选择进行中再次触发则取消
+pub async fn trigger_screenshot(app: AppHandle, delay_secs: u32) -> Result<(), String> {
+    if app.state::<delay::DelayedCapture>().is_pending() {
+        delay::cancel_delayed_capture(app.clone(), app.state())?;
+        return Ok(());
+    }
+    // 区域选择进行中再次触发则取消选择
+    if app.state::<region::RegionSelector>().is_active() {
+        region::cancel_region_selection(app.state())?;
+        return Ok(());
+    }
+
+    // 冻结画面并框选区域，完成后保存并显示
+    delay::countdown(&app, delay_secs).await?;
+    let _result = region::region_capture_and_show(app).await?;
     
     Ok(())
 }
 
 // 优化的截图命令：直接截图并显示，避免数据在前后端之间传输两次
+// 图片数据只保存在 ScreenshotStore 中，返回结果的 data 为空，前端通过 screenshot:// 协议按 id 加载
+// 截图与编码在阻塞线程池中完成，预览图（贴图窗口）与存档图（写入文件）并行编码
+// monitor 可指定显示器或拼接全部显示器，为空时截取鼠标所在的显示器
+// include_cursor 为 true 时在截图中绘制鼠标指针（用于教程类截图）
+// delay_secs 大于 0 时先显示倒计时，倒计时窗口关闭后再截图，耗时统计不包含倒计时
 #[command]
-pub async fn capture_and_show(app: AppHandle) -> Result<ScreenshotResult, String> {
-    use std::time::Instant;
-    
+pub async fn capture_and_show(
+    app: AppHandle,
+    monitor: Option<MonitorTarget>,
+    include_cursor: Option<bool>,
+    delay_secs: Option<u32>,
+) -> Result<ScreenshotResult, String> {
+    delay::countdown(&app, delay_secs.unwrap_or(0)).await?;
     let total_start = Instant::now();
-    println!("\n>>> 开始执行 capture_and_show");
+    let mut metadata = capture_metadata(&app, Some("fullscreen"), None);
     
-    // 执行截图
-    let start = Instant::now();
-    let mut result = capture_full_screen().await?;
-    println!(">>> 截图完成，耗时: {:?}", start.elapsed());
+    let capture = pipeline::run_blocking(move || {
+        let target = monitor.unwrap_or_default();
+        monitors::capture_target(target, include_cursor.unwrap_or(false))
+    })
+    .await?;
+    metadata.monitor = Some(monitor_label(capture.monitor));
     
-    // 保存到文件系统
-    let start = Instant::now();
-    let file_path = save_screenshot_to_file(app.clone(), result.data.clone()).await?;
-    result.file_path = Some(file_path);
-    println!(">>> 文件保存完成，耗时: {:?}", start.elapsed());
+    save_and_show(&app, capture.image, capture.timings, total_start, metadata).await
+}
+
+// 编码并保存截图，存入 ScreenshotStore 后直接打开贴图窗口
+// 预览图（贴图窗口）与存档图（写入文件）并行编码，返回结果的 data 为空，metadata 写入存档文件
+pub async fn save_and_show(
+    app: &AppHandle,
+    image: RgbaImage,
+    timings: CaptureTimings,
+    total_start: Instant,
+    metadata: CaptureMetadata,
+) -> Result<ScreenshotResult, String> {
+    let worker_app = app.clone();
+    let (outputs, file_path, width, height, mut timings) = pipeline::run_blocking(move || {
+        let mut timings = timings;
+        let outputs = pipeline::encode_outputs(&image, true, &mut timings)?;
+        
+        // 保存到文件系统
+        let start = Instant::now();
+        let archive = outputs.archive.as_deref().unwrap_or(&outputs.preview);
+        let file_path = write_screenshot_file(&worker_app, archive, &metadata, None)?;
+        timings.save_ms = Some(pipeline::elapsed_ms(start));
+        
+        Ok((outputs, file_path, image.width(), image.height(), timings))
+    })
+    .await?;
     
-    // 直接打开窗口显示截图，返回窗口ID
+    // 存入 ScreenshotStore 并直接打开窗口显示截图
     let start = Instant::now();
-    let _window_id = open_screenshot_window(app, result.data.clone(), result.width, result.height).await?;
-    println!(">>> 窗口创建完成，耗时: {:?}", start.elapsed());
-    
-    println!(">>> capture_and_show 总耗时: {:?}\n", total_start.elapsed());
+    let window_id = format!("screenshot_{}", chrono::Utc::now().timestamp_millis());
+    app.state::<ScreenshotStore>().insert(
+        window_id.clone(),
+        StoredImage::new(outputs.preview, width, height, Some(file_path.clone())),
+    );
+    show_screenshot_window(app, &window_id, width, height)?;
+    timings.window_ms = Some(pipeline::elapsed_ms(start));
+    timings.total_ms = pipeline::elapsed_ms(total_start);
     
-    Ok(result)
+    Ok(ScreenshotResult {
+        data: String::new(),
+        width,
+        height,
+        file_path: Some(file_path),
+        id: Some(window_id),
+        timings: Some(timings),
+        region: None,
+    })
 }
@@ -0,0 +1,183 @@
+// 截图后端：Linux X11 下优先使用 MIT-SHM 共享内存截图，每个显示器的连接和共享内存段在多次截图之间复用，
+// 扩展不可用（远程 X、Wayland）或截图失败时回退到 screenshots crate
+// 两种后端的耗时对比见 xshm.rs 中被忽略的测试（cargo test -- --ignored --nocapture shm_capture）
+use image::RgbaImage;
+use screenshots::Screen;
+use serde::Serialize;
+use tauri::command;
+
+use super::monitors::CaptureRegion;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureBackend {
+    Xshm,
+    Screenshots,
+}
+
+#[cfg(target_os = "linux")]
+mod shm {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use crate::screenshot::xshm::ShmCapturer;
+
+    enum ScreenShm {
+        Ready(Arc<Mutex<ShmCapturer>>),
+        Failed, // 该显示器截图失败过，之后一直使用普通截图
+    }
+
+    enum ShmState {
+        Untried,
+        Ready(HashMap<u32, ScreenShm>),
+        Unavailable, // 扩展不可用，之后不再尝试
+    }
+
+    // 每个显示器使用独立的连接和共享内存段，不同显示器可以并行截图，全局锁只在查找时持有
+    static SHM: Mutex<ShmState> = Mutex::new(ShmState::Untried);
+
+    // 与 screenshots crate 的判断一致，Wayland 会话下不使用 X11 截图
+    fn is_wayland() -> bool {
+        std::env::var("XDG_SESSION_TYPE").is_ok_and(|value| value == "wayland")
+            || std::env::var("WAYLAND_DISPLAY").is_ok_and(|value| value.to_lowercase().contains("wayland"))
+    }
+
+    // 取得显示器对应的截图器，首次使用时建立连接
+    fn capturer(screen_id: u32) -> Option<Arc<Mutex<ShmCapturer>>> {
+        let mut state = SHM.lock().unwrap();
+        if let ShmState::Untried = *state {
+            *state = if is_wayland() { ShmState::Unavailable } else { ShmState::Ready(HashMap::new()) };
+        }
+        let ShmState::Ready(screens) = &mut *state else {
+            return None;
+        };
+
+        if let Some(screen) = screens.get(&screen_id) {
+            return match screen {
+                ScreenShm::Ready(capturer) => Some(capturer.clone()),
+                ScreenShm::Failed => None,
+            };
+        }
+        match ShmCapturer::connect() {
+            Ok(capturer) => {
+                let capturer = Arc::new(Mutex::new(capturer));
+                screens.insert(screen_id, ScreenShm::Ready(capturer.clone()));
+                Some(capturer)
+            }
+            Err(e) => {
+                eprintln!("MIT-SHM 不可用，使用普通截图: {}", e);
+                *state = ShmState::Unavailable;
+                None
+            }
+        }
+    }
+
+    // 使用显示器的共享内存截图，不可用时返回 None
+    pub fn with_capturer<T>(screen_id: u32, task: impl FnOnce(&mut ShmCapturer) -> Result<T, String>) -> Option<T> {
+        let capturer = capturer(screen_id)?;
+        let result = task(&mut capturer.lock().unwrap());
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                // 不再重连重试，该显示器之后都回退到普通截图
+                eprintln!("MIT-SHM 截图失败，显示器 {} 改用普通截图: {}", screen_id, e);
+                if let ShmState::Ready(screens) = &mut *SHM.lock().unwrap() {
+                    screens.insert(screen_id, ScreenShm::Failed);
+                }
+                None
+            }
+        }
+    }
+
+    pub fn is_available(screen_id: u32) -> bool {
+        with_capturer(screen_id, |_| Ok(())).is_some()
+    }
+}
+
+// 显示器在根窗口上的物理像素区域，与 screenshots crate 的换算方式一致
+#[cfg(target_os = "linux")]
+fn physical_rect(screen: &Screen, x: i32, y: i32, width: u32, height: u32) -> (i32, i32, u32, u32) {
+    let info = &screen.display_info;
+    let scale = info.scale_factor;
+    (
+        ((x + info.x) as f32 * scale) as i32,
+        ((y + info.y) as f32 * scale) as i32,
+        (width as f32 * scale) as u32,
+        (height as f32 * scale) as u32,
+    )
+}
+
+fn to_rgba(screenshot: screenshots::Image) -> Result<RgbaImage, String> {
+    // 直接接管 screenshots::Image 的像素缓冲区，不再复制一份 RGBA 数据
+    let (width, height) = (screenshot.width(), screenshot.height());
+    RgbaImage::from_raw(width, height, screenshot.into()).ok_or_else(|| "Failed to create image buffer".to_string())
+}
+
+// 截取整个显示器（阻塞）
+pub fn capture_screen(screen: &Screen) -> Result<RgbaImage, String> {
+    #[cfg(target_os = "linux")]
+    {
+        let info = &screen.display_info;
+        let (x, y, width, height) = physical_rect(screen, 0, 0, info.width, info.height);
+        if let Some(image) = shm::with_capturer(screen.display_info.id, |capturer| capturer.capture(x, y, width, height)) {
+            return Ok(image);
+        }
+    }
+
+    let screenshot = screen.capture().map_err(|e| format!("Failed to capture screen: {}", e))?;
+    to_rgba(screenshot)
+}
+
+// 截取显示器内的区域，region 为相对于显示器的逻辑坐标（阻塞）
+pub fn capture_area(screen: &Screen, region: &CaptureRegion) -> Result<RgbaImage, String> {
+    #[cfg(target_os = "linux")]
+    {
+        let (x, y, width, height) = physical_rect(screen, region.x, region.y, region.width, region.height);
+        if let Some(image) = shm::with_capturer(screen.display_info.id, |capturer| capturer.capture(x, y, width, height)) {
+            return Ok(image);
+        }
+    }
+
+    let screenshot = screen
+        .capture_area(region.x, region.y, region.width, region.height)
+        .map_err(|e| format!("Failed to capture region: {}", e))?;
+    to_rgba(screenshot)
+}
+
+// 截取整个显示器或其中的区域写入 out，MIT-SHM 后端复用 out 的缓冲区（用于录屏等连续截图，阻塞）
+pub fn capture_into(screen: &Screen, region: Option<&CaptureRegion>, out: &mut RgbaImage) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    {
+        let info = &screen.display_info;
+        let (x, y, width, height) = match region {
+            Some(region) => physical_rect(screen, region.x, region.y, region.width, region.height),
+            None => physical_rect(screen, 0, 0, info.width, info.height),
+        };
+        if shm::with_capturer(screen.display_info.id, |capturer| capturer.capture_into(x, y, width, height, out)).is_some() {
+            return Ok(());
+        }
+    }
+
+    *out = match region {
+        Some(region) => capture_area(screen, region)?,
+        None => capture_screen(screen)?,
+    };
+    Ok(())
+}
+
+pub fn active_backend() -> CaptureBackend {
+    // 以主显示器的截图方式为准
+    #[cfg(target_os = "linux")]
+    if let Ok(screens) = Screen::all() {
+        let primary = screens.iter().find(|screen| screen.display_info.is_primary).or(screens.first());
+        if primary.is_some_and(|screen| shm::is_available(screen.display_info.id)) {
+            return CaptureBackend::Xshm;
+        }
+    }
+    CaptureBackend::Screenshots
+}
+
+// 当前使用的截图后端
+#[command]
+pub async fn get_capture_backend() -> Result<CaptureBackend, String> {
+    super::pipeline::run_blocking(|| Ok(active_backend())).await
+}
@@ -0,0 +1,159 @@
+// 二维码与条码识别：在内存中的截图（贴图窗口）或截图文件里查找 QR 码和常见一维条码
+// （EAN-13 / UPC-A、EAN-8、Code 128、Code 39），返回内容和位置，完全离线
+// 可只识别截图中的一个区域，返回的坐标始终相对于整张截图
+pub mod linear;
+pub mod qr;
+
+use image::{imageops, GrayImage};
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle, Manager};
+
+use super::pipeline;
+use super::store::ScreenshotStore;
+use linear::LinearFormat;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CodeFormat {
+    Qr,
+    Ean13,
+    Ean8,
+    UpcA,
+    Code128,
+    Code39,
+}
+
+impl From<LinearFormat> for CodeFormat {
+    fn from(format: LinearFormat) -> Self {
+        match format {
+            LinearFormat::Ean13 => CodeFormat::Ean13,
+            LinearFormat::Ean8 => CodeFormat::Ean8,
+            LinearFormat::UpcA => CodeFormat::UpcA,
+            LinearFormat::Code128 => CodeFormat::Code128,
+            LinearFormat::Code39 => CodeFormat::Code39,
+        }
+    }
+}
+
+// 截图中的区域，物理像素
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ScanRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodedCode {
+    pub format: CodeFormat,
+    pub text: String,
+    pub bounds: ScanRegion,
+    pub corners: [[f32; 2]; 4], // 左上、右上、右下、左下，QR 码倾斜时与 bounds 不同
+    pub version: Option<u8>,    // QR 码版本
+    pub ec_level: Option<char>, // QR 码纠错等级
+}
+
+// 读取截图：优先 ScreenshotStore 中的记录，其次文件
+fn load_image(app: &AppHandle, id: Option<&str>, file_path: Option<&str>) -> Result<GrayImage, String> {
+    let decoded = match (id, file_path) {
+        (Some(id), _) => {
+            let stored = app
+                .state::<ScreenshotStore>()
+                .get(id)
+                .ok_or_else(|| format!("Screenshot {} is not in memory", id))?;
+            image::load_from_memory(&stored.bytes).map_err(|e| format!("Failed to decode screenshot {}: {}", id, e))?
+        }
+        (None, Some(path)) => image::open(path).map_err(|e| format!("Failed to decode {}: {}", path, e))?,
+        (None, None) => return Err("Either a screenshot id or a file path is required".to_string()),
+    };
+    Ok(decoded.to_luma8())
+}
+
+fn offset_point((x, y): (f32, f32), region: &ScanRegion) -> [f32; 2] {
+    [x + region.x as f32, y + region.y as f32]
+}
+
+// 识别图片（或其中一个区域）中的所有码（阻塞）
+pub fn scan_image(image: &GrayImage, region: Option<ScanRegion>) -> Result<Vec<DecodedCode>, String> {
+    let region = match region {
+        Some(region) => {
+            let x = region.x.min(image.width());
+            let y = region.y.min(image.height());
+            let width = region.width.min(image.width() - x);
+            let height = region.height.min(image.height() - y);
+            if width == 0 || height == 0 {
+                return Err("Scan region is outside the screenshot".to_string());
+            }
+            ScanRegion { x, y, width, height }
+        }
+        None => ScanRegion { x: 0, y: 0, width: image.width(), height: image.height() },
+    };
+    let cropped = imageops::crop_imm(image, region.x, region.y, region.width, region.height).to_image();
+
+    let mut codes = Vec::new();
+    for code in qr::decode(&cropped) {
+        let corners = code.corners.map(|corner| offset_point(corner, &region));
+        let min_x = corners.iter().map(|corner| corner[0]).fold(f32::MAX, f32::min).max(0.0);
+        let min_y = corners.iter().map(|corner| corner[1]).fold(f32::MAX, f32::min).max(0.0);
+        let max_x = corners.iter().map(|corner| corner[0]).fold(f32::MIN, f32::max);
+        let max_y = corners.iter().map(|corner| corner[1]).fold(f32::MIN, f32::max);
+        codes.push(DecodedCode {
+            format: CodeFormat::Qr,
+            text: code.text,
+            bounds: ScanRegion {
+                x: min_x.floor() as u32,
+                y: min_y.floor() as u32,
+                width: (max_x - min_x).ceil().max(1.0) as u32,
+                height: (max_y - min_y).ceil().max(1.0) as u32,
+            },
+            corners,
+            version: Some(code.version),
+            ec_level: Some(code.ec_level),
+        });
+    }
+    for code in linear::decode(&cropped) {
+        let (x0, y0) = ((code.x + region.x) as f32, (code.y + region.y) as f32);
+        let (x1, y1) = (x0 + code.width as f32, y0 + code.height as f32);
+        codes.push(DecodedCode {
+            format: code.format.into(),
+            text: code.text,
+            bounds: ScanRegion { x: x0 as u32, y: y0 as u32, width: code.width, height: code.height },
+            corners: [[x0, y0], [x1, y0], [x1, y1], [x0, y1]],
+            version: None,
+            ec_level: None,
+        });
+    }
+    // 按位置从上到下、从左到右排列
+    codes.sort_by_key(|code| (code.bounds.y, code.bounds.x));
+    Ok(codes)
+}
+
+// 识别截图中的二维码和条码，id 为 ScreenshotStore 中的截图，未指定时读取 file_path
+#[command]
+pub async fn scan_codes(
+    app: AppHandle,
+    id: Option<String>,
+    file_path: Option<String>,
+    region: Option<ScanRegion>,
+) -> Result<Vec<DecodedCode>, String> {
+    pipeline::run_blocking(move || {
+        let image = load_image(&app, id.as_deref(), file_path.as_deref())?;
+        scan_image(&image, region)
+    })
+    .await
+}
+
+// 测试用的模拟界面背景：浅色窗口、深色侧栏、文字行
+#[cfg(test)]
+fn test_background(width: u32, height: u32) -> GrayImage {
+    GrayImage::from_fn(width, height, |x, y| {
+        if x < 80 {
+            image::Luma([45])
+        } else if y % 28 < 10 && x % 150 > 100 && (x / 3 + y) % 4 != 0 {
+            image::Luma([60])
+        } else {
+            image::Luma([240])
+        }
+    })
+}
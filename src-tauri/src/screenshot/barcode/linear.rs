@@ -0,0 +1,646 @@
+// 一维条码识别：逐行（和逐列，识别竖排条码）按局部阈值分成深浅段，在段序列中匹配
+// EAN-13 / UPC-A、EAN-8、Code 128 和 Code 39，正反两个方向都尝试；多条扫描线结果一致时才算识别成功
+use image::GrayImage;
+use serde::Serialize;
+
+// 每隔几行 / 几列扫描一次
+const SCAN_STEP: u32 = 2;
+// 局部阈值的分块大小，取左右各 2 块的最小值和最大值
+const BLOCK_SIZE: usize = 16;
+const MIN_CONTRAST: u8 = 40;
+// 条码两侧的空白区至少为该数量的模块宽度（屏幕上的条码空白区常被裁窄）
+const QUIET_MODULES: f32 = 3.0;
+// 单个字符与编码表的最大平均偏差（以模块为单位）
+const MAX_VARIANCE: f32 = 0.38;
+// 至少被这么多条扫描线识别为同样内容
+const MIN_HITS: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinearFormat {
+    Ean13,
+    Ean8,
+    UpcA,
+    Code128,
+    Code39,
+}
+
+#[derive(Debug, Clone)]
+pub struct LinearCode {
+    pub format: LinearFormat,
+    pub text: String,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+// EAN 的 L 码（浅深浅深），G 码为其倒序，R 码宽度与 L 码相同（深浅深浅）
+const EAN_DIGITS: [[u8; 4]; 10] = [
+    [3, 2, 1, 1],
+    [2, 2, 2, 1],
+    [2, 1, 2, 2],
+    [1, 4, 1, 1],
+    [1, 1, 3, 2],
+    [1, 2, 3, 1],
+    [1, 1, 1, 4],
+    [1, 3, 1, 2],
+    [1, 2, 1, 3],
+    [3, 1, 1, 2],
+];
+
+// EAN-13 首位数字决定左侧 6 位的 L / G 组合（位为 1 表示 G 码，最高位对应第 1 位）
+const EAN_FIRST_DIGIT: [u8; 10] = [0x00, 0x0B, 0x0D, 0x0E, 0x13, 0x19, 0x1C, 0x15, 0x16, 0x1A];
+
+const CODE128_PATTERNS: [[u8; 6]; 106] = [
+    [2, 1, 2, 2, 2, 2], [2, 2, 2, 1, 2, 2], [2, 2, 2, 2, 2, 1], [1, 2, 1, 2, 2, 3], [1, 2, 1, 3, 2, 2],
+    [1, 3, 1, 2, 2, 2], [1, 2, 2, 2, 1, 3], [1, 2, 2, 3, 1, 2], [1, 3, 2, 2, 1, 2], [2, 2, 1, 2, 1, 3],
+    [2, 2, 1, 3, 1, 2], [2, 3, 1, 2, 1, 2], [1, 1, 2, 2, 3, 2], [1, 2, 2, 1, 3, 2], [1, 2, 2, 2, 3, 1],
+    [1, 1, 3, 2, 2, 2], [1, 2, 3, 1, 2, 2], [1, 2, 3, 2, 2, 1], [2, 2, 3, 2, 1, 1], [2, 2, 1, 1, 3, 2],
+    [2, 2, 1, 2, 3, 1], [2, 1, 3, 2, 1, 2], [2, 2, 3, 1, 1, 2], [3, 1, 2, 1, 3, 1], [3, 1, 1, 2, 2, 2],
+    [3, 2, 1, 1, 2, 2], [3, 2, 1, 2, 2, 1], [3, 1, 2, 2, 1, 2], [3, 2, 2, 1, 1, 2], [3, 2, 2, 2, 1, 1],
+    [2, 1, 2, 1, 2, 3], [2, 1, 2, 3, 2, 1], [2, 3, 2, 1, 2, 1], [1, 1, 1, 3, 2, 3], [1, 3, 1, 1, 2, 3],
+    [1, 3, 1, 3, 2, 1], [1, 1, 2, 3, 1, 3], [1, 3, 2, 1, 1, 3], [1, 3, 2, 3, 1, 1], [2, 1, 1, 3, 1, 3],
+    [2, 3, 1, 1, 1, 3], [2, 3, 1, 3, 1, 1], [1, 1, 2, 1, 3, 3], [1, 1, 2, 3, 3, 1], [1, 3, 2, 1, 3, 1],
+    [1, 1, 3, 1, 2, 3], [1, 1, 3, 3, 2, 1], [1, 3, 3, 1, 2, 1], [3, 1, 3, 1, 2, 1], [2, 1, 1, 3, 3, 1],
+    [2, 3, 1, 1, 3, 1], [2, 1, 3, 1, 1, 3], [2, 1, 3, 3, 1, 1], [2, 1, 3, 1, 3, 1], [3, 1, 1, 1, 2, 3],
+    [3, 1, 1, 3, 2, 1], [3, 3, 1, 1, 2, 1], [3, 1, 2, 1, 1, 3], [3, 1, 2, 3, 1, 1], [3, 3, 2, 1, 1, 1],
+    [3, 1, 4, 1, 1, 1], [2, 2, 1, 4, 1, 1], [4, 3, 1, 1, 1, 1], [1, 1, 1, 2, 2, 4], [1, 1, 1, 4, 2, 2],
+    [1, 2, 1, 1, 2, 4], [1, 2, 1, 4, 2, 1], [1, 4, 1, 1, 2, 2], [1, 4, 1, 2, 2, 1], [1, 1, 2, 2, 1, 4],
+    [1, 1, 2, 4, 1, 2], [1, 2, 2, 1, 1, 4], [1, 2, 2, 4, 1, 1], [1, 4, 2, 1, 1, 2], [1, 4, 2, 2, 1, 1],
+    [2, 4, 1, 2, 1, 1], [2, 2, 1, 1, 1, 4], [4, 1, 3, 1, 1, 1], [2, 4, 1, 1, 1, 2], [1, 3, 4, 1, 1, 1],
+    [1, 1, 1, 2, 4, 2], [1, 2, 1, 1, 4, 2], [1, 2, 1, 2, 4, 1], [1, 1, 4, 2, 1, 2], [1, 2, 4, 1, 1, 2],
+    [1, 2, 4, 2, 1, 1], [4, 1, 1, 2, 1, 2], [4, 2, 1, 1, 1, 2], [4, 2, 1, 2, 1, 1], [2, 1, 2, 1, 4, 1],
+    [2, 1, 4, 1, 2, 1], [4, 1, 2, 1, 2, 1], [1, 1, 1, 1, 4, 3], [1, 1, 1, 3, 4, 1], [1, 3, 1, 1, 4, 1],
+    [1, 1, 4, 1, 1, 3], [1, 1, 4, 3, 1, 1], [4, 1, 1, 1, 1, 3], [4, 1, 1, 3, 1, 1], [1, 1, 3, 1, 4, 1],
+    [1, 1, 4, 1, 3, 1], [3, 1, 1, 1, 4, 1], [4, 1, 1, 1, 3, 1], [2, 1, 1, 4, 1, 2], [2, 1, 1, 2, 1, 4],
+    [2, 1, 1, 2, 3, 2],
+];
+const CODE128_STOP: [u8; 7] = [2, 3, 3, 1, 1, 1, 2];
+const CODE128_START_A: usize = 103;
+const CODE128_START_C: usize = 105;
+
+// Code 39 每个字符 9 个元素（深浅交替，深色开头），位为 1 表示宽元素，最高位对应第一个元素
+const CODE39_ALPHABET: &[u8; 43] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ-. $/+%";
+const CODE39_PATTERNS: [u16; 43] = [
+    0x034, 0x121, 0x061, 0x160, 0x031, 0x130, 0x070, 0x025, 0x124, 0x064, 0x109, 0x049, 0x148, 0x019, 0x118,
+    0x058, 0x00D, 0x10C, 0x04C, 0x01C, 0x103, 0x043, 0x142, 0x013, 0x112, 0x052, 0x007, 0x106, 0x046, 0x016,
+    0x181, 0x0C1, 0x1C0, 0x091, 0x190, 0x0D0, 0x085, 0x184, 0x0C4, 0x0A8, 0x0A2, 0x08A, 0x02A,
+];
+const CODE39_ASTERISK: u16 = 0x094;
+
+// 扫描线上的一段，位置为扫描线上的像素坐标
+#[derive(Debug, Clone, Copy)]
+struct Run {
+    start: u32,
+    length: u32,
+    dark: bool,
+}
+
+// 扫描线上识别到的一次结果，start..end 为条码在扫描线上的范围
+struct LineHit {
+    format: LinearFormat,
+    text: String,
+    start: u32,
+    end: u32,
+}
+
+// 按局部阈值把扫描线分段，对比度不足的区域视为浅色
+fn scan_runs(values: &[u8]) -> Vec<Run> {
+    let blocks = values.len().div_ceil(BLOCK_SIZE);
+    let extremes: Vec<(u8, u8)> = values
+        .chunks(BLOCK_SIZE)
+        .map(|chunk| (*chunk.iter().min().unwrap_or(&255), *chunk.iter().max().unwrap_or(&0)))
+        .collect();
+    let thresholds: Vec<Option<u8>> = (0..blocks)
+        .map(|block| {
+            let range = &extremes[block.saturating_sub(2)..(block + 3).min(blocks)];
+            let low = range.iter().map(|(low, _)| *low).min().unwrap_or(255);
+            let high = range.iter().map(|(_, high)| *high).max().unwrap_or(0);
+            (high.saturating_sub(low) >= MIN_CONTRAST).then(|| ((low as u16 + high as u16) / 2) as u8)
+        })
+        .collect();
+
+    let mut runs: Vec<Run> = Vec::new();
+    for (index, &value) in values.iter().enumerate() {
+        let dark = thresholds[index / BLOCK_SIZE].is_some_and(|threshold| value <= threshold);
+        match runs.last_mut() {
+            Some(run) if run.dark == dark => run.length += 1,
+            _ => runs.push(Run { start: index as u32, length: 1, dark }),
+        }
+    }
+    runs
+}
+
+// 段宽与编码宽度的平均偏差（以模块为单位），总宽度按编码的模块总数换算
+fn variance(runs: &[Run], pattern: &[u8]) -> f32 {
+    let total: u32 = runs.iter().map(|run| run.length).sum();
+    let modules: u32 = pattern.iter().map(|&width| width as u32).sum();
+    let unit = total as f32 / modules as f32;
+    runs.iter()
+        .zip(pattern)
+        .map(|(run, &width)| (run.length as f32 / unit - width as f32).abs())
+        .sum::<f32>()
+        / pattern.len() as f32
+}
+
+// 在编码表中找偏差最小的一项
+fn best_match<const N: usize>(runs: &[Run], patterns: &[[u8; N]]) -> Option<usize> {
+    patterns
+        .iter()
+        .enumerate()
+        .map(|(index, pattern)| (index, variance(runs, pattern)))
+        .filter(|(_, variance)| *variance < MAX_VARIANCE)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(index, _)| index)
+}
+
+// 条码前后的空白区：位于扫描线端点，或浅色段足够宽
+fn quiet_before(runs: &[Run], index: usize, module: f32) -> bool {
+    index == 0 || runs[index - 1].length as f32 >= module * QUIET_MODULES
+}
+
+fn quiet_after(runs: &[Run], index: usize, module: f32) -> bool {
+    index >= runs.len() || runs[index].length as f32 >= module * QUIET_MODULES
+}
+
+fn span(runs: &[Run], first: usize, last: usize) -> (u32, u32) {
+    let start = runs[first..=last].iter().map(|run| run.start).min().unwrap_or(0);
+    let end = runs[first..=last].iter().map(|run| run.start + run.length).max().unwrap_or(0);
+    (start, end)
+}
+
+fn total_width(runs: &[Run]) -> u32 {
+    runs.iter().map(|run| run.length).sum()
+}
+
+// 保护符（1 模块宽的深浅交替）
+fn is_guard(runs: &[Run], module: f32) -> bool {
+    runs.iter().all(|run| (run.length as f32 / module - 1.0).abs() < 0.5 + MAX_VARIANCE)
+}
+
+fn ean_checksum(digits: &[u8]) -> bool {
+    let (body, check) = digits.split_at(digits.len() - 1);
+    // 从右往左，紧邻校验位的一位权重为 3
+    let sum: u32 = body
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(index, &digit)| digit as u32 * if index % 2 == 0 { 3 } else { 1 })
+        .sum();
+    (10 - sum % 10) % 10 == check[0] as u32
+}
+
+// 解码一组 EAN 数字，返回数字和 G 码标记
+fn ean_digits(runs: &[Run], count: usize, allow_g: bool) -> Option<(Vec<u8>, u8)> {
+    let mut digits = Vec::with_capacity(count);
+    let mut parity = 0u8;
+    let reversed = EAN_DIGITS.map(|pattern| [pattern[3], pattern[2], pattern[1], pattern[0]]);
+    for chunk in runs.chunks(4).take(count) {
+        let l = EAN_DIGITS.iter().map(|pattern| variance(chunk, pattern)).enumerate().min_by(|a, b| a.1.total_cmp(&b.1))?;
+        let g = reversed.iter().map(|pattern| variance(chunk, pattern)).enumerate().min_by(|a, b| a.1.total_cmp(&b.1))?;
+        parity <<= 1;
+        if allow_g && g.1 < l.1 {
+            if g.1 >= MAX_VARIANCE {
+                return None;
+            }
+            digits.push(g.0 as u8);
+            parity |= 1;
+        } else {
+            if l.1 >= MAX_VARIANCE {
+                return None;
+            }
+            digits.push(l.0 as u8);
+        }
+    }
+    Some((digits, parity))
+}
+
+// EAN-13（含 UPC-A）：3 + 6 × 4 + 5 + 6 × 4 + 3 = 59 段，共 95 个模块
+fn decode_ean13(runs: &[Run], index: usize) -> Option<LineHit> {
+    let symbol = runs.get(index..index + 59)?;
+    let module = total_width(symbol) as f32 / 95.0;
+    if !quiet_before(runs, index, module) || !quiet_after(runs, index + 59, module) {
+        return None;
+    }
+    if !is_guard(&symbol[..3], module) || !is_guard(&symbol[27..32], module) || !is_guard(&symbol[56..], module) {
+        return None;
+    }
+    let (left, parity) = ean_digits(&symbol[3..27], 6, true)?;
+    let (right, _) = ean_digits(&symbol[32..56], 6, false)?;
+    let first = EAN_FIRST_DIGIT.iter().position(|&pattern| pattern == parity)? as u8;
+    let digits: Vec<u8> = std::iter::once(first).chain(left).chain(right).collect();
+    if !ean_checksum(&digits) {
+        return None;
+    }
+    let text: String = digits.iter().map(|digit| (b'0' + digit) as char).collect();
+    let (start, end) = span(runs, index, index + 58);
+    // 首位为 0 的 EAN-13 即 UPC-A
+    let (format, text) = if first == 0 { (LinearFormat::UpcA, text[1..].to_string()) } else { (LinearFormat::Ean13, text) };
+    Some(LineHit { format, text, start, end })
+}
+
+// EAN-8：3 + 4 × 4 + 5 + 4 × 4 + 3 = 43 段，共 67 个模块
+fn decode_ean8(runs: &[Run], index: usize) -> Option<LineHit> {
+    let symbol = runs.get(index..index + 43)?;
+    let module = total_width(symbol) as f32 / 67.0;
+    if !quiet_before(runs, index, module) || !quiet_after(runs, index + 43, module) {
+        return None;
+    }
+    if !is_guard(&symbol[..3], module) || !is_guard(&symbol[19..24], module) || !is_guard(&symbol[40..], module) {
+        return None;
+    }
+    let (left, _) = ean_digits(&symbol[3..19], 4, false)?;
+    let (right, _) = ean_digits(&symbol[24..40], 4, false)?;
+    let digits: Vec<u8> = left.into_iter().chain(right).collect();
+    if !ean_checksum(&digits) {
+        return None;
+    }
+    let (start, end) = span(runs, index, index + 42);
+    Some(LineHit {
+        format: LinearFormat::Ean8,
+        text: digits.iter().map(|digit| (b'0' + digit) as char).collect(),
+        start,
+        end,
+    })
+}
+
+// Code 128：起始符、数据符、校验符各 6 段（11 个模块），终止符 7 段（13 个模块）
+fn decode_code128(runs: &[Run], index: usize) -> Option<LineHit> {
+    let start_symbol = runs.get(index..index + 6)?;
+    let start_code = best_match(start_symbol, &CODE128_PATTERNS)?;
+    if start_code < CODE128_START_A {
+        return None;
+    }
+    let module = total_width(start_symbol) as f32 / 11.0;
+    if !quiet_before(runs, index, module) {
+        return None;
+    }
+
+    let mut values = vec![start_code];
+    let mut position = index + 6;
+    loop {
+        // 终止符后必须是空白区；校验符加终止符的前段可能恰好像终止符，校验通过才结束
+        if let Some(stop) = runs.get(position..position + 7) {
+            if variance(stop, &CODE128_STOP) < MAX_VARIANCE && quiet_after(runs, position + 7, module) && code128_checksum(&values) {
+                break;
+            }
+        }
+        let symbol = runs.get(position..position + 6)?;
+        let value = best_match(symbol, &CODE128_PATTERNS)?;
+        if value >= CODE128_START_A {
+            return None;
+        }
+        values.push(value);
+        position += 6;
+    }
+    values.pop();
+    let text = code128_text(&values)?;
+    let (start, end) = span(runs, index, position + 6);
+    Some(LineHit { format: LinearFormat::Code128, text, start, end })
+}
+
+// 至少包含一个数据符，最后一个为校验符
+fn code128_checksum(values: &[usize]) -> bool {
+    let Some((&check, data)) = values.split_last() else {
+        return false;
+    };
+    let sum = data.iter().enumerate().map(|(weight, &value)| value * weight.max(1)).sum::<usize>();
+    data.len() >= 2 && sum % 103 == check
+}
+
+// 按 A / B / C 字符集解释码值，FNC1 输出为 GS（GS1-128 的分隔符，首位的 FNC1 省略）
+fn code128_text(values: &[usize]) -> Option<String> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Set {
+        A,
+        B,
+        C,
+    }
+    let mut set = match values[0] {
+        CODE128_START_A => Set::A,
+        CODE128_START_C => Set::C,
+        _ => Set::B,
+    };
+    let mut text = String::new();
+    let mut shift = false;
+    for (position, &value) in values.iter().enumerate().skip(1) {
+        let current = if shift {
+            if set == Set::A { Set::B } else { Set::A }
+        } else {
+            set
+        };
+        shift = false;
+        match (current, value) {
+            (_, 102) => {
+                if position > 1 {
+                    text.push('\u{1D}');
+                }
+            }
+            (Set::C, 0..=99) => text.push_str(&format!("{:02}", value)),
+            (Set::C, 100) => set = Set::B,
+            (Set::C, 101) => set = Set::A,
+            (Set::A, 0..=63) => text.push((value as u8 + 32) as char),
+            (Set::A, 64..=95) => text.push((value as u8 - 64) as char),
+            (Set::B, 0..=95) => text.push((value as u8 + 32) as char),
+            (Set::A | Set::B, 98) => shift = true,
+            (Set::A | Set::B, 99) => set = Set::C,
+            (Set::A, 100) => set = Set::B,
+            (Set::B, 101) => set = Set::A,
+            // FNC2、FNC3、FNC4 不影响文本
+            (Set::A | Set::B, 96 | 97) | (Set::A, 101) | (Set::B, 100) => {}
+            _ => return None,
+        }
+    }
+    Some(text)
+}
+
+// Code 39 字符：9 段中 3 段为宽元素
+fn code39_pattern(runs: &[Run]) -> Option<u16> {
+    let mut widths: Vec<u32> = runs.iter().map(|run| run.length).collect();
+    widths.sort_unstable();
+    let (narrow_max, wide_min) = (widths[5], widths[6]);
+    if (wide_min as f32) < narrow_max as f32 * 1.5 {
+        return None;
+    }
+    let threshold = (narrow_max + wide_min) as f32 / 2.0;
+    Some(runs.iter().fold(0u16, |acc, run| (acc << 1) | (run.length as f32 > threshold) as u16))
+}
+
+// Code 39：以 * 开始和结束，字符之间以窄空白分隔
+fn decode_code39(runs: &[Run], index: usize) -> Option<LineHit> {
+    let first = runs.get(index..index + 9)?;
+    if code39_pattern(first)? != CODE39_ASTERISK {
+        return None;
+    }
+    let narrow = first.iter().map(|run| run.length).min()? as f32;
+    if !quiet_before(runs, index, narrow * 2.0) {
+        return None;
+    }
+
+    let mut text = String::new();
+    let mut position = index + 9;
+    loop {
+        let gap = runs.get(position)?;
+        if gap.length as f32 > narrow * 4.0 {
+            return None;
+        }
+        let symbol = runs.get(position + 1..position + 10)?;
+        let pattern = code39_pattern(symbol)?;
+        position += 10;
+        if pattern == CODE39_ASTERISK {
+            break;
+        }
+        let index = CODE39_PATTERNS.iter().position(|&candidate| candidate == pattern)?;
+        text.push(CODE39_ALPHABET[index] as char);
+    }
+    if text.is_empty() || !quiet_after(runs, position, narrow * 2.0) {
+        return None;
+    }
+    let (start, end) = span(runs, index, position - 1);
+    Some(LineHit { format: LinearFormat::Code39, text, start, end })
+}
+
+// 在扫描线的每个深色段处尝试各种条码，命中后跳过该条码
+fn decode_line(runs: &[Run], hits: &mut Vec<LineHit>) {
+    let mut index = 0;
+    while index < runs.len() {
+        if !runs[index].dark {
+            index += 1;
+            continue;
+        }
+        let hit = decode_ean13(runs, index)
+            .or_else(|| decode_ean8(runs, index))
+            .or_else(|| decode_code128(runs, index))
+            .or_else(|| decode_code39(runs, index));
+        match hit {
+            Some(hit) => {
+                // 反向扫描时段的位置递减，按范围而不是方向跳过
+                let (start, end) = (hit.start, hit.end);
+                hits.push(hit);
+                while index < runs.len() && runs[index].start >= start && runs[index].start < end {
+                    index += 1;
+                }
+            }
+            None => index += 1,
+        }
+    }
+}
+
+// 正反两个方向解码一条扫描线
+fn scan_line(values: &[u8]) -> Vec<LineHit> {
+    let runs = scan_runs(values);
+    let mut hits = Vec::new();
+    decode_line(&runs, &mut hits);
+    let reversed: Vec<Run> = runs.iter().rev().cloned().collect();
+    decode_line(&reversed, &mut hits);
+    hits
+}
+
+// 多条扫描线上的同一个条码
+struct Group {
+    format: LinearFormat,
+    text: String,
+    hits: usize,
+    x0: u32,
+    y0: u32,
+    x1: u32,
+    y1: u32,
+}
+
+fn add_hit(groups: &mut Vec<Group>, hit: LineHit, line: u32, vertical: bool) {
+    // 横向扫描时扫描线为行，纵向时为列
+    let (x0, y0, x1, y1) = if vertical { (line, hit.start, line + 1, hit.end) } else { (hit.start, line, hit.end, line + 1) };
+    let gap = SCAN_STEP * 4;
+    let existing = groups.iter_mut().find(|group| {
+        group.format == hit.format
+            && group.text == hit.text
+            && x0 <= group.x1 + gap
+            && group.x0 <= x1 + gap
+            && y0 <= group.y1 + gap
+            && group.y0 <= y1 + gap
+    });
+    match existing {
+        Some(group) => {
+            group.hits += 1;
+            group.x0 = group.x0.min(x0);
+            group.y0 = group.y0.min(y0);
+            group.x1 = group.x1.max(x1);
+            group.y1 = group.y1.max(y1);
+        }
+        None => groups.push(Group { format: hit.format, text: hit.text, hits: 1, x0, y0, x1, y1 }),
+    }
+}
+
+// 识别图片中的一维条码（阻塞）
+pub fn decode(image: &GrayImage) -> Vec<LinearCode> {
+    let (width, height) = image.dimensions();
+    let mut groups = Vec::new();
+    for y in (0..height).step_by(SCAN_STEP as usize) {
+        let row: Vec<u8> = (0..width).map(|x| image.get_pixel(x, y).0[0]).collect();
+        for hit in scan_line(&row) {
+            add_hit(&mut groups, hit, y, false);
+        }
+    }
+    for x in (0..width).step_by(SCAN_STEP as usize) {
+        let column: Vec<u8> = (0..height).map(|y| image.get_pixel(x, y).0[0]).collect();
+        for hit in scan_line(&column) {
+            add_hit(&mut groups, hit, x, true);
+        }
+    }
+    // 较矮的条码只被一条扫描线覆盖时也接受
+    let min_hits = if height < SCAN_STEP * MIN_HITS as u32 * 2 { 1 } else { MIN_HITS };
+    groups
+        .into_iter()
+        .filter(|group| group.hits >= min_hits)
+        .map(|group| LinearCode {
+            format: group.format,
+            text: group.text,
+            x: group.x0,
+            y: group.y0,
+            width: group.x1 - group.x0,
+            height: group.y1 - group.y0,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_background;
+    use image::{imageops, Luma};
+
+    // 深浅交替的宽度序列（深色开头）绘制为条码，空白区为 10 个模块
+    fn render_bars(widths: &[u8], module: u32, height: u32) -> GrayImage {
+        let total: u32 = widths.iter().map(|&width| width as u32).sum::<u32>() + 20;
+        let mut image = GrayImage::from_pixel(total * module, height, Luma([250]));
+        let mut x = 10 * module;
+        for (index, &width) in widths.iter().enumerate() {
+            let pixels = width as u32 * module;
+            if index % 2 == 0 {
+                for dx in 0..pixels {
+                    for y in 0..height {
+                        image.put_pixel(x + dx, y, Luma([20]));
+                    }
+                }
+            }
+            x += pixels;
+        }
+        image
+    }
+
+    fn ean_widths(digits: &str) -> Vec<u8> {
+        let digits: Vec<usize> = digits.bytes().map(|byte| (byte - b'0') as usize).collect();
+        let (first, rest) = if digits.len() == 13 { (Some(digits[0]), &digits[1..]) } else { (None, &digits[..]) };
+        let half = rest.len() / 2;
+        let parity = first.map(|digit| EAN_FIRST_DIGIT[digit]).unwrap_or(0);
+        let mut widths = vec![1, 1, 1];
+        for (index, &digit) in rest[..half].iter().enumerate() {
+            let pattern = EAN_DIGITS[digit];
+            if parity & (1 << (half - 1 - index)) != 0 {
+                widths.extend(pattern.iter().rev());
+            } else {
+                widths.extend(pattern);
+            }
+        }
+        widths.extend([1, 1, 1, 1, 1]);
+        for &digit in &rest[half..] {
+            widths.extend(EAN_DIGITS[digit]);
+        }
+        widths.extend([1, 1, 1]);
+        widths
+    }
+
+    fn code128_widths(start: usize, values: &[usize]) -> Vec<u8> {
+        let mut codes = vec![start];
+        codes.extend_from_slice(values);
+        let check = codes.iter().enumerate().map(|(weight, &value)| value * weight.max(1)).sum::<usize>() % 103;
+        codes.push(check);
+        let mut widths: Vec<u8> = codes.iter().flat_map(|&code| CODE128_PATTERNS[code]).collect();
+        widths.extend(CODE128_STOP);
+        widths
+    }
+
+    // 宽窄比 3:1，字符间隔 1 个窄元素
+    fn code39_widths(text: &str) -> Vec<u8> {
+        let mut widths = Vec::new();
+        for (index, c) in format!("*{}*", text).bytes().enumerate() {
+            let pattern = if c == b'*' {
+                CODE39_ASTERISK
+            } else {
+                CODE39_PATTERNS[CODE39_ALPHABET.iter().position(|&a| a == c).unwrap()]
+            };
+            if index > 0 {
+                widths.push(1);
+            }
+            widths.extend((0..9).rev().map(|bit| if pattern & (1 << bit) != 0 { 3 } else { 1 }));
+        }
+        widths
+    }
+
+    fn cases() -> Vec<(LinearFormat, &'static str, Vec<u8>)> {
+        let code128_b: Vec<usize> = "Hello, 128!".bytes().map(|byte| (byte - 32) as usize).collect();
+        vec![
+            (LinearFormat::Ean13, "5901234123457", ean_widths("5901234123457")),
+            (LinearFormat::UpcA, "036000291452", ean_widths("0036000291452")),
+            (LinearFormat::Ean8, "96385074", ean_widths("96385074")),
+            (LinearFormat::Code128, "Hello, 128!", code128_widths(104, &code128_b)),
+            (LinearFormat::Code128, "1234567890", code128_widths(105, &[12, 34, 56, 78, 90])),
+            (LinearFormat::Code39, "CODE-39", code39_widths("CODE-39")),
+        ]
+    }
+
+    // 把条码放到模拟界面上识别
+    fn decode_bars(widths: &[u8], module: u32) -> Vec<LinearCode> {
+        let bars = render_bars(widths, module, 40);
+        let mut canvas = test_background(bars.width() + 200, 160);
+        imageops::overlay(&mut canvas, &bars, 120, 60);
+        decode(&canvas)
+    }
+
+    #[test]
+    fn decodes_formats_at_module_sizes() {
+        for (format, text, widths) in cases() {
+            for module in [1, 2, 3] {
+                let decoded = decode_bars(&widths, module);
+                assert_eq!(decoded.len(), 1, "{:?} {} module {}", format, text, module);
+                assert_eq!(decoded[0].format, format);
+                assert_eq!(decoded[0].text, text);
+            }
+        }
+    }
+
+    #[test]
+    fn decodes_vertical_barcode() {
+        let (_, text, widths) = &cases()[0];
+        let bars = render_bars(widths, 2, 50);
+        let mut canvas = test_background(400, bars.width() + 100);
+        imageops::overlay(&mut canvas, &imageops::rotate90(&bars), 150, 50);
+        let decoded = decode(&canvas);
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].text, *text);
+    }
+
+    #[test]
+    fn decodes_upside_down_barcode() {
+        let (_, text, widths) = &cases()[3];
+        let bars = render_bars(widths, 2, 50);
+        let mut canvas = test_background(bars.width() + 200, 200);
+        imageops::overlay(&mut canvas, &imageops::rotate180(&bars), 100, 60);
+        let decoded = decode(&canvas);
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].text, *text);
+    }
+
+    #[test]
+    fn rejects_bad_ean_checksum() {
+        let mut wrong = cases()[0].2.clone();
+        wrong.swap(3, 4); // 第二位 9 (3112) 变为 7 (1312)
+        assert!(decode_bars(&wrong, 2).is_empty());
+    }
+
+    #[test]
+    fn ignores_images_without_barcodes() {
+        assert!(decode(&test_background(800, 600)).is_empty());
+    }
+}
@@ -0,0 +1,1091 @@
+// QR 码识别：自适应二值化 → 按 1:1:3:1:1 比例查找定位图案 → 三个定位图案组成的直角确定方向和版本
+// → 用右下角的校正图案做透视变换采样 → 读取格式信息、去掩码、按块 Reed-Solomon 纠错 → 解析数据段
+// 版本与纠错块参数取自 QR 码规范（ISO/IEC 18004），与 qrcodegen 的表一致
+use image::GrayImage;
+
+// 二值化时的分块大小，阈值取周围 5 × 5 块的最小值和最大值的中点
+const BLOCK_SIZE: u32 = 16;
+// 对比度低于该值的区域使用全局阈值
+const MIN_CONTRAST: u8 = 24;
+// 参与组合的定位图案数量上限
+const MAX_FINDERS: usize = 40;
+
+const ECC_CODEWORDS_PER_BLOCK: [[u8; 41]; 4] = [
+    // 按纠错等级 L、M、Q、H，下标为版本号（0 不使用）
+    [0, 7, 10, 15, 20, 26, 18, 20, 24, 30, 18, 20, 24, 26, 30, 22, 24, 28, 30, 28, 28, 28, 28, 30, 30, 26, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+    [0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28],
+    [0, 13, 22, 18, 26, 18, 24, 18, 22, 20, 24, 28, 26, 24, 20, 30, 24, 28, 28, 26, 30, 28, 30, 30, 30, 30, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+    [0, 17, 28, 22, 16, 22, 28, 26, 26, 24, 28, 24, 28, 22, 24, 24, 30, 28, 28, 26, 28, 30, 24, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+];
+
+const NUM_ERROR_CORRECTION_BLOCKS: [[u8; 41]; 4] = [
+    [0, 1, 1, 1, 1, 1, 2, 2, 2, 2, 4, 4, 4, 4, 4, 6, 6, 6, 6, 7, 8, 8, 9, 9, 10, 12, 12, 12, 13, 14, 15, 16, 17, 18, 19, 19, 20, 21, 22, 24, 25],
+    [0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21, 23, 25, 26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49],
+    [0, 1, 1, 2, 2, 4, 4, 6, 6, 8, 8, 8, 10, 12, 16, 12, 17, 16, 18, 21, 20, 23, 23, 25, 27, 29, 34, 34, 35, 38, 40, 43, 45, 48, 51, 53, 56, 59, 62, 65, 68],
+    [0, 1, 1, 2, 4, 4, 4, 5, 6, 8, 8, 11, 11, 16, 16, 18, 16, 19, 21, 25, 25, 25, 34, 30, 32, 35, 37, 40, 42, 45, 48, 51, 54, 57, 60, 63, 66, 70, 74, 77, 81],
+];
+
+const ALPHANUMERIC: &[u8; 45] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+#[derive(Debug, Clone)]
+pub struct QrCode {
+    pub text: String,
+    pub version: u8,
+    pub ec_level: char, // L、M、Q、H
+    pub corners: [(f32, f32); 4], // 码的四个外角（左上、右上、右下、左下），图片像素坐标
+}
+
+// 二值化后的图片，true 为深色
+struct BitImage {
+    width: u32,
+    height: u32,
+    bits: Vec<bool>,
+}
+
+impl BitImage {
+    fn get(&self, x: i64, y: i64) -> Option<bool> {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return None;
+        }
+        Some(self.bits[(y as u32 * self.width + x as u32) as usize])
+    }
+
+    fn dark(&self, x: i64, y: i64) -> bool {
+        self.get(x, y).unwrap_or(false)
+    }
+
+    fn inverted(&self) -> BitImage {
+        BitImage { width: self.width, height: self.height, bits: self.bits.iter().map(|bit| !bit).collect() }
+    }
+}
+
+// 大津法全局阈值，用于对比度不足的区域
+fn otsu_threshold(image: &GrayImage) -> u8 {
+    let mut histogram = [0u64; 256];
+    for pixel in image.pixels() {
+        histogram[pixel.0[0] as usize] += 1;
+    }
+    let total: u64 = histogram.iter().sum();
+    let sum: f64 = histogram.iter().enumerate().map(|(value, &count)| value as f64 * count as f64).sum();
+    let (mut sum_below, mut count_below) = (0.0, 0u64);
+    let (mut best, mut best_variance) = (127u8, -1.0);
+    for (value, &count) in histogram.iter().enumerate() {
+        count_below += count;
+        if count_below == 0 || count_below == total {
+            continue;
+        }
+        sum_below += value as f64 * count as f64;
+        let mean_below = sum_below / count_below as f64;
+        let mean_above = (sum - sum_below) / (total - count_below) as f64;
+        let variance = count_below as f64 * (total - count_below) as f64 * (mean_below - mean_above).powi(2);
+        if variance > best_variance {
+            best_variance = variance;
+            best = value as u8;
+        }
+    }
+    best
+}
+
+// 分块局部阈值：屏幕上的二维码对比度高，但所在界面可能有深色背景或渐变
+fn binarize(image: &GrayImage) -> BitImage {
+    let (width, height) = image.dimensions();
+    let columns = width.div_ceil(BLOCK_SIZE) as usize;
+    let rows = height.div_ceil(BLOCK_SIZE) as usize;
+    let mut block_min = vec![255u8; columns * rows];
+    let mut block_max = vec![0u8; columns * rows];
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let index = (y / BLOCK_SIZE) as usize * columns + (x / BLOCK_SIZE) as usize;
+        block_min[index] = block_min[index].min(pixel.0[0]);
+        block_max[index] = block_max[index].max(pixel.0[0]);
+    }
+
+    let global = otsu_threshold(image);
+    let mut thresholds = vec![global; columns * rows];
+    for row in 0..rows {
+        for column in 0..columns {
+            let (mut low, mut high) = (255u8, 0u8);
+            for neighbor_row in row.saturating_sub(2)..(row + 3).min(rows) {
+                for neighbor_column in column.saturating_sub(2)..(column + 3).min(columns) {
+                    let index = neighbor_row * columns + neighbor_column;
+                    low = low.min(block_min[index]);
+                    high = high.max(block_max[index]);
+                }
+            }
+            if high - low >= MIN_CONTRAST {
+                thresholds[row * columns + column] = ((low as u16 + high as u16) / 2) as u8;
+            }
+        }
+    }
+
+    let bits = image
+        .enumerate_pixels()
+        .map(|(x, y, pixel)| pixel.0[0] <= thresholds[(y / BLOCK_SIZE) as usize * columns + (x / BLOCK_SIZE) as usize])
+        .collect();
+    BitImage { width, height, bits }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Finder {
+    x: f64,
+    y: f64,
+    module: f64,
+    count: u32,
+}
+
+// 五段长度是否符合 1:1:3:1:1
+fn finder_ratio(runs: &[u32; 5]) -> bool {
+    let total: u32 = runs.iter().sum();
+    if total < 7 {
+        return false;
+    }
+    let module = total as f64 / 7.0;
+    let tolerance = module / 2.0;
+    (runs[0] as f64 - module).abs() < tolerance
+        && (runs[1] as f64 - module).abs() < tolerance
+        && (runs[2] as f64 - module * 3.0).abs() < tolerance * 3.0
+        && (runs[3] as f64 - module).abs() < tolerance
+        && (runs[4] as f64 - module).abs() < tolerance
+}
+
+// 从 (x, y) 沿 (dx, dy) 方向两侧统计五段长度，中心必须为深色；返回中心位置（沿该方向）和总长度
+fn cross_check(bits: &BitImage, x: i64, y: i64, dx: i64, dy: i64, max_total: u32) -> Option<(f64, u32)> {
+    if !bits.dark(x, y) {
+        return None;
+    }
+    let mut runs = [0u32; 5];
+    // 向负方向：中心段、浅色、深色
+    let mut step = 0i64;
+    for (index, dark) in [(2usize, true), (1, false), (0, true)] {
+        while bits.get(x - (step + 1) * dx, y - (step + 1) * dy) == Some(dark) && runs[index] <= max_total {
+            runs[index] += 1;
+            step += 1;
+        }
+        if runs[index] == 0 && index != 2 {
+            return None;
+        }
+    }
+    runs[2] += 1; // 中心像素
+    let back = step;
+    step = 0;
+    for (index, dark) in [(2usize, true), (3, false), (4, true)] {
+        while bits.get(x + (step + 1) * dx, y + (step + 1) * dy) == Some(dark) && runs[index] <= max_total {
+            runs[index] += 1;
+            step += 1;
+        }
+        if runs[index] == 0 && index != 2 {
+            return None;
+        }
+    }
+    let total: u32 = runs.iter().sum();
+    if total > max_total || !finder_ratio(&runs) {
+        return None;
+    }
+    // 中心段的中点相对 (x, y) 的偏移
+    let center_start = -back + runs[0] as i64 + runs[1] as i64;
+    let center = center_start as f64 + runs[2] as f64 / 2.0 - 0.5;
+    Some((center, total))
+}
+
+fn add_finder(finders: &mut Vec<Finder>, x: f64, y: f64, module: f64) {
+    for finder in finders.iter_mut() {
+        let close = (finder.x - x).abs() <= finder.module * 2.0 && (finder.y - y).abs() <= finder.module * 2.0;
+        let similar = (finder.module / module).max(module / finder.module) < 1.5;
+        if close && similar {
+            let count = finder.count as f64;
+            finder.x = (finder.x * count + x) / (count + 1.0);
+            finder.y = (finder.y * count + y) / (count + 1.0);
+            finder.module = (finder.module * count + module) / (count + 1.0);
+            finder.count += 1;
+            return;
+        }
+    }
+    finders.push(Finder { x, y, module, count: 1 });
+}
+
+// 逐行查找 1:1:3:1:1 的深浅序列，再在纵向和横向交叉确认
+fn find_finders(bits: &BitImage) -> Vec<Finder> {
+    let mut finders = Vec::new();
+    for y in 0..bits.height as i64 {
+        let mut runs: Vec<(u32, u32)> = Vec::new(); // (起点, 长度)，与行首颜色交替
+        let first_dark = bits.dark(0, y);
+        let mut start = 0u32;
+        for x in 1..=bits.width {
+            if x == bits.width || bits.dark(x as i64, y) != bits.dark(x as i64 - 1, y) {
+                runs.push((start, x - start));
+                start = x;
+            }
+        }
+        // 以深色段开头的连续五段
+        let first = if first_dark { 0 } else { 1 };
+        for index in (first..runs.len().saturating_sub(4)).step_by(2) {
+            let lengths = [runs[index].1, runs[index + 1].1, runs[index + 2].1, runs[index + 3].1, runs[index + 4].1];
+            if !finder_ratio(&lengths) {
+                continue;
+            }
+            let total: u32 = lengths.iter().sum();
+            let center_x = runs[index + 2].0 as f64 + runs[index + 2].1 as f64 / 2.0 - 0.5;
+            let max_total = total * 2;
+            let Some((offset_y, vertical)) = cross_check(bits, center_x.round() as i64, y, 0, 1, max_total) else {
+                continue;
+            };
+            // 纵横长度差距过大时不是定位图案
+            if (vertical as f64 - total as f64).abs() * 5.0 >= total as f64 * 2.0 {
+                continue;
+            }
+            let center_y = y as f64 + offset_y;
+            let Some((offset_x, horizontal)) = cross_check(bits, center_x.round() as i64, center_y.round() as i64, 1, 0, max_total)
+            else {
+                continue;
+            };
+            let center_x = center_x.round() + offset_x;
+            add_finder(&mut finders, center_x, center_y, (horizontal + vertical) as f64 / 14.0);
+        }
+    }
+    // 中心段有多行，真实的定位图案至少被确认两次
+    finders.retain(|finder| finder.count >= 2);
+    finders.sort_by_key(|finder| std::cmp::Reverse(finder.count));
+    finders.truncate(MAX_FINDERS);
+    finders
+}
+
+fn distance(a: &Finder, b: &Finder) -> f64 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+}
+
+// 三个定位图案按左上、右上、左下排列，不构成直角时返回空
+fn order_finders(a: Finder, b: Finder, c: Finder) -> Option<[Finder; 3]> {
+    let modules = [a.module, b.module, c.module];
+    let max_module = modules.iter().cloned().fold(f64::MIN, f64::max);
+    let min_module = modules.iter().cloned().fold(f64::MAX, f64::min);
+    if max_module / min_module > 1.5 {
+        return None;
+    }
+    // 最长边对面的是左上角
+    let (ab, bc, ca) = (distance(&a, &b), distance(&b, &c), distance(&c, &a));
+    let (top_left, mut first, mut second) = if bc >= ab && bc >= ca {
+        (a, b, c)
+    } else if ca >= ab && ca >= bc {
+        (b, c, a)
+    } else {
+        (c, a, b)
+    };
+    let (v1, v2) = ((first.x - top_left.x, first.y - top_left.y), (second.x - top_left.x, second.y - top_left.y));
+    let (l1, l2) = ((v1.0 * v1.0 + v1.1 * v1.1).sqrt(), (v2.0 * v2.0 + v2.1 * v2.1).sqrt());
+    let module = (a.module + b.module + c.module) / 3.0;
+    // 版本 1 的定位图案中心相距 14 个模块
+    if l1.max(l2) / l1.min(l2) > 1.3 || l1.min(l2) < module * 12.0 {
+        return None;
+    }
+    let cosine = (v1.0 * v2.0 + v1.1 * v2.1) / (l1 * l2);
+    if cosine.abs() > 0.25 {
+        return None;
+    }
+    // 图片坐标 y 向下，右上角在左上角顺时针 90° 方向
+    if v1.0 * v2.1 - v1.1 * v2.0 < 0.0 {
+        std::mem::swap(&mut first, &mut second);
+    }
+    Some([top_left, first, second])
+}
+
+// 模块坐标到图片坐标的透视变换
+#[derive(Debug, Clone, Copy)]
+struct Perspective([f64; 8]);
+
+impl Perspective {
+    // 由四组对应点求解 8 个参数（高斯消元）
+    fn from_points(source: [(f64, f64); 4], target: [(f64, f64); 4]) -> Option<Self> {
+        let mut matrix = [[0.0f64; 9]; 8];
+        for (index, (&(u, v), &(x, y))) in source.iter().zip(target.iter()).enumerate() {
+            matrix[index * 2] = [u, v, 1.0, 0.0, 0.0, 0.0, -u * x, -v * x, x];
+            matrix[index * 2 + 1] = [0.0, 0.0, 0.0, u, v, 1.0, -u * y, -v * y, y];
+        }
+        for column in 0..8 {
+            let pivot = (column..8).max_by(|&a, &b| matrix[a][column].abs().total_cmp(&matrix[b][column].abs()))?;
+            if matrix[pivot][column].abs() < 1e-9 {
+                return None;
+            }
+            matrix.swap(column, pivot);
+            let pivot_row = matrix[column];
+            for (index, row) in matrix.iter_mut().enumerate() {
+                if index != column {
+                    let factor = row[column] / pivot_row[column];
+                    for (value, pivot) in row[column..].iter_mut().zip(&pivot_row[column..]) {
+                        *value -= factor * pivot;
+                    }
+                }
+            }
+        }
+        let mut parameters = [0.0; 8];
+        for (index, parameter) in parameters.iter_mut().enumerate() {
+            *parameter = matrix[index][8] / matrix[index][index];
+        }
+        Some(Perspective(parameters))
+    }
+
+    // 三个点确定的仿射变换，第四个点按平行四边形补齐
+    fn affine(source: [(f64, f64); 3], target: [(f64, f64); 3]) -> Option<Self> {
+        let fourth = |points: [(f64, f64); 3]| (points[1].0 + points[2].0 - points[0].0, points[1].1 + points[2].1 - points[0].1);
+        Self::from_points(
+            [source[0], source[1], source[2], fourth(source)],
+            [target[0], target[1], target[2], fourth(target)],
+        )
+    }
+
+    fn map(&self, u: f64, v: f64) -> (f64, f64) {
+        let p = &self.0;
+        let w = p[6] * u + p[7] * v + 1.0;
+        ((p[0] * u + p[1] * v + p[2]) / w, (p[3] * u + p[4] * v + p[5]) / w)
+    }
+}
+
+fn alignment_positions(version: usize) -> Vec<usize> {
+    if version == 1 {
+        return Vec::new();
+    }
+    let size = version * 4 + 17;
+    let count = version / 7 + 2;
+    let step = if version == 32 { 26 } else { (version * 4 + count * 2 + 1) / (count * 2 - 2) * 2 };
+    let mut positions: Vec<usize> = (0..count - 1).map(|index| size - 7 - index * step).collect();
+    positions.push(6);
+    positions.reverse();
+    positions
+}
+
+// 在预测位置附近查找右下角的校正图案（5 × 5，中心深色、外圈深色、中间一圈浅色）
+fn find_alignment(bits: &BitImage, transform: &Perspective, center: f64) -> Option<(f64, f64)> {
+    let (px, py) = transform.map(center, center);
+    let (ux, uy) = {
+        let (x, y) = transform.map(center + 1.0, center);
+        (x - px, y - py)
+    };
+    let (vx, vy) = {
+        let (x, y) = transform.map(center, center + 1.0);
+        (x - px, y - py)
+    };
+    let mut best: Option<(u32, f64, (f64, f64))> = None;
+    // 在 ±4 个模块范围内以 1/4 模块为步长搜索
+    for j in -16..=16 {
+        for i in -16..=16 {
+            let (di, dj) = (i as f64 / 4.0, j as f64 / 4.0);
+            let (cx, cy) = (px + ux * di + vx * dj, py + uy * di + vy * dj);
+            let mut score = 0;
+            for b in -2i32..=2 {
+                for a in -2i32..=2 {
+                    let expected = a.abs().max(b.abs()) != 1;
+                    let (x, y) = (cx + ux * a as f64 + vx * b as f64, cy + uy * a as f64 + vy * b as f64);
+                    if bits.dark(x.round() as i64, y.round() as i64) == expected {
+                        score += 1;
+                    }
+                }
+            }
+            let offset = di * di + dj * dj;
+            let better = match best {
+                None => true,
+                Some((best_score, best_offset, _)) => score > best_score || (score == best_score && offset < best_offset),
+            };
+            if better {
+                best = Some((score, offset, (cx, cy)));
+            }
+        }
+    }
+    best.filter(|(score, _, _)| *score >= 23).map(|(_, _, point)| point)
+}
+
+// 按给定版本采样模块矩阵，返回矩阵和变换
+fn sample_grid(bits: &BitImage, finders: &[Finder; 3], version: usize) -> Option<(Vec<bool>, Perspective)> {
+    let size = version * 4 + 17;
+    let far = size as f64 - 3.5;
+    let source = [(3.5, 3.5), (far, 3.5), (3.5, far)];
+    let target = [(finders[0].x, finders[0].y), (finders[1].x, finders[1].y), (finders[2].x, finders[2].y)];
+    let affine = Perspective::affine(source, target)?;
+    let transform = if version >= 2 {
+        let center = size as f64 - 6.5;
+        match find_alignment(bits, &affine, center) {
+            Some(point) => Perspective::from_points(
+                [source[0], source[1], source[2], (center, center)],
+                [target[0], target[1], target[2], point],
+            )?,
+            None => affine,
+        }
+    } else {
+        affine
+    };
+
+    let mut grid = vec![false; size * size];
+    for y in 0..size {
+        for x in 0..size {
+            let (px, py) = transform.map(x as f64 + 0.5, y as f64 + 0.5);
+            grid[y * size + x] = bits.get(px.round() as i64, py.round() as i64)?;
+        }
+    }
+    Some((grid, transform))
+}
+
+// 纠错等级的格式位：L=1、M=0、Q=3、H=2，数组下标按 L、M、Q、H
+fn ec_index(format_bits: u32) -> usize {
+    match format_bits {
+        1 => 0,
+        0 => 1,
+        3 => 2,
+        _ => 3,
+    }
+}
+
+fn format_codeword(data: u32) -> u32 {
+    let mut remainder = data;
+    for _ in 0..10 {
+        remainder = (remainder << 1) ^ ((remainder >> 9) * 0x537);
+    }
+    ((data << 10) | remainder) ^ 0x5412
+}
+
+fn version_codeword(version: u32) -> u32 {
+    let mut remainder = version;
+    for _ in 0..12 {
+        remainder = (remainder << 1) ^ ((remainder >> 11) * 0x1F25);
+    }
+    (version << 12) | remainder
+}
+
+// 读取两份格式信息，取汉明距离最近的合法值（最多 3 位错误）：返回（纠错等级下标, 掩码）
+fn read_format(grid: &[bool], size: usize) -> Option<(usize, u32)> {
+    let module = |x: usize, y: usize| grid[y * size + x] as u32;
+    let mut first = 0u32;
+    let mut second = 0u32;
+    for i in 0..15 {
+        let (x, y) = match i {
+            0..=5 => (8, i),
+            6 => (8, 7),
+            7 => (8, 8),
+            8 => (7, 8),
+            _ => (14 - i, 8),
+        };
+        first |= module(x, y) << i;
+        let (x, y) = if i < 8 { (size - 1 - i, 8) } else { (8, size - 15 + i) };
+        second |= module(x, y) << i;
+    }
+    let (distance, data) = (0..32u32)
+        .map(|data| {
+            let codeword = format_codeword(data);
+            (((codeword ^ first).count_ones()).min((codeword ^ second).count_ones()), data)
+        })
+        .min()?;
+    if distance > 3 {
+        return None;
+    }
+    Some((ec_index(data >> 3), data & 7))
+}
+
+// 版本 7 及以上的版本信息，与按尺寸推算的版本不一致时返回 false
+fn check_version(grid: &[bool], size: usize, version: usize) -> bool {
+    if version < 7 {
+        return true;
+    }
+    let (mut first, mut second) = (0u32, 0u32);
+    for i in 0..18 {
+        let (a, b) = (size - 11 + i % 3, i / 3);
+        first |= (grid[b * size + a] as u32) << i;
+        second |= (grid[a * size + b] as u32) << i;
+    }
+    let best = (7..=40u32)
+        .map(|candidate| {
+            let codeword = version_codeword(candidate);
+            (((codeword ^ first).count_ones()).min((codeword ^ second).count_ones()), candidate)
+        })
+        .min();
+    matches!(best, Some((distance, candidate)) if distance <= 3 && candidate as usize == version)
+}
+
+// 功能图案（定位、分隔符、格式、时序、校正、版本信息）所在的模块
+fn function_modules(version: usize) -> Vec<bool> {
+    let size = version * 4 + 17;
+    let mut function = vec![false; size * size];
+    let mut mark = |x: usize, y: usize| function[y * size + x] = true;
+    for i in 0..size {
+        mark(6, i);
+        mark(i, 6);
+    }
+    for y in 0..9 {
+        for x in 0..9 {
+            mark(x, y);
+        }
+        for x in size - 8..size {
+            mark(x, y);
+        }
+    }
+    for y in size - 8..size {
+        for x in 0..9 {
+            mark(x, y);
+        }
+    }
+    let positions = alignment_positions(version);
+    let last = positions.len().saturating_sub(1);
+    for (i, &cx) in positions.iter().enumerate() {
+        for (j, &cy) in positions.iter().enumerate() {
+            if (i == 0 && (j == 0 || j == last)) || (i == last && j == 0) {
+                continue;
+            }
+            for y in cy - 2..=cy + 2 {
+                for x in cx - 2..=cx + 2 {
+                    mark(x, y);
+                }
+            }
+        }
+    }
+    if version >= 7 {
+        for i in 0..18 {
+            let (a, b) = (size - 11 + i % 3, i / 3);
+            mark(a, b);
+            mark(b, a);
+        }
+    }
+    function
+}
+
+fn mask_bit(mask: u32, x: usize, y: usize) -> bool {
+    match mask {
+        0 => (x + y).is_multiple_of(2),
+        1 => y.is_multiple_of(2),
+        2 => x.is_multiple_of(3),
+        3 => (x + y).is_multiple_of(3),
+        4 => (x / 3 + y / 2).is_multiple_of(2),
+        5 => x * y % 2 + x * y % 3 == 0,
+        6 => (x * y % 2 + x * y % 3).is_multiple_of(2),
+        _ => ((x + y) % 2 + x * y % 3).is_multiple_of(2),
+    }
+}
+
+fn raw_data_modules(version: usize) -> usize {
+    let mut result = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let count = version / 7 + 2;
+        result -= (25 * count - 10) * count - 55;
+        if version >= 7 {
+            result -= 36;
+        }
+    }
+    result
+}
+
+// 按之字形顺序读取去掩码后的码字
+fn read_codewords(grid: &[bool], size: usize, version: usize, mask: u32) -> Vec<u8> {
+    let function = function_modules(version);
+    let total = raw_data_modules(version) / 8;
+    let mut codewords = vec![0u8; total];
+    let mut bit = 0usize;
+    let mut right = size as i64 - 1;
+    while right >= 1 {
+        if right == 6 {
+            right = 5;
+        }
+        for vertical in 0..size {
+            for j in 0..2 {
+                let x = (right - j) as usize;
+                let upward = (right + 1) & 2 == 0;
+                let y = if upward { size - 1 - vertical } else { vertical };
+                if function[y * size + x] || bit >= total * 8 {
+                    continue;
+                }
+                if grid[y * size + x] ^ mask_bit(mask, x, y) {
+                    codewords[bit >> 3] |= 0x80 >> (bit & 7);
+                }
+                bit += 1;
+            }
+        }
+        right -= 2;
+    }
+    codewords
+}
+
+// GF(256)，本原多项式 x^8 + x^4 + x^3 + x^2 + 1
+struct Galois {
+    exp: [u8; 512],
+    log: [u8; 256],
+}
+
+impl Galois {
+    fn new() -> Self {
+        let mut exp = [0u8; 512];
+        let mut log = [0u8; 256];
+        let mut value = 1u16;
+        for (power, slot) in exp.iter_mut().enumerate().take(255) {
+            *slot = value as u8;
+            log[value as usize] = power as u8;
+            value <<= 1;
+            if value & 0x100 != 0 {
+                value ^= 0x11D;
+            }
+        }
+        for index in 255..512 {
+            exp[index] = exp[index - 255];
+        }
+        Galois { exp, log }
+    }
+
+    fn mul(&self, a: u8, b: u8) -> u8 {
+        if a == 0 || b == 0 {
+            return 0;
+        }
+        self.exp[self.log[a as usize] as usize + self.log[b as usize] as usize]
+    }
+
+    fn div(&self, a: u8, b: u8) -> u8 {
+        if a == 0 {
+            return 0;
+        }
+        self.exp[self.log[a as usize] as usize + 255 - self.log[b as usize] as usize]
+    }
+
+    fn pow(&self, power: usize) -> u8 {
+        self.exp[power % 255]
+    }
+
+    // 多项式求值，系数按次数从低到高
+    fn eval(&self, poly: &[u8], x: u8) -> u8 {
+        poly.iter().rev().fold(0, |acc, &coefficient| self.mul(acc, x) ^ coefficient)
+    }
+}
+
+// Reed-Solomon 纠错（Berlekamp-Massey + Chien 搜索 + Forney），block 为数据和纠错码字，首字节为最高次项
+fn correct_block(galois: &Galois, block: &mut [u8], ecc_len: usize) -> bool {
+    let n = block.len();
+    let syndromes: Vec<u8> = (0..ecc_len)
+        .map(|j| block.iter().fold(0u8, |acc, &byte| galois.mul(acc, galois.pow(j)) ^ byte))
+        .collect();
+    if syndromes.iter().all(|&s| s == 0) {
+        return true;
+    }
+
+    // 错误位置多项式
+    let mut locator = vec![1u8];
+    let mut previous = vec![1u8];
+    let mut length = 0usize;
+    let mut shift = 1usize;
+    let mut previous_discrepancy = 1u8;
+    for step in 0..ecc_len {
+        let mut discrepancy = syndromes[step];
+        for i in 1..=length.min(locator.len() - 1) {
+            discrepancy ^= galois.mul(locator[i], syndromes[step - i]);
+        }
+        if discrepancy == 0 {
+            shift += 1;
+            continue;
+        }
+        let factor = galois.div(discrepancy, previous_discrepancy);
+        let mut next = locator.clone();
+        if next.len() < previous.len() + shift {
+            next.resize(previous.len() + shift, 0);
+        }
+        for (i, &coefficient) in previous.iter().enumerate() {
+            next[i + shift] ^= galois.mul(factor, coefficient);
+        }
+        if 2 * length <= step {
+            length = step + 1 - length;
+            previous = std::mem::replace(&mut locator, next);
+            previous_discrepancy = discrepancy;
+            shift = 1;
+        } else {
+            locator = next;
+            shift += 1;
+        }
+    }
+    if length * 2 > ecc_len {
+        return false;
+    }
+
+    // 错误值多项式 Ω = S·Λ mod x^ecc_len
+    let mut evaluator = vec![0u8; ecc_len];
+    for (i, &s) in syndromes.iter().enumerate() {
+        for (j, &l) in locator.iter().enumerate() {
+            if i + j < ecc_len {
+                evaluator[i + j] ^= galois.mul(s, l);
+            }
+        }
+    }
+    let derivative: Vec<u8> = locator
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(i, &coefficient)| if i % 2 == 1 { coefficient } else { 0 })
+        .collect();
+
+    let mut found = 0;
+    for degree in 0..n {
+        let inverse = galois.pow(255 - degree % 255);
+        if galois.eval(&locator, inverse) != 0 {
+            continue;
+        }
+        let denominator = galois.eval(&derivative, inverse);
+        if denominator == 0 {
+            return false;
+        }
+        let magnitude = galois.mul(galois.pow(degree), galois.div(galois.eval(&evaluator, inverse), denominator));
+        block[n - 1 - degree] ^= magnitude;
+        found += 1;
+    }
+    if found != length {
+        return false;
+    }
+    (0..ecc_len).all(|j| block.iter().fold(0u8, |acc, &byte| galois.mul(acc, galois.pow(j)) ^ byte) == 0)
+}
+
+// 拆分交织的码字并逐块纠错，返回数据码字
+fn correct_codewords(codewords: &[u8], version: usize, ec: usize) -> Option<Vec<u8>> {
+    let blocks = NUM_ERROR_CORRECTION_BLOCKS[ec][version] as usize;
+    let ecc_len = ECC_CODEWORDS_PER_BLOCK[ec][version] as usize;
+    let total = codewords.len();
+    let short_blocks = blocks - total % blocks;
+    let short_len = total / blocks;
+
+    // 短块在数据部分末尾补一个占位字节，与交织顺序对齐
+    let mut split = vec![Vec::with_capacity(short_len + 1); blocks];
+    let mut index = 0;
+    for i in 0..=short_len {
+        for (j, block) in split.iter_mut().enumerate() {
+            if i == short_len - ecc_len && j < short_blocks {
+                block.push(0);
+            } else {
+                block.push(*codewords.get(index)?);
+                index += 1;
+            }
+        }
+    }
+
+    let galois = Galois::new();
+    let mut data = Vec::new();
+    for (j, mut block) in split.into_iter().enumerate() {
+        if j < short_blocks {
+            block.remove(short_len - ecc_len);
+        }
+        if !correct_block(&galois, &mut block, ecc_len) {
+            return None;
+        }
+        data.extend_from_slice(&block[..block.len() - ecc_len]);
+    }
+    Some(data)
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl BitReader<'_> {
+    fn remaining(&self) -> usize {
+        self.data.len() * 8 - self.position
+    }
+
+    fn read(&mut self, count: usize) -> Option<u32> {
+        if count > self.remaining() {
+            return None;
+        }
+        let mut value = 0u32;
+        for _ in 0..count {
+            let bit = (self.data[self.position >> 3] >> (7 - (self.position & 7))) & 1;
+            value = (value << 1) | bit as u32;
+            self.position += 1;
+        }
+        Some(value)
+    }
+}
+
+// 数据段内容按 ECI 指定的字符集解码，未指定时整体是合法 UTF-8 则按 UTF-8（编码器常把 UTF-8 字节
+// 拆进汉字模式），否则按 ISO-8859-1，汉字模式的字符无 Shift JIS 对照表时以替换字符表示
+fn decode_payload(bytes: &[u8], kanji: &[(usize, usize)], eci: Option<u32>) -> String {
+    let latin1 = || {
+        let mut text = String::new();
+        let mut index = 0;
+        while index < bytes.len() {
+            if kanji.iter().any(|&(start, end)| (start..end).contains(&index)) {
+                text.push(char::REPLACEMENT_CHARACTER);
+                index += 2;
+            } else {
+                text.push(bytes[index] as char);
+                index += 1;
+            }
+        }
+        text
+    };
+    match eci {
+        Some(1) | Some(3) => latin1(),
+        Some(_) => String::from_utf8_lossy(bytes).into_owned(),
+        None => match std::str::from_utf8(bytes) {
+            Ok(text) => text.to_string(),
+            Err(_) => latin1(),
+        },
+    }
+}
+
+// 解析数据段：数字、字母数字、字节、汉字（还原为 Shift JIS 字节）和 ECI
+fn parse_segments(data: &[u8], version: usize) -> Option<String> {
+    let size_class = if version <= 9 { 0 } else if version <= 26 { 1 } else { 2 };
+    let mut reader = BitReader { data, position: 0 };
+    let mut bytes = Vec::new();
+    let mut kanji = Vec::new();
+    let mut eci = None;
+    while reader.remaining() >= 4 {
+        match reader.read(4)? {
+            0 => break,
+            1 => {
+                let mut count = reader.read([10, 12, 14][size_class])? as usize;
+                while count > 0 {
+                    let digits = count.min(3);
+                    let value = reader.read([4, 7, 10][digits - 1])?;
+                    if value >= 10u32.pow(digits as u32) {
+                        return None;
+                    }
+                    bytes.extend(format!("{:0width$}", value, width = digits).bytes());
+                    count -= digits;
+                }
+            }
+            2 => {
+                let mut count = reader.read([9, 11, 13][size_class])? as usize;
+                while count >= 2 {
+                    let value = reader.read(11)? as usize;
+                    if value >= 45 * 45 {
+                        return None;
+                    }
+                    bytes.push(ALPHANUMERIC[value / 45]);
+                    bytes.push(ALPHANUMERIC[value % 45]);
+                    count -= 2;
+                }
+                if count == 1 {
+                    bytes.push(*ALPHANUMERIC.get(reader.read(6)? as usize)?);
+                }
+            }
+            4 => {
+                let count = reader.read([8, 16, 16][size_class])?;
+                for _ in 0..count {
+                    bytes.push(reader.read(8)? as u8);
+                }
+            }
+            8 => {
+                let count = reader.read([8, 10, 12][size_class])?;
+                let start = bytes.len();
+                for _ in 0..count {
+                    let value = reader.read(13)?;
+                    let packed = ((value / 0xC0) << 8) | (value % 0xC0);
+                    let sjis = packed + if packed < 0x1F00 { 0x8140 } else { 0xC140 };
+                    bytes.push((sjis >> 8) as u8);
+                    bytes.push(sjis as u8);
+                }
+                kanji.push((start, bytes.len()));
+            }
+            7 => {
+                let first = reader.read(8)?;
+                eci = Some(if first & 0x80 == 0 {
+                    first
+                } else if first & 0xC0 == 0x80 {
+                    ((first & 0x3F) << 8) | reader.read(8)?
+                } else {
+                    ((first & 0x1F) << 16) | reader.read(16)?
+                });
+            }
+            3 => {
+                reader.read(16)?; // 结构链接
+            }
+            5 => {}
+            9 => {
+                reader.read(8)?;
+            }
+            _ => return None,
+        }
+    }
+    Some(decode_payload(&bytes, &kanji, eci))
+}
+
+fn decode_grid(grid: &[bool], version: usize) -> Option<(String, usize)> {
+    let size = version * 4 + 17;
+    if !check_version(grid, size, version) {
+        return None;
+    }
+    let (ec, mask) = read_format(grid, size)?;
+    let codewords = read_codewords(grid, size, version, mask);
+    let data = correct_codewords(&codewords, version, ec)?;
+    Some((parse_segments(&data, version)?, ec))
+}
+
+// 尝试按估计的版本及相邻版本解码三个定位图案组成的码
+fn decode_finders(bits: &BitImage, finders: &[Finder; 3]) -> Option<QrCode> {
+    let module = (finders[0].module + finders[1].module + finders[2].module) / 3.0;
+    let modules = (distance(&finders[0], &finders[1]) + distance(&finders[0], &finders[2])) / 2.0 / module + 7.0;
+    let estimate = ((modules - 17.0) / 4.0).round() as i64;
+    for delta in [0, -1, 1, -2, 2] {
+        let version = estimate + delta;
+        if !(1..=40).contains(&version) {
+            continue;
+        }
+        let version = version as usize;
+        let Some((grid, transform)) = sample_grid(bits, finders, version) else {
+            continue;
+        };
+        if let Some((text, ec)) = decode_grid(&grid, version) {
+            let size = (version * 4 + 17) as f64;
+            let corner = |u: f64, v: f64| {
+                let (x, y) = transform.map(u, v);
+                (x as f32, y as f32)
+            };
+            return Some(QrCode {
+                text,
+                version: version as u8,
+                ec_level: ['L', 'M', 'Q', 'H'][ec],
+                corners: [corner(0.0, 0.0), corner(size, 0.0), corner(size, size), corner(0.0, size)],
+            });
+        }
+    }
+    None
+}
+
+fn decode_bits(bits: &BitImage, codes: &mut Vec<QrCode>) {
+    let finders = find_finders(bits);
+    let mut used = vec![false; finders.len()];
+    for i in 0..finders.len() {
+        for j in i + 1..finders.len() {
+            for k in j + 1..finders.len() {
+                if used[i] || used[j] || used[k] {
+                    continue;
+                }
+                let Some(ordered) = order_finders(finders[i], finders[j], finders[k]) else {
+                    continue;
+                };
+                if let Some(code) = decode_finders(bits, &ordered) {
+                    used[i] = true;
+                    used[j] = true;
+                    used[k] = true;
+                    codes.push(code);
+                }
+            }
+        }
+    }
+}
+
+// 识别图片中的所有 QR 码（阻塞），同时尝试深色码和反色（浅色码深色背景）
+pub fn decode(image: &GrayImage) -> Vec<QrCode> {
+    let bits = binarize(image);
+    let mut codes = Vec::new();
+    decode_bits(&bits, &mut codes);
+    let mut inverted = Vec::new();
+    decode_bits(&bits.inverted(), &mut inverted);
+    for code in inverted {
+        let duplicate = codes.iter().any(|existing| {
+            existing.text == code.text
+                && (existing.corners[0].0 - code.corners[0].0).abs() < 4.0
+                && (existing.corners[0].1 - code.corners[0].1).abs() < 4.0
+        });
+        if !duplicate {
+            codes.push(code);
+        }
+    }
+    codes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_background;
+    use image::{imageops, Luma};
+    use qrcode::{Color, EcLevel};
+
+    // 按模块绘制二维码（含 4 个模块的空白区），可反色，同时返回版本号
+    fn render(text: &str, level: EcLevel, scale: u32, inverted: bool) -> (GrayImage, u8) {
+        let code = qrcode::QrCode::with_error_correction_level(text, level).unwrap();
+        let size = code.width() as u32;
+        let colors = code.to_colors();
+        let total = (size + 8) * scale;
+        let image = GrayImage::from_fn(total, total, |x, y| {
+            let (mx, my) = ((x / scale) as i64 - 4, (y / scale) as i64 - 4);
+            let dark = mx >= 0
+                && my >= 0
+                && (mx as u32) < size
+                && (my as u32) < size
+                && colors[(my as u32 * size + mx as u32) as usize] == Color::Dark;
+            if dark != inverted { Luma([20]) } else { Luma([250]) }
+        });
+        let qrcode::Version::Normal(version) = code.version() else {
+            panic!("unexpected micro QR code");
+        };
+        (image, version as u8)
+    }
+
+    fn decode_texts(image: &GrayImage) -> Vec<String> {
+        let mut texts: Vec<String> = decode(image).into_iter().map(|code| code.text).collect();
+        texts.sort();
+        texts
+    }
+
+    #[test]
+    fn decodes_versions_levels_and_scales() {
+        let long_text = "二维码识别测试 https://example.com/path?query=value&lang=zh-CN ".repeat(8);
+        let medium_text: String = long_text.chars().take(120).collect();
+        let cases: [(&str, EcLevel, char); 7] = [
+            ("https://example.com/qr?id=12345", EcLevel::M, 'M'),
+            ("0123456789012345678901234567890", EcLevel::L, 'L'),
+            ("HELLO WORLD 123 $%*+-./:", EcLevel::Q, 'Q'),
+            ("截图里的二维码 — UTF-8 ✓", EcLevel::H, 'H'),
+            (&medium_text, EcLevel::M, 'M'),
+            (&long_text, EcLevel::L, 'L'),
+            (&long_text, EcLevel::H, 'H'),
+        ];
+        for (text, level, level_name) in cases {
+            for scale in [1, 3] {
+                let (code, version) = render(text, level, scale, false);
+                let mut canvas = test_background(code.width() + 300, code.height() + 200);
+                imageops::overlay(&mut canvas, &code, 200, 100);
+                let decoded = decode(&canvas);
+                assert_eq!(decoded.len(), 1, "version {} {:?} scale {}", version, level, scale);
+                assert_eq!(decoded[0].text, text);
+                assert_eq!(decoded[0].version, version);
+                assert_eq!(decoded[0].ec_level, level_name);
+            }
+        }
+    }
+
+    #[test]
+    fn decodes_non_integer_scale() {
+        // 非整数缩放（抗锯齿）
+        let text = "https://example.com/qr?id=12345";
+        let (code, _) = render(text, EcLevel::M, 4, false);
+        let scaled = imageops::resize(&code, code.width() * 73 / 100, code.height() * 73 / 100, imageops::FilterType::Triangle);
+        let mut canvas = test_background(600, 400);
+        imageops::overlay(&mut canvas, &scaled, 150, 60);
+        assert_eq!(decode_texts(&canvas), [text]);
+    }
+
+    #[test]
+    fn decodes_inverted_code() {
+        // 深色背景上的浅色码
+        let text = "HELLO WORLD 123 $%*+-./:";
+        let (code, _) = render(text, EcLevel::Q, 3, true);
+        assert_eq!(decode_texts(&code), [text]);
+    }
+
+    #[test]
+    fn corrects_damaged_modules() {
+        // 损坏的模块由 Reed-Solomon 纠正
+        let text = "截图里的二维码 — UTF-8 ✓";
+        let (mut code, _) = render(text, EcLevel::H, 4, false);
+        let center = code.width() / 2;
+        for y in center - 12..center + 12 {
+            for x in center + 4..center + 20 {
+                let value = code.get_pixel(x, y).0[0];
+                code.put_pixel(x, y, Luma([255 - value]));
+            }
+        }
+        assert_eq!(decode_texts(&code), [text]);
+    }
+
+    #[test]
+    fn decodes_multiple_codes() {
+        let (first, _) = render("first code", EcLevel::M, 3, false);
+        let (second, _) = render("second code", EcLevel::M, 2, false);
+        let mut canvas = test_background(800, 400);
+        imageops::overlay(&mut canvas, &first, 120, 40);
+        imageops::overlay(&mut canvas, &second, 500, 150);
+        assert_eq!(decode_texts(&canvas), ["first code", "second code"]);
+    }
+
+    #[test]
+    fn ignores_images_without_codes() {
+        assert!(decode(&test_background(800, 600)).is_empty());
+    }
+}
@@ -0,0 +1,122 @@
+// 屏幕取色：返回鼠标所在（或指定桌面坐标）像素的颜色（HEX / RGB / HSL），以及周围像素放大后的
+// 放大镜图像，供前端绘制取色放大镜；只截取目标点附近的一小块区域，可随鼠标移动频繁调用
+// 坐标为桌面物理像素（与区域截图的 desktop_x / desktop_y 一致），多显示器和缩放比例不同时按目标点所在的显示器换算
+pub mod loupe;
+
+use base64::{engine::general_purpose, Engine};
+use image::{ImageEncoder, RgbaImage};
+use serde::{Deserialize, Serialize};
+use tauri::command;
+
+use super::monitors::{self, CaptureRegion};
+use super::{cursor, pipeline};
+use loupe::ColorValue;
+
+const DEFAULT_RADIUS: u32 = 7;
+const MAX_RADIUS: u32 = 32;
+const DEFAULT_ZOOM: u32 = 10;
+const MAX_ZOOM: u32 = 32;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PickOptions {
+    pub radius: Option<u32>, // 放大镜显示目标点周围多少像素，默认 7（即 15 × 15）
+    pub zoom: Option<u32>,   // 每个像素放大的倍数，默认 10
+    pub grid: Option<bool>,  // 是否绘制像素网格，默认 true
+    pub loupe: Option<bool>, // 是否生成放大镜图像，只取色时可关闭，默认 true
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PickedColor {
+    pub x: i32, // 桌面物理像素
+    pub y: i32,
+    pub monitor: usize,
+    pub scale_factor: f32,
+    #[serde(flatten)]
+    pub color: ColorValue,
+    pub loupe: Option<String>, // base64 编码的 PNG，目标像素位于正中
+    pub loupe_size: u32,       // 放大镜图像的边长（像素）
+}
+
+// 查找包含该桌面物理坐标的显示器，返回索引和相对于显示器左上角的物理坐标
+fn locate(screens: &[screenshots::Screen], x: i32, y: i32) -> Option<(usize, i32, i32)> {
+    screens.iter().enumerate().find_map(|(index, screen)| {
+        let info = &screen.display_info;
+        let (left, top) = monitors::physical_origin(screen);
+        let width = (info.width as f32 * info.scale_factor).round() as i32;
+        let height = (info.height as f32 * info.scale_factor).round() as i32;
+        let (local_x, local_y) = (x - left, y - top);
+        (local_x >= 0 && local_y >= 0 && local_x < width && local_y < height).then_some((index, local_x, local_y))
+    })
+}
+
+fn encode_png(image: &RgbaImage) -> Result<String, String> {
+    let mut buffer = Vec::new();
+    image::codecs::png::PngEncoder::new(&mut buffer)
+        .write_image(image.as_raw(), image.width(), image.height(), image::ColorType::Rgba8)
+        .map_err(|e| format!("Failed to encode loupe: {}", e))?;
+    Ok(general_purpose::STANDARD.encode(buffer))
+}
+
+// 取色（阻塞），未指定坐标时使用当前鼠标位置
+pub fn pick(point: Option<(i32, i32)>, options: &PickOptions) -> Result<PickedColor, String> {
+    let (x, y) = match point {
+        Some(point) => point,
+        None => cursor::cursor_position().ok_or("Cursor position is unavailable, coordinates are required")?,
+    };
+    let radius = options.radius.unwrap_or(DEFAULT_RADIUS).min(MAX_RADIUS);
+    let zoom = options.zoom.unwrap_or(DEFAULT_ZOOM).clamp(1, MAX_ZOOM);
+
+    let screens = monitors::list_screens()?;
+    let (index, local_x, local_y) = locate(&screens, x, y).ok_or_else(|| format!("No monitor at ({}, {})", x, y))?;
+    let info = &screens[index].display_info;
+    let scale = if info.scale_factor > 0.0 { info.scale_factor } else { 1.0 };
+
+    // 截取覆盖邻域的逻辑区域（截图接口以逻辑坐标指定区域），超出显示器的部分在放大镜中显示为棋盘格
+    let to_logical = |value: i32| value as f32 / scale;
+    let left = to_logical(local_x - radius as i32).floor().max(0.0) as i32;
+    let top = to_logical(local_y - radius as i32).floor().max(0.0) as i32;
+    let right = (to_logical(local_x + radius as i32 + 1).ceil() as i32).min(info.width as i32);
+    let bottom = (to_logical(local_y + radius as i32 + 1).ceil() as i32).min(info.height as i32);
+    let region = CaptureRegion {
+        x: left,
+        y: top,
+        width: (right - left).max(1) as u32,
+        height: (bottom - top).max(1) as u32,
+    };
+    let image = monitors::capture_region_image(&screens[index], &region)?;
+
+    // 截图的实际像素比例可能与缩放比例略有差异，按截图尺寸换算目标点在截图中的位置
+    let pixel_scale_x = image.width() as f32 / region.width as f32;
+    let pixel_scale_y = image.height() as f32 / region.height as f32;
+    let center_x = (((local_x as f32 + 0.5) / scale - left as f32) * pixel_scale_x).floor() as i64;
+    let center_y = (((local_y as f32 + 0.5) / scale - top as f32) * pixel_scale_y).floor() as i64;
+    let center_x = center_x.clamp(0, image.width() as i64 - 1);
+    let center_y = center_y.clamp(0, image.height() as i64 - 1);
+
+    let pixels = loupe::neighborhood(&image, center_x, center_y, radius);
+    let color = loupe::color_value(*image.get_pixel(center_x as u32, center_y as u32));
+    let (loupe_image, loupe_size) = if options.loupe.unwrap_or(true) {
+        let rendered = loupe::render_loupe(&pixels, zoom, options.grid.unwrap_or(true));
+        (Some(encode_png(&rendered)?), rendered.width())
+    } else {
+        (None, 0)
+    };
+
+    Ok(PickedColor {
+        x,
+        y,
+        monitor: index,
+        scale_factor: info.scale_factor,
+        color,
+        loupe: loupe_image,
+        loupe_size,
+    })
+}
+
+// 屏幕取色，x / y 为桌面物理像素，省略时取鼠标所在位置
+#[command]
+pub async fn pick_color(x: Option<i32>, y: Option<i32>, options: Option<PickOptions>) -> Result<PickedColor, String> {
+    let point = x.zip(y);
+    let options = options.unwrap_or_default();
+    pipeline::run_blocking(move || pick(point, &options)).await
+}
@@ -0,0 +1,174 @@
+// 取色与放大镜：颜色的 HEX / RGB / HSL 表示，取出目标像素周围的邻域，
+// 并按最近邻放大绘制为放大镜图像（网格线、中心像素框，屏幕外的像素显示为棋盘格）
+use image::{Rgba, RgbaImage};
+use serde::Serialize;
+
+const GRID_COLOR: Rgba<u8> = Rgba([0, 0, 0, 40]);
+const CHECKER_LIGHT: Rgba<u8> = Rgba([204, 204, 204, 255]);
+const CHECKER_DARK: Rgba<u8> = Rgba([153, 153, 153, 255]);
+// 放大倍数不小于该值时才绘制网格线
+const MIN_GRID_ZOOM: u32 = 6;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ColorValue {
+    pub hex: String,     // #RRGGBB
+    pub rgb: [u8; 3],
+    pub hsl: [f32; 3],   // 色相 0-360，饱和度和亮度 0-100，保留一位小数
+}
+
+pub fn color_value(pixel: Rgba<u8>) -> ColorValue {
+    let [r, g, b, _] = pixel.0;
+    ColorValue {
+        hex: format!("#{:02X}{:02X}{:02X}", r, g, b),
+        rgb: [r, g, b],
+        hsl: rgb_to_hsl([r, g, b]),
+    }
+}
+
+pub fn rgb_to_hsl([r, g, b]: [u8; 3]) -> [f32; 3] {
+    let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let lightness = (max + min) / 2.0;
+    let delta = max - min;
+    if delta == 0.0 {
+        return [0.0, 0.0, round1(lightness * 100.0)];
+    }
+    let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+    let hue = if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    [round1(hue) % 360.0, round1(saturation * 100.0), round1(lightness * 100.0)]
+}
+
+fn round1(value: f32) -> f32 {
+    (value * 10.0).round() / 10.0
+}
+
+// 以 (cx, cy) 为中心取 (2 × radius + 1) 见方的邻域，超出图像的像素为全透明
+pub fn neighborhood(image: &RgbaImage, cx: i64, cy: i64, radius: u32) -> RgbaImage {
+    let size = radius * 2 + 1;
+    RgbaImage::from_fn(size, size, |x, y| {
+        let (sx, sy) = (cx + x as i64 - radius as i64, cy + y as i64 - radius as i64);
+        if sx < 0 || sy < 0 || sx >= image.width() as i64 || sy >= image.height() as i64 {
+            Rgba([0, 0, 0, 0])
+        } else {
+            *image.get_pixel(sx as u32, sy as u32)
+        }
+    })
+}
+
+// 按最近邻放大邻域，每个像素为 zoom × zoom 的方块；中心像素用与其颜色反差较大的边框标出
+pub fn render_loupe(pixels: &RgbaImage, zoom: u32, grid: bool) -> RgbaImage {
+    let zoom = zoom.max(1);
+    let (width, height) = (pixels.width() * zoom, pixels.height() * zoom);
+    let (center_x, center_y) = (pixels.width() / 2, pixels.height() / 2);
+    let center = *pixels.get_pixel(center_x, center_y);
+    let luminance = 0.299 * center.0[0] as f32 + 0.587 * center.0[1] as f32 + 0.114 * center.0[2] as f32;
+    let outline = if luminance > 128.0 { Rgba([0, 0, 0, 255]) } else { Rgba([255, 255, 255, 255]) };
+    let draw_grid = grid && zoom >= MIN_GRID_ZOOM;
+
+    RgbaImage::from_fn(width, height, |x, y| {
+        let (px, py) = (x / zoom, y / zoom);
+        let (dx, dy) = (x % zoom, y % zoom);
+        if px == center_x && py == center_y && (dx == 0 || dy == 0 || dx == zoom - 1 || dy == zoom - 1) {
+            return outline;
+        }
+        let pixel = *pixels.get_pixel(px, py);
+        let mut color = if pixel.0[3] == 0 {
+            // 屏幕外：4 像素的棋盘格
+            if (x / 4 + y / 4).is_multiple_of(2) { CHECKER_LIGHT } else { CHECKER_DARK }
+        } else {
+            Rgba([pixel.0[0], pixel.0[1], pixel.0[2], 255])
+        };
+        if draw_grid && (dx == 0 || dy == 0) {
+            blend(&mut color, GRID_COLOR);
+        }
+        color
+    })
+}
+
+fn blend(base: &mut Rgba<u8>, over: Rgba<u8>) {
+    let alpha = over.0[3] as u32;
+    for channel in 0..3 {
+        base.0[channel] = ((base.0[channel] as u32 * (255 - alpha) + over.0[channel] as u32 * alpha) / 255) as u8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 每个像素颜色不同的测试图
+    fn test_image() -> RgbaImage {
+        RgbaImage::from_fn(40, 30, |x, y| Rgba([x as u8 * 6, y as u8 * 8, 100, 255]))
+    }
+
+    #[test]
+    fn converts_hex_and_hsl() {
+        let cases: [([u8; 3], &str, [f32; 3]); 8] = [
+            ([0, 0, 0], "#000000", [0.0, 0.0, 0.0]),
+            ([255, 255, 255], "#FFFFFF", [0.0, 0.0, 100.0]),
+            ([255, 0, 0], "#FF0000", [0.0, 100.0, 50.0]),
+            ([0, 255, 0], "#00FF00", [120.0, 100.0, 50.0]),
+            ([0, 0, 255], "#0000FF", [240.0, 100.0, 50.0]),
+            ([102, 126, 234], "#667EEA", [229.1, 75.9, 65.9]),
+            ([66, 185, 131], "#42B983", [152.8, 47.4, 49.2]),
+            ([255, 0, 128], "#FF0080", [329.9, 100.0, 50.0]),
+        ];
+        for (rgb, hex, hsl) in cases {
+            let color = color_value(Rgba([rgb[0], rgb[1], rgb[2], 255]));
+            assert_eq!(color.hex, hex);
+            assert_eq!(color.rgb, rgb);
+            assert!(color.hsl.iter().zip(hsl).all(|(a, b)| (a - b).abs() < 0.15), "{}: {:?}", hex, color.hsl);
+        }
+    }
+
+    #[test]
+    fn neighborhood_is_centered_on_target() {
+        let image = test_image();
+        let pixels = neighborhood(&image, 10, 12, 3);
+        assert_eq!(pixels.dimensions(), (7, 7));
+        assert_eq!(pixels.get_pixel(3, 3), image.get_pixel(10, 12));
+        assert_eq!(pixels.get_pixel(0, 0), image.get_pixel(7, 9));
+    }
+
+    #[test]
+    fn neighborhood_outside_image_is_transparent() {
+        let image = test_image();
+        let corner = neighborhood(&image, 0, 29, 2);
+        assert_eq!(corner.get_pixel(0, 0).0[3], 0);
+        assert_eq!(corner.get_pixel(4, 4).0[3], 0);
+        assert_eq!(corner.get_pixel(2, 2), image.get_pixel(0, 29));
+    }
+
+    #[test]
+    fn renders_loupe_with_grid_and_center_outline() {
+        let pixels = neighborhood(&test_image(), 10, 12, 3);
+        let rendered = render_loupe(&pixels, 10, true);
+        assert_eq!(rendered.dimensions(), (70, 70));
+        // 非网格线、非中心框的位置保持原色
+        assert_eq!(rendered.get_pixel(15, 25), pixels.get_pixel(1, 2));
+        let outline = *rendered.get_pixel(30, 35);
+        assert!(outline == Rgba([255, 255, 255, 255]) || outline == Rgba([0, 0, 0, 255]));
+        assert_eq!(rendered.get_pixel(35, 35), pixels.get_pixel(3, 3));
+        assert_ne!(rendered.get_pixel(10, 15), pixels.get_pixel(1, 1));
+
+        let plain = render_loupe(&pixels, 10, false);
+        assert_eq!(plain.get_pixel(10, 15), pixels.get_pixel(1, 1));
+    }
+
+    #[test]
+    fn renders_checkerboard_outside_image() {
+        let corner = neighborhood(&test_image(), 0, 29, 2);
+        let rendered = render_loupe(&corner, 10, true);
+        let (a, b) = (*rendered.get_pixel(1, 1), *rendered.get_pixel(5, 1));
+        assert_eq!(a.0[3], 255);
+        assert_eq!(b.0[3], 255);
+        assert_ne!(a, b);
+    }
+}
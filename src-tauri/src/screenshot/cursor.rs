@@ -0,0 +1,130 @@
+// 鼠标指针：查询指针位置（用于选择鼠标所在的显示器），以及把指针图像合成到截图中
+// Linux 下通过 X11 QueryPointer / XFixes GetCursorImage 实现，Windows 下只支持查询指针位置
+use image::{imageops, RgbaImage};
+use screenshots::Screen;
+
+#[cfg(target_os = "linux")]
+use xcb::{x, xfixes, Extension};
+#[cfg(windows)]
+use windows::Win32::Foundation::POINT;
+#[cfg(windows)]
+use windows::Win32::UI::WindowsAndMessaging::GetCursorPos;
+
+// 指针图像，x / y 为图像左上角（已减去热点）在桌面上的物理像素坐标
+pub struct CursorImage {
+    pub x: i32,
+    pub y: i32,
+    pub image: RgbaImage,
+}
+
+// 查询指针位置（X11 根窗口坐标，物理像素）
+#[cfg(target_os = "linux")]
+pub fn cursor_position() -> Option<(i32, i32)> {
+    let (conn, screen_num) = xcb::Connection::connect(None).ok()?;
+    let root = conn.get_setup().roots().nth(screen_num as usize)?.root();
+    let cookie = conn.send_request(&x::QueryPointer { window: root });
+    let reply = conn.wait_for_reply(cookie).ok()?;
+    Some((reply.root_x() as i32, reply.root_y() as i32))
+}
+
+// Windows 下进程按 DPI 感知运行，GetCursorPos 返回物理像素
+#[cfg(windows)]
+pub fn cursor_position() -> Option<(i32, i32)> {
+    let mut point = POINT { x: 0, y: 0 };
+    unsafe { GetCursorPos(&mut point) }.ok()?;
+    Some((point.x, point.y))
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn cursor_position() -> Option<(i32, i32)> {
+    None
+}
+
+// 根据指针位置查找所在屏幕
+// display-info 在 X11 下返回的是逻辑坐标（物理坐标 / 缩放比例），需换算后再比较
+#[cfg(target_os = "linux")]
+pub fn screen_at_cursor(screens: &[Screen]) -> Option<usize> {
+    let (px, py) = cursor_position()?;
+    screens.iter().position(|screen| {
+        let info = &screen.display_info;
+        let scale = if info.scale_factor > 0.0 { info.scale_factor } else { 1.0 };
+        let (x, y) = (px as f32 / scale, py as f32 / scale);
+        x >= info.x as f32
+            && x < (info.x + info.width as i32) as f32
+            && y >= info.y as f32
+            && y < (info.y + info.height as i32) as f32
+    })
+}
+
+// 获取当前指针图像（XFixes）
+#[cfg(target_os = "linux")]
+pub fn cursor_image() -> Option<CursorImage> {
+    let (conn, _) = xcb::Connection::connect_with_extensions(None, &[], &[Extension::XFixes]).ok()?;
+    if !conn.active_extensions().any(|ext| ext == Extension::XFixes) {
+        return None;
+    }
+
+    // 使用 XFixes 前必须先声明客户端支持的版本
+    let cookie = conn.send_request(&xfixes::QueryVersion {
+        client_major_version: 4,
+        client_minor_version: 0,
+    });
+    conn.wait_for_reply(cookie).ok()?;
+
+    let cookie = conn.send_request(&xfixes::GetCursorImage {});
+    let reply = conn.wait_for_reply(cookie).ok()?;
+    let (width, height) = (reply.width() as u32, reply.height() as u32);
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    // 像素为预乘 alpha 的 ARGB，转换为非预乘的 RGBA
+    let mut image = RgbaImage::new(width, height);
+    for (pixel, &argb) in image.pixels_mut().zip(reply.cursor_image()) {
+        let a = (argb >> 24) as u8;
+        let unpremultiply = |c: u32| -> u8 {
+            if a == 0 {
+                0
+            } else {
+                ((c & 0xff) * 255 / a as u32).min(255) as u8
+            }
+        };
+        pixel.0 = [unpremultiply(argb >> 16), unpremultiply(argb >> 8), unpremultiply(argb), a];
+    }
+
+    Some(CursorImage {
+        x: reply.x() as i32 - reply.xhot() as i32,
+        y: reply.y() as i32 - reply.yhot() as i32,
+        image,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn cursor_image() -> Option<CursorImage> {
+    None
+}
+
+// 将指针合成到截图中
+// origin 为截图左上角的逻辑坐标，pixel_scale 为截图中每个逻辑单位对应的像素数，
+// desktop_scale 为桌面物理像素与逻辑坐标之比（X11 下即 Xft.dpi 缩放比例）
+pub fn draw_cursor(
+    target: &mut RgbaImage,
+    cursor: &CursorImage,
+    origin: (i32, i32),
+    pixel_scale: f32,
+    desktop_scale: f32,
+) {
+    let ratio = pixel_scale / desktop_scale;
+    let left = ((cursor.x as f32 / desktop_scale - origin.0 as f32) * pixel_scale).round() as i64;
+    let top = ((cursor.y as f32 / desktop_scale - origin.1 as f32) * pixel_scale).round() as i64;
+
+    // 截图与桌面像素比例不同（例如拼接时放大的低 DPI 显示器）时同步缩放指针
+    if (ratio - 1.0).abs() > 0.01 {
+        let width = ((cursor.image.width() as f32 * ratio).round() as u32).max(1);
+        let height = ((cursor.image.height() as f32 * ratio).round() as u32).max(1);
+        let resized = imageops::resize(&cursor.image, width, height, imageops::FilterType::Triangle);
+        imageops::overlay(target, &resized, left, top);
+    } else {
+        imageops::overlay(target, &cursor.image, left, top);
+    }
+}
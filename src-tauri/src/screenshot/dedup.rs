@@ -0,0 +1,377 @@
+// 截图库去重：计算感知哈希（dHash / pHash），按汉明距离把近似重复的截图分组
+use std::collections::HashMap;
+use std::path::Path;
+use image::{imageops, imageops::FilterType, GrayImage};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle};
+
+use super::pipeline;
+use super::library::{ensure_column, list_screenshots, open_db, trash_records, ScreenshotRecord, TrashResult};
+
+// 默认汉明距离阈值（64 位哈希），不超过该值视为近似重复
+const DEFAULT_MAX_DISTANCE: u32 = 5;
+
+#[derive(Debug, Clone, Copy, Deserialize, Default)]
+pub enum HashAlgorithm {
+    #[serde(rename = "dhash")]
+    #[default]
+    DHash,
+    #[serde(rename = "phash")]
+    PHash,
+}
+
+impl HashAlgorithm {
+    // 缓存哈希值的列名
+    fn column(self) -> &'static str {
+        match self {
+            HashAlgorithm::DHash => "dhash",
+            HashAlgorithm::PHash => "phash",
+        }
+    }
+
+    fn compute(self, image: &GrayImage) -> u64 {
+        match self {
+            HashAlgorithm::DHash => dhash(image),
+            HashAlgorithm::PHash => phash(image),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateEntry {
+    #[serde(flatten)]
+    pub record: ScreenshotRecord,
+    pub hash: String,
+    pub distance: u32, // 与组内保留项的汉明距离
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateGroup {
+    pub keep_id: i64, // 建议保留的截图（分辨率最高，其次最新）
+    pub entries: Vec<DuplicateEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateReport {
+    pub groups: Vec<DuplicateGroup>,
+    pub hashed: usize,
+    pub missing: Vec<i64>, // 文件已不存在的记录
+    pub failed: Vec<i64>,  // 无法解码的记录
+}
+
+// dHash：缩放到 9x8，比较相邻像素亮度
+fn dhash(image: &GrayImage) -> u64 {
+    let small = imageops::resize(image, 9, 8, FilterType::Triangle);
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if small.get_pixel(x, y).0[0] < small.get_pixel(x + 1, y).0[0] {
+                hash |= 1;
+            }
+        }
+    }
+    hash
+}
+
+// pHash：缩放到 32x32 做二维 DCT，取左上角 8x8 低频系数与中位数比较
+fn phash(image: &GrayImage) -> u64 {
+    const N: usize = 32;
+    let small = imageops::resize(image, N as u32, N as u32, FilterType::Triangle);
+    let pixels: Vec<f64> = small.as_raw().iter().map(|&p| p as f64).collect();
+
+    let cos_table: Vec<f64> = (0..8 * N)
+        .map(|i| {
+            let (u, x) = (i / N, i % N);
+            ((2 * x + 1) as f64 * u as f64 * std::f64::consts::PI / (2 * N) as f64).cos()
+        })
+        .collect();
+
+    // 先按行再按列，只计算需要的 8x8 低频部分
+    let mut rows = vec![0.0; N * 8];
+    for y in 0..N {
+        for u in 0..8 {
+            rows[y * 8 + u] = (0..N).map(|x| pixels[y * N + x] * cos_table[u * N + x]).sum();
+        }
+    }
+    let mut coeffs = [0.0f64; 64];
+    for v in 0..8 {
+        for u in 0..8 {
+            coeffs[v * 8 + u] = (0..N).map(|y| rows[y * 8 + u] * cos_table[v * N + y]).sum();
+        }
+    }
+
+    // 直流分量不参与中位数计算
+    let mut sorted: Vec<f64> = coeffs[1..].to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let median = sorted[sorted.len() / 2];
+
+    coeffs.iter().fold(0u64, |hash, &c| (hash << 1) | (c > median) as u64)
+}
+
+fn hash_file(path: &Path, algorithm: HashAlgorithm) -> Result<u64, String> {
+    let image = image::open(path).map_err(|e| format!("Failed to decode image: {}", e))?;
+    // 先快速缩小再转灰度，避免对整张 4K 图做滤波
+    let gray = image.thumbnail(256, 256).to_luma8();
+    Ok(algorithm.compute(&gray))
+}
+
+// 保留优先级：分辨率最高，其次最新
+fn keep_priority(record: &ScreenshotRecord) -> (u64, &str, i64) {
+    (record.width as u64 * record.height as u64, record.created_at.as_str(), record.id)
+}
+
+// 围绕保留项分组：按优先级从高到低取出尚未分组的截图作为保留项，
+// 只把与它距离不超过阈值的截图归入该组，保证组内每一项都是保留项的近似重复
+fn group_duplicates(mut hashed: Vec<(ScreenshotRecord, u64)>, max_distance: u32) -> Vec<DuplicateGroup> {
+    hashed.sort_by(|a, b| keep_priority(&b.0).cmp(&keep_priority(&a.0)));
+    let mut grouped = vec![false; hashed.len()];
+    let mut groups = Vec::new();
+
+    for keep in 0..hashed.len() {
+        if grouped[keep] {
+            continue;
+        }
+        let keep_hash = hashed[keep].1;
+        let members: Vec<usize> = (keep..hashed.len())
+            .filter(|&i| !grouped[i] && (hashed[i].1 ^ keep_hash).count_ones() <= max_distance)
+            .collect();
+        if members.len() < 2 {
+            continue;
+        }
+
+        for &i in &members {
+            grouped[i] = true;
+        }
+        groups.push(DuplicateGroup {
+            keep_id: hashed[keep].0.id,
+            entries: members
+                .iter()
+                .map(|&i| DuplicateEntry {
+                    record: hashed[i].0.clone(),
+                    hash: format!("{:016x}", hashed[i].1),
+                    distance: (hashed[i].1 ^ keep_hash).count_ones(),
+                })
+                .collect(),
+        });
+    }
+    groups.sort_by_key(|group| std::cmp::Reverse(group.entries.len()));
+    groups
+}
+
+fn build_report(
+    conn: &Connection,
+    algorithm: HashAlgorithm,
+    max_distance: u32,
+) -> Result<DuplicateReport, String> {
+    let column = algorithm.column();
+    ensure_column(conn, "screenshots", column, "TEXT")?;
+
+    // 读取已缓存的哈希
+    let mut cached: HashMap<i64, u64> = HashMap::new();
+    {
+        let mut stmt = conn
+            .prepare(&format!("SELECT id, {} FROM screenshots WHERE {} IS NOT NULL", column, column))
+            .map_err(|e| format!("Failed to query hashes: {}", e))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+            .map_err(|e| format!("Failed to query hashes: {}", e))?;
+        for (id, hex) in rows.flatten() {
+            if let Ok(hash) = u64::from_str_radix(&hex, 16) {
+                cached.insert(id, hash);
+            }
+        }
+    }
+
+    let mut hashed: Vec<(ScreenshotRecord, u64)> = Vec::new();
+    let mut missing = Vec::new();
+    let mut failed = Vec::new();
+
+    for record in list_screenshots(conn)? {
+        let path = Path::new(&record.file_path);
+        if !path.exists() {
+            missing.push(record.id);
+            continue;
+        }
+
+        let hash = match cached.get(&record.id) {
+            Some(&hash) => hash,
+            None => match hash_file(path, algorithm) {
+                Ok(hash) => {
+                    conn.execute(
+                        &format!("UPDATE screenshots SET {} = ?1 WHERE id = ?2", column),
+                        params![format!("{:016x}", hash), record.id],
+                    ).map_err(|e| format!("Failed to cache hash: {}", e))?;
+                    hash
+                }
+                Err(e) => {
+                    eprintln!("计算截图哈希失败 {}: {}", record.file_path, e);
+                    failed.push(record.id);
+                    continue;
+                }
+            },
+        };
+
+        hashed.push((record, hash));
+    }
+
+    let total = hashed.len();
+    let groups = group_duplicates(hashed, max_distance);
+
+    Ok(DuplicateReport {
+        groups,
+        hashed: total,
+        missing,
+        failed,
+    })
+}
+
+// 扫描截图库，返回近似重复的分组；调用方选定每组保留项后通过 trash_screenshots 清理其余截图
+#[command]
+pub async fn find_duplicate_screenshots(
+    app: AppHandle,
+    algorithm: Option<HashAlgorithm>,
+    max_distance: Option<u32>,
+) -> Result<DuplicateReport, String> {
+    pipeline::run_blocking(move || {
+        let conn = open_db(&app)?;
+        build_report(
+            &conn,
+            algorithm.unwrap_or_default(),
+            max_distance.unwrap_or(DEFAULT_MAX_DISTANCE),
+        )
+    })
+    .await
+}
+
+// 按建议一次性清理：每组保留 keep_id，其余移入回收站
+#[command]
+pub async fn remove_duplicate_screenshots(
+    app: AppHandle,
+    algorithm: Option<HashAlgorithm>,
+    max_distance: Option<u32>,
+) -> Result<TrashResult, String> {
+    pipeline::run_blocking(move || {
+        let conn = open_db(&app)?;
+        let max_distance = max_distance.unwrap_or(DEFAULT_MAX_DISTANCE);
+        let report = build_report(&conn, algorithm.unwrap_or_default(), max_distance)?;
+
+        // 只清理与保留项本身足够接近的截图
+        let ids: Vec<i64> = report
+            .groups
+            .iter()
+            .flat_map(|group| {
+                group
+                    .entries
+                    .iter()
+                    .filter(move |e| e.record.id != group.keep_id && e.distance <= max_distance)
+                    .map(|e| e.record.id)
+            })
+            .collect();
+        trash_records(&app, &conn, &ids)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+
+    fn record(id: i64, width: u32, created_at: &str) -> ScreenshotRecord {
+        ScreenshotRecord {
+            id,
+            file_path: format!("/tmp/{}.jpg", id),
+            width,
+            height: 100,
+            created_at: created_at.to_string(),
+        }
+    }
+
+    // 平滑起伏的测试图，避免大片相同亮度让 dHash 的比较结果不稳定
+    fn test_image(width: u32, height: u32) -> GrayImage {
+        GrayImage::from_fn(width, height, |x, y| {
+            let (u, v) = (x as f32 / width as f32, y as f32 / height as f32);
+            Luma([(128.0 + 60.0 * (u * 7.0).sin() + 50.0 * (v * 5.0 + u * 2.0).cos()) as u8])
+        })
+    }
+
+    #[test]
+    fn hashes_are_stable_under_resize_and_noise() {
+        let image = test_image(640, 480);
+        let resized = imageops::resize(&image, 320, 240, FilterType::Triangle);
+        let mut noisy = image.clone();
+        for (index, pixel) in noisy.pixels_mut().enumerate() {
+            pixel.0[0] = pixel.0[0].saturating_add((index % 5) as u8);
+        }
+        for algorithm in [HashAlgorithm::DHash, HashAlgorithm::PHash] {
+            let hash = algorithm.compute(&image);
+            assert!((hash ^ algorithm.compute(&resized)).count_ones() <= DEFAULT_MAX_DISTANCE);
+            assert!((hash ^ algorithm.compute(&noisy)).count_ones() <= DEFAULT_MAX_DISTANCE);
+        }
+    }
+
+    #[test]
+    fn hashes_differ_for_different_images() {
+        let image = test_image(640, 480);
+        let flipped = imageops::flip_horizontal(&image);
+        for algorithm in [HashAlgorithm::DHash, HashAlgorithm::PHash] {
+            assert!((algorithm.compute(&image) ^ algorithm.compute(&flipped)).count_ones() > DEFAULT_MAX_DISTANCE * 2);
+        }
+    }
+
+    #[test]
+    fn groups_around_highest_resolution() {
+        let groups = group_duplicates(
+            vec![
+                (record(1, 100, "2026-01-01"), 0b0000),
+                (record(2, 300, "2026-01-01"), 0b0001),
+                (record(3, 200, "2026-01-02"), 0b0011),
+                (record(4, 100, "2026-01-03"), u64::MAX),
+            ],
+            2,
+        );
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].keep_id, 2);
+        let mut ids: Vec<i64> = groups[0].entries.iter().map(|e| e.record.id).collect();
+        ids.sort();
+        assert_eq!(ids, [1, 2, 3]);
+    }
+
+    #[test]
+    fn chained_near_duplicates_do_not_merge() {
+        // A~B、B~C，但 A 与 C 相距 8，不能因为 B 而归入同一组
+        let (a, b, c) = (0u64, 0x0Fu64, 0xFFu64);
+        let groups = group_duplicates(
+            vec![
+                (record(1, 300, "2026-01-01"), a),
+                (record(2, 200, "2026-01-01"), b),
+                (record(3, 100, "2026-01-01"), c),
+            ],
+            4,
+        );
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].keep_id, 1);
+        let ids: Vec<i64> = groups[0].entries.iter().map(|e| e.record.id).collect();
+        assert_eq!(ids, [1, 2]);
+        assert!(groups.iter().flat_map(|g| &g.entries).all(|e| e.distance <= 4));
+    }
+
+    #[test]
+    fn chain_is_split_into_groups_around_each_keep() {
+        // 保留项在链的一端时，链上较远的截图另外分组，最远的一张没有近似重复
+        let hashes = [0u64, 0x3, 0xF, 0x3F, 0xFF];
+        let hashed = hashes
+            .iter()
+            .enumerate()
+            .map(|(i, &hash)| (record(i as i64 + 1, 500 - i as u32 * 100, "2026-01-01"), hash))
+            .collect();
+        let groups = group_duplicates(hashed, 2);
+        let ids: Vec<(i64, Vec<i64>)> = groups
+            .iter()
+            .map(|group| (group.keep_id, group.entries.iter().map(|e| e.record.id).collect()))
+            .collect();
+        assert_eq!(ids, [(1, vec![1, 2]), (3, vec![3, 4])]);
+    }
+}
@@ -0,0 +1,213 @@
+// 延时截图：倒计时期间显示一个小的倒计时窗口，截图前关闭该窗口，避免出现在截图中
+// 倒计时可通过命令、倒计时窗口中的 ESC 或再次按下截图快捷键取消
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use tauri::{command, AppHandle, Emitter, Manager, State};
+use tauri::webview::WebviewWindowBuilder;
+
+use super::{get_cursor_screen, monitors, pipeline};
+
+// 倒计时窗口 label（匹配 capabilities 中的 screenshot_*）
+pub const COUNTDOWN_WINDOW: &str = "screenshot_countdown";
+// 最长延时
+pub const MAX_DELAY_SECS: u32 = 60;
+// 关闭倒计时窗口后等待其从屏幕上消失（合成器淡出动画）
+const WINDOW_CLOSE_GRACE: Duration = Duration::from_millis(250);
+
+const COUNTDOWN_WIDTH: f64 = 120.0;
+const COUNTDOWN_HEIGHT: f64 = 120.0;
+const COUNTDOWN_MARGIN: f64 = 40.0;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CountdownEvent {
+    pub remaining: u32, // 剩余秒数，0 表示即将截图
+    pub total: u32,
+    pub cancelled: bool,
+}
+
+#[derive(Default)]
+struct DelayState {
+    session: u64,
+    pending: bool,
+}
+
+pub struct DelayedCapture {
+    // 每次开始或取消倒计时都递增会话号，等待中的线程发现会话号变化后退出
+    state: Arc<Mutex<DelayState>>,
+}
+
+impl DelayedCapture {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(DelayState::default())),
+        }
+    }
+
+    fn begin(&self) -> Result<u64, String> {
+        let mut state = self.state.lock().unwrap();
+        if state.pending {
+            return Err("A delayed capture is already pending".to_string());
+        }
+        state.session += 1;
+        state.pending = true;
+        Ok(state.session)
+    }
+
+    fn finish(&self, session: u64) {
+        let mut state = self.state.lock().unwrap();
+        if state.session == session {
+            state.pending = false;
+        }
+    }
+
+    fn is_current(&self, session: u64) -> bool {
+        let state = self.state.lock().unwrap();
+        state.pending && state.session == session
+    }
+
+    // 取消正在进行的倒计时，没有倒计时时返回 false
+    pub fn cancel(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if !state.pending {
+            return false;
+        }
+        state.session += 1;
+        state.pending = false;
+        true
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.state.lock().unwrap().pending
+    }
+}
+
+// 在鼠标所在屏幕的右上角打开倒计时窗口
+fn open_countdown_window(app: &AppHandle, delay_secs: u32) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(COUNTDOWN_WINDOW) {
+        let _ = window.destroy();
+    }
+
+    // 按目标显示器的缩放换算为物理像素
+    let placement = monitors::list_screens().ok().and_then(|screens| {
+        let screen = screens.get(get_cursor_screen(&screens)?)?;
+        let info = &screen.display_info;
+        let scale = info.scale_factor as f64;
+        let (origin_x, origin_y) = monitors::physical_origin(screen);
+        let physical = |logical: f64| (logical * scale).round() as i32;
+        Some((
+            (
+                origin_x + physical(info.width as f64 - COUNTDOWN_WIDTH - COUNTDOWN_MARGIN),
+                origin_y + physical(COUNTDOWN_MARGIN),
+            ),
+            (physical(COUNTDOWN_WIDTH) as u32, physical(COUNTDOWN_HEIGHT) as u32),
+        ))
+    });
+
+    let url = format!("/screenshot-countdown?seconds={}", delay_secs);
+    let builder = WebviewWindowBuilder::new(app, COUNTDOWN_WINDOW, tauri::WebviewUrl::App(url.into()))
+        .title("延时截图")
+        .inner_size(COUNTDOWN_WIDTH, COUNTDOWN_HEIGHT)
+        .resizable(false)
+        .decorations(false)
+        .transparent(true)
+        .shadow(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .focused(true);
+    let window = builder
+        .visible(placement.is_none())
+        .center()
+        .build()
+        .map_err(|e| format!("Failed to create countdown window: {}", e))?;
+
+    match placement {
+        Some((origin, size)) => monitors::place_window(&window, origin, size),
+        None => Ok(()),
+    }
+}
+
+fn close_countdown_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(COUNTDOWN_WINDOW) {
+        let _ = window.destroy();
+    }
+}
+
+// 倒计时（阻塞），每秒发送一次 screenshot-countdown 事件，被取消时返回错误
+fn wait_countdown(app: &AppHandle, session: u64, delay_secs: u32) -> Result<(), String> {
+    let delayed = app.state::<DelayedCapture>();
+    let deadline = Instant::now() + Duration::from_secs(delay_secs as u64);
+    let mut last_emitted = None;
+
+    loop {
+        if !delayed.is_current(session) {
+            let _ = app.emit(
+                "screenshot-countdown",
+                CountdownEvent { remaining: 0, total: delay_secs, cancelled: true },
+            );
+            return Err("Delayed capture cancelled".to_string());
+        }
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let remaining_secs = remaining.as_secs_f64().ceil() as u32;
+        if last_emitted != Some(remaining_secs) {
+            last_emitted = Some(remaining_secs);
+            let _ = app.emit(
+                "screenshot-countdown",
+                CountdownEvent { remaining: remaining_secs, total: delay_secs, cancelled: false },
+            );
+        }
+        if remaining.is_zero() {
+            return Ok(());
+        }
+
+        // 分段休眠，保证取消能及时生效
+        thread::sleep(remaining.min(Duration::from_millis(100)));
+    }
+}
+
+// 执行倒计时：显示倒计时窗口、等待、关闭窗口，delay_secs 为 0 时直接返回
+pub async fn countdown(app: &AppHandle, delay_secs: u32) -> Result<(), String> {
+    if delay_secs == 0 {
+        return Ok(());
+    }
+    if delay_secs > MAX_DELAY_SECS {
+        return Err(format!("Delay must be at most {} seconds", MAX_DELAY_SECS));
+    }
+
+    let session = app.state::<DelayedCapture>().begin()?;
+    if let Err(e) = open_countdown_window(app, delay_secs) {
+        // 倒计时窗口只是提示，创建失败时仍然继续倒计时
+        eprintln!("{}", e);
+    }
+
+    let worker_app = app.clone();
+    let result = pipeline::run_blocking(move || wait_countdown(&worker_app, session, delay_secs)).await;
+
+    close_countdown_window(app);
+    app.state::<DelayedCapture>().finish(session);
+    result?;
+
+    // 等待倒计时窗口从屏幕上完全消失，避免被截进去
+    pipeline::run_blocking(|| {
+        thread::sleep(WINDOW_CLOSE_GRACE);
+        Ok(())
+    })
+    .await
+}
+
+// 取消正在进行的延时截图
+#[command]
+pub fn cancel_delayed_capture(app: AppHandle, delayed: State<DelayedCapture>) -> Result<bool, String> {
+    let cancelled = delayed.cancel();
+    if cancelled {
+        close_countdown_window(&app);
+    }
+    Ok(cancelled)
+}
+
+#[command]
+pub fn is_delayed_capture_pending(delayed: State<DelayedCapture>) -> Result<bool, String> {
+    Ok(delayed.is_pending())
+}
@@ -0,0 +1,155 @@
+// 截图对比：对齐两张截图（截图库中的记录、内存中的截图、任意文件或定时截图），计算逐像素差异和
+// 结构相似度（SSIM），返回相似度、变化区域和高亮差异图，供 QA 对比修改前后的界面
+// 差异图存入 ScreenshotStore，前端通过 screenshot://localhost/<image_id> 加载，用完调用 release_screenshot 释放
+pub mod compare;
+
+use std::path::Path;
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle, Manager};
+
+use super::library::{list_screenshots, open_db};
+use super::store::{ScreenshotStore, StoredImage};
+use super::{encode_jpeg, pipeline, timed};
+use compare::{ChangedRegion, CompareOptions};
+
+const DIFF_IMAGE_QUALITY: u8 = 90;
+// 定时截图向前查找的范围
+const TIMED_LOOKBACK_MS: i64 = 24 * 60 * 60 * 1000;
+
+// 参与对比的截图来源
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffSource {
+    Library(i64),  // screenshots 表中的 id
+    Store(String), // ScreenshotStore 中的 id（贴图窗口）
+    File(String),
+    Timed { monitor: usize, timestamp: i64 }, // 该显示器在该时间点（毫秒）之前最近的一次定时截图
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DiffOptions {
+    pub threshold: Option<u8>,
+    pub align: Option<bool>,
+    pub max_shift: Option<u32>,
+    pub min_pixels: Option<u32>,
+    pub image: Option<bool>, // 是否生成差异图，批量对比时可关闭
+}
+
+impl DiffOptions {
+    fn compare_options(&self) -> CompareOptions {
+        let defaults = CompareOptions::default();
+        CompareOptions {
+            threshold: self.threshold.unwrap_or(defaults.threshold),
+            align: self.align.unwrap_or(defaults.align),
+            max_shift: self.max_shift.unwrap_or(defaults.max_shift),
+            min_pixels: self.min_pixels.unwrap_or(defaults.min_pixels),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffResult {
+    pub width: u32, // 以第一张截图的尺寸为准
+    pub height: u32,
+    pub offset_x: i32, // 第二张截图相对第一张的平移
+    pub offset_y: i32,
+    pub resized: bool,
+    pub similarity: f64,
+    pub changed_pixels: u64,
+    pub changed_ratio: f64,
+    pub regions: Vec<ChangedRegion>,
+    pub image_id: Option<String>,
+    pub before_path: Option<String>,
+    pub after_path: Option<String>,
+}
+
+fn open_file(path: &str) -> Result<RgbaImage, String> {
+    if !Path::new(path).exists() {
+        return Err(format!("Screenshot file not found: {}", path));
+    }
+    Ok(image::open(path)
+        .map_err(|e| format!("Failed to decode {}: {}", path, e))?
+        .to_rgba8())
+}
+
+// 读取来源对应的图片和文件路径（阻塞）
+fn load_source(app: &AppHandle, source: &DiffSource) -> Result<(RgbaImage, Option<String>), String> {
+    match source {
+        DiffSource::Library(id) => {
+            let conn = open_db(app)?;
+            let record = list_screenshots(&conn)?
+                .into_iter()
+                .find(|record| record.id == *id)
+                .ok_or_else(|| format!("Screenshot {} not found", id))?;
+            Ok((open_file(&record.file_path)?, Some(record.file_path)))
+        }
+        DiffSource::Store(id) => {
+            let stored = app
+                .state::<ScreenshotStore>()
+                .get(id)
+                .ok_or_else(|| format!("Screenshot {} is not in memory", id))?;
+            let image = image::load_from_memory(&stored.bytes)
+                .map_err(|e| format!("Failed to decode screenshot {}: {}", id, e))?
+                .to_rgba8();
+            Ok((image, stored.file_path))
+        }
+        DiffSource::File(path) => Ok((open_file(path)?, Some(path.clone()))),
+        DiffSource::Timed { monitor, timestamp } => {
+            let events = timed::read_index(app, timestamp - TIMED_LOOKBACK_MS, *timestamp, *monitor)?;
+            let path = events
+                .into_iter()
+                .filter(|event| event.timestamp <= *timestamp)
+                .max_by_key(|event| event.timestamp)
+                .and_then(|event| event.file_path)
+                .ok_or_else(|| format!("No timed capture for monitor {} before {}", monitor, timestamp))?;
+            Ok((open_file(&path)?, Some(path)))
+        }
+    }
+}
+
+fn run_diff(app: &AppHandle, before: &DiffSource, after: &DiffSource, options: &DiffOptions) -> Result<DiffResult, String> {
+    let (before_image, before_path) = load_source(app, before)?;
+    let (after_image, after_path) = load_source(app, after)?;
+    let comparison = compare::compare(&before_image, &after_image, &options.compare_options());
+
+    let image_id = if options.image.unwrap_or(true) {
+        let highlighted = compare::highlight(&before_image, &comparison);
+        let jpeg = encode_jpeg(&highlighted, DIFF_IMAGE_QUALITY)?;
+        let id = format!("diff_{}", chrono::Utc::now().timestamp_millis());
+        app.state::<ScreenshotStore>().insert(
+            id.clone(),
+            StoredImage::new(jpeg, comparison.width, comparison.height, None),
+        );
+        Some(id)
+    } else {
+        None
+    };
+
+    Ok(DiffResult {
+        width: comparison.width,
+        height: comparison.height,
+        offset_x: comparison.offset.0,
+        offset_y: comparison.offset.1,
+        resized: comparison.resized,
+        similarity: comparison.similarity,
+        changed_pixels: comparison.changed_pixels,
+        changed_ratio: comparison.changed_ratio,
+        regions: comparison.regions,
+        image_id,
+        before_path,
+        after_path,
+    })
+}
+
+// 对比两张截图，返回相似度、变化区域和差异图 id
+#[command]
+pub async fn diff_screenshots(
+    app: AppHandle,
+    before: DiffSource,
+    after: DiffSource,
+    options: Option<DiffOptions>,
+) -> Result<DiffResult, String> {
+    let options = options.unwrap_or_default();
+    pipeline::run_blocking(move || run_diff(&app, &before, &after, &options)).await
+}
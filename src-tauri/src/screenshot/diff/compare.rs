@@ -0,0 +1,434 @@
+// 两张截图的比较：先在小范围内搜索平移对齐（滚动、窗口移动），再逐像素比较颜色差异并计算分块 SSIM，
+// 变化像素按网格聚类为矩形区域，最后生成高亮差异图（未变化部分淡化，变化像素标红并框出区域）
+use image::{imageops, GrayImage, Rgba, RgbaImage};
+use serde::Serialize;
+
+// 对齐的粗搜索在缩小到 1/ALIGN_SCALE 的图上进行
+const ALIGN_SCALE: u32 = 4;
+// 偏移后的代价需低于不偏移时的该比例才采用，避免完全相同的画面被误判为平移
+const ALIGN_MIN_GAIN: f64 = 0.9;
+// SSIM 的分块大小与区域聚类的网格大小
+const BLOCK_SIZE: u32 = 8;
+const CELL_SIZE: u32 = 8;
+// 相距不超过该格数的变化合并为同一区域
+const CELL_GAP: i64 = 2;
+const MAX_REGIONS: usize = 200;
+
+const HIGHLIGHT_COLOR: Rgba<u8> = Rgba([255, 0, 64, 255]);
+const OUTLINE_COLOR: Rgba<u8> = Rgba([255, 0, 200, 255]);
+
+#[derive(Debug, Clone, Copy)]
+pub struct CompareOptions {
+    pub threshold: u8,  // 单个颜色通道的差值超过该值视为变化（忽略 JPEG 压缩噪声）
+    pub align: bool,
+    pub max_shift: u32, // 对齐时最大搜索的平移像素数
+    pub min_pixels: u32, // 变化像素少于该数量的区域忽略
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        Self { threshold: 24, align: true, max_shift: 32, min_pixels: 4 }
+    }
+}
+
+// 变化区域（在第一张图中的像素坐标）
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangedRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub changed_pixels: u32,
+}
+
+pub struct Comparison {
+    pub width: u32,
+    pub height: u32,
+    pub offset: (i32, i32), // 第二张图相对第一张的平移：a(x, y) 对应 b(x + dx, y + dy)
+    pub resized: bool,      // 尺寸不同，第二张图已缩放到第一张的尺寸
+    pub similarity: f64,    // 分块 SSIM 的平均值，0-1
+    pub changed_pixels: u64,
+    pub changed_ratio: f64, // 变化像素占重叠部分的比例，0-1
+    pub regions: Vec<ChangedRegion>,
+    mask: Vec<bool>,
+}
+
+fn luma(pixel: &Rgba<u8>) -> u8 {
+    let [r, g, b, _] = pixel.0;
+    ((r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000) as u8
+}
+
+fn to_gray(image: &RgbaImage) -> GrayImage {
+    GrayImage::from_fn(image.width(), image.height(), |x, y| image::Luma([luma(image.get_pixel(x, y))]))
+}
+
+// 平移 (dx, dy) 后重叠区域的平均亮度差，step 为采样间隔；重叠不足一半时返回空
+fn shift_cost(a: &GrayImage, b: &GrayImage, dx: i64, dy: i64, step: usize) -> Option<f64> {
+    let (width, height) = (a.width() as i64, a.height() as i64);
+    let x0 = 0.max(-dx);
+    let x1 = width.min(b.width() as i64 - dx);
+    let y0 = 0.max(-dy);
+    let y1 = height.min(b.height() as i64 - dy);
+    if x1 <= x0 || y1 <= y0 || (x1 - x0) * (y1 - y0) * 2 < width * height {
+        return None;
+    }
+    let (mut total, mut count) = (0u64, 0u64);
+    for y in (y0..y1).step_by(step) {
+        for x in (x0..x1).step_by(step) {
+            let pa = a.get_pixel(x as u32, y as u32).0[0] as i64;
+            let pb = b.get_pixel((x + dx) as u32, (y + dy) as u32).0[0] as i64;
+            total += (pa - pb).unsigned_abs();
+            count += 1;
+        }
+    }
+    Some(total as f64 / count.max(1) as f64)
+}
+
+// 在 center 周围 ±radius 内搜索代价最小的平移，代价相同时取位移较小的
+fn search_shift(a: &GrayImage, b: &GrayImage, center: (i64, i64), radius: i64, step: usize) -> Option<((i64, i64), f64)> {
+    let mut best: Option<((i64, i64), f64)> = None;
+    for dy in center.1 - radius..=center.1 + radius {
+        for dx in center.0 - radius..=center.0 + radius {
+            let Some(cost) = shift_cost(a, b, dx, dy, step) else { continue };
+            let better = match best {
+                None => true,
+                Some(((bx, by), best_cost)) => {
+                    cost < best_cost || (cost == best_cost && dx.abs() + dy.abs() < bx.abs() + by.abs())
+                }
+            };
+            if better {
+                best = Some(((dx, dy), cost));
+            }
+        }
+    }
+    best
+}
+
+// 先在缩小的图上粗搜索，再在原图上细化
+fn align(a: &GrayImage, b: &GrayImage, max_shift: u32) -> (i64, i64) {
+    let Some(base_cost) = shift_cost(a, b, 0, 0, 2) else {
+        return (0, 0);
+    };
+    if base_cost == 0.0 || max_shift == 0 {
+        return (0, 0);
+    }
+
+    let scale = ALIGN_SCALE.min(max_shift).max(1);
+    let coarse = if scale > 1 && a.width() / scale > 16 && a.height() / scale > 16 {
+        let small = |image: &GrayImage| {
+            imageops::resize(image, image.width() / scale, image.height() / scale, imageops::FilterType::Triangle)
+        };
+        let radius = (max_shift / scale) as i64;
+        search_shift(&small(a), &small(b), (0, 0), radius, 1)
+            .map(|((dx, dy), _)| (dx * scale as i64, dy * scale as i64))
+            .unwrap_or((0, 0))
+    } else {
+        (0, 0)
+    };
+
+    let limit = max_shift as i64;
+    let refined = search_shift(a, b, coarse, scale as i64, 2)
+        .map(|((dx, dy), cost)| ((dx.clamp(-limit, limit), dy.clamp(-limit, limit)), cost));
+    match refined {
+        Some((offset, cost)) if cost < base_cost * ALIGN_MIN_GAIN => offset,
+        _ => (0, 0),
+    }
+}
+
+// 分块 SSIM，只统计平移后完全落在第二张图内的块
+fn structural_similarity(a: &GrayImage, b: &GrayImage, (dx, dy): (i64, i64)) -> f64 {
+    const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+    let (mut total, mut blocks) = (0.0, 0u64);
+    for by in (0..a.height()).step_by(BLOCK_SIZE as usize) {
+        for bx in (0..a.width()).step_by(BLOCK_SIZE as usize) {
+            let x1 = (bx + BLOCK_SIZE).min(a.width());
+            let y1 = (by + BLOCK_SIZE).min(a.height());
+            let inside = bx as i64 + dx >= 0
+                && by as i64 + dy >= 0
+                && x1 as i64 + dx <= b.width() as i64
+                && y1 as i64 + dy <= b.height() as i64;
+            if !inside {
+                continue;
+            }
+            blocks += 1;
+            let (mut sum_a, mut sum_b, mut sum_aa, mut sum_bb, mut sum_ab) = (0.0, 0.0, 0.0, 0.0, 0.0);
+            for y in by..y1 {
+                for x in bx..x1 {
+                    let pa = a.get_pixel(x, y).0[0] as f64;
+                    let pb = b.get_pixel((x as i64 + dx) as u32, (y as i64 + dy) as u32).0[0] as f64;
+                    sum_a += pa;
+                    sum_b += pb;
+                    sum_aa += pa * pa;
+                    sum_bb += pb * pb;
+                    sum_ab += pa * pb;
+                }
+            }
+            let n = ((x1 - bx) * (y1 - by)) as f64;
+            let (mean_a, mean_b) = (sum_a / n, sum_b / n);
+            let var_a = sum_aa / n - mean_a * mean_a;
+            let var_b = sum_bb / n - mean_b * mean_b;
+            let covariance = sum_ab / n - mean_a * mean_b;
+            total += ((2.0 * mean_a * mean_b + C1) * (2.0 * covariance + C2))
+                / ((mean_a * mean_a + mean_b * mean_b + C1) * (var_a + var_b + C2));
+        }
+    }
+    if blocks == 0 {
+        return 0.0;
+    }
+    (total / blocks as f64).clamp(0.0, 1.0)
+}
+
+// 网格中的一格，记录变化像素数量和范围
+#[derive(Clone, Copy)]
+struct Cell {
+    count: u32,
+    min_x: u32,
+    min_y: u32,
+    max_x: u32,
+    max_y: u32,
+}
+
+// 相邻（间隔不超过 CELL_GAP 格）的变化格合并为区域
+fn cluster_regions(mask: &[bool], width: u32, height: u32, min_pixels: u32) -> Vec<ChangedRegion> {
+    let columns = width.div_ceil(CELL_SIZE) as i64;
+    let rows = height.div_ceil(CELL_SIZE) as i64;
+    let mut cells: Vec<Option<Cell>> = vec![None; (columns * rows) as usize];
+    for y in 0..height {
+        for x in 0..width {
+            if !mask[(y * width + x) as usize] {
+                continue;
+            }
+            let index = ((y / CELL_SIZE) as i64 * columns + (x / CELL_SIZE) as i64) as usize;
+            let cell = cells[index].get_or_insert(Cell { count: 0, min_x: x, min_y: y, max_x: x, max_y: y });
+            cell.count += 1;
+            cell.min_x = cell.min_x.min(x);
+            cell.min_y = cell.min_y.min(y);
+            cell.max_x = cell.max_x.max(x);
+            cell.max_y = cell.max_y.max(y);
+        }
+    }
+
+    let mut visited = vec![false; cells.len()];
+    let mut regions = Vec::new();
+    for start in 0..cells.len() {
+        if visited[start] || cells[start].is_none() {
+            continue;
+        }
+        visited[start] = true;
+        let mut stack = vec![start];
+        let mut region: Option<Cell> = None;
+        while let Some(index) = stack.pop() {
+            let Some(cell) = cells[index] else { continue };
+            region = Some(match region {
+                None => cell,
+                Some(r) => Cell {
+                    count: r.count + cell.count,
+                    min_x: r.min_x.min(cell.min_x),
+                    min_y: r.min_y.min(cell.min_y),
+                    max_x: r.max_x.max(cell.max_x),
+                    max_y: r.max_y.max(cell.max_y),
+                },
+            });
+            let (column, row) = (index as i64 % columns, index as i64 / columns);
+            for ny in (row - CELL_GAP).max(0)..=(row + CELL_GAP).min(rows - 1) {
+                for nx in (column - CELL_GAP).max(0)..=(column + CELL_GAP).min(columns - 1) {
+                    let neighbor = (ny * columns + nx) as usize;
+                    if !visited[neighbor] && cells[neighbor].is_some() {
+                        visited[neighbor] = true;
+                        stack.push(neighbor);
+                    }
+                }
+            }
+        }
+        if let Some(r) = region.filter(|r| r.count >= min_pixels) {
+            regions.push(ChangedRegion {
+                x: r.min_x,
+                y: r.min_y,
+                width: r.max_x - r.min_x + 1,
+                height: r.max_y - r.min_y + 1,
+                changed_pixels: r.count,
+            });
+        }
+    }
+
+    regions.sort_by_key(|region| std::cmp::Reverse(region.changed_pixels));
+    regions.truncate(MAX_REGIONS);
+    regions
+}
+
+// 比较两张图，尺寸不同时先把第二张缩放到第一张的尺寸（阻塞）
+pub fn compare(a: &RgbaImage, b: &RgbaImage, options: &CompareOptions) -> Comparison {
+    let (width, height) = a.dimensions();
+    let resized = b.dimensions() != (width, height);
+    let resized_b;
+    let b = if resized {
+        resized_b = imageops::resize(b, width, height, imageops::FilterType::Triangle);
+        &resized_b
+    } else {
+        b
+    };
+
+    let (gray_a, gray_b) = (to_gray(a), to_gray(b));
+    let offset = if options.align { align(&gray_a, &gray_b, options.max_shift) } else { (0, 0) };
+    let similarity = structural_similarity(&gray_a, &gray_b, offset);
+
+    // 只比较平移后的重叠部分，滚出和滚入画面的内容不算变化
+    let mut mask = vec![false; (width * height) as usize];
+    let mut changed_pixels = 0u64;
+    let mut compared_pixels = 0u64;
+    for (x, y, pixel) in a.enumerate_pixels() {
+        let (bx, by) = (x as i64 + offset.0, y as i64 + offset.1);
+        if bx < 0 || by < 0 || bx >= width as i64 || by >= height as i64 {
+            continue;
+        }
+        compared_pixels += 1;
+        let other = b.get_pixel(bx as u32, by as u32);
+        if (0..3).any(|channel| pixel.0[channel].abs_diff(other.0[channel]) > options.threshold) {
+            mask[(y * width + x) as usize] = true;
+            changed_pixels += 1;
+        }
+    }
+    let regions = cluster_regions(&mask, width, height, options.min_pixels);
+
+    Comparison {
+        width,
+        height,
+        offset: (offset.0 as i32, offset.1 as i32),
+        resized,
+        similarity,
+        changed_pixels,
+        changed_ratio: changed_pixels as f64 / compared_pixels.max(1) as f64,
+        regions,
+        mask,
+    }
+}
+
+impl Comparison {
+    // 第一张图的 (x, y) 在平移后是否落在第二张图内
+    fn overlaps(&self, x: u32, y: u32) -> bool {
+        let (bx, by) = (x as i64 + self.offset.0 as i64, y as i64 + self.offset.1 as i64);
+        bx >= 0 && by >= 0 && bx < self.width as i64 && by < self.height as i64
+    }
+}
+
+// 高亮差异图：第一张图淡化为灰色，变化像素标红，变化区域加边框，未参与比较的部分（平移后超出范围）显示为深灰
+pub fn highlight(a: &RgbaImage, comparison: &Comparison) -> RgbaImage {
+    let mut output = RgbaImage::from_fn(a.width(), a.height(), |x, y| {
+        let pixel = a.get_pixel(x, y);
+        if comparison.mask[(y * a.width() + x) as usize] {
+            let blend = |value: u8, target: u8| ((value as u32 + target as u32 * 2) / 3) as u8;
+            Rgba([
+                blend(pixel.0[0], HIGHLIGHT_COLOR.0[0]),
+                blend(pixel.0[1], HIGHLIGHT_COLOR.0[1]),
+                blend(pixel.0[2], HIGHLIGHT_COLOR.0[2]),
+                255,
+            ])
+        } else if comparison.overlaps(x, y) {
+            let faded = ((luma(pixel) as u32 + 255 * 2) / 3) as u8;
+            Rgba([faded, faded, faded, 255])
+        } else {
+            let faded = ((luma(pixel) as u32 + 128 * 2) / 3) as u8;
+            Rgba([faded, faded, faded, 255])
+        }
+    });
+
+    for region in &comparison.regions {
+        // 边框画在区域外侧 2 像素
+        let x0 = region.x.saturating_sub(2);
+        let y0 = region.y.saturating_sub(2);
+        let x1 = (region.x + region.width + 1).min(a.width() - 1);
+        let y1 = (region.y + region.height + 1).min(a.height() - 1);
+        for x in x0..=x1 {
+            for y in [y0, y0 + 1, y1.saturating_sub(1), y1] {
+                output.put_pixel(x, y.min(a.height() - 1), OUTLINE_COLOR);
+            }
+        }
+        for y in y0..=y1 {
+            for x in [x0, x0 + 1, x1.saturating_sub(1), x1] {
+                output.put_pixel(x.min(a.width() - 1), y, OUTLINE_COLOR);
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 带文字行和色块的界面样式测试图
+    fn test_page(width: u32, height: u32) -> RgbaImage {
+        RgbaImage::from_fn(width, height, |x, y| {
+            if y % 24 < 12 && x % 90 < 70 && (x / 7 + y / 5) % 3 != 0 {
+                Rgba([40, 40, 40, 255])
+            } else if (x / 120 + y / 80) % 4 == 0 {
+                Rgba([102, 126, 234, 255])
+            } else {
+                Rgba([250, 250, 250, 255])
+            }
+        })
+    }
+
+    // 整体下移 10、右移 6 像素（滚动），并在 (300, 200) 处改变一个 50 × 30 的按钮
+    fn scrolled(before: &RgbaImage) -> RgbaImage {
+        RgbaImage::from_fn(before.width(), before.height(), |x, y| {
+            let (sx, sy) = (x as i64 - 6, y as i64 - 10);
+            if (306..356).contains(&x) && (210..240).contains(&y) {
+                Rgba([230, 60, 60, 255])
+            } else if sx < 0 || sy < 0 {
+                Rgba([250, 250, 250, 255])
+            } else {
+                *before.get_pixel(sx as u32, sy as u32)
+            }
+        })
+    }
+
+    #[test]
+    fn identical_images_have_no_changes() {
+        let before = test_page(640, 480);
+        let same = compare(&before, &before, &CompareOptions::default());
+        assert_eq!(same.changed_pixels, 0);
+        assert!(same.regions.is_empty());
+        assert!(same.similarity > 0.999);
+        assert_eq!(same.offset, (0, 0));
+    }
+
+    #[test]
+    fn aligns_scrolled_image_and_finds_changed_region() {
+        let before = test_page(640, 480);
+        let shifted = compare(&before, &scrolled(&before), &CompareOptions::default());
+        assert_eq!(shifted.offset, (6, 10));
+        let inside = |x: u32, y: u32| {
+            shifted.regions.iter().any(|r| r.x <= x && x < r.x + r.width && r.y <= y && y < r.y + r.height)
+        };
+        assert_eq!(shifted.regions.len(), 1, "{:?}", shifted.regions);
+        assert!(inside(310, 205) && inside(340, 225));
+        // 对齐后其余部分基本不变
+        assert!(shifted.changed_ratio < 0.05);
+    }
+
+    #[test]
+    fn alignment_reduces_changes() {
+        let before = test_page(640, 480);
+        let after = scrolled(&before);
+        let aligned = compare(&before, &after, &CompareOptions::default());
+        let unaligned = compare(&before, &after, &CompareOptions { align: false, ..CompareOptions::default() });
+        assert!(unaligned.changed_pixels > aligned.changed_pixels * 2);
+    }
+
+    #[test]
+    fn resizes_images_of_different_size() {
+        let before = test_page(640, 480);
+        let resized = compare(&before, &test_page(320, 240), &CompareOptions::default());
+        assert!(resized.resized);
+        assert_eq!((resized.width, resized.height), (640, 480));
+    }
+
+    #[test]
+    fn highlight_keeps_first_image_size() {
+        let before = test_page(640, 480);
+        let shifted = compare(&before, &scrolled(&before), &CompareOptions::default());
+        assert_eq!(highlight(&before, &shifted).dimensions(), before.dimensions());
+    }
+}
@@ -0,0 +1,100 @@
+// 截图编辑：对 ScreenshotStore 中的截图（贴图窗口）做裁剪、旋转、翻转、缩放、边框、阴影、圆角和按比例补边
+// 每次编辑生成一条新记录，原记录保持不变，贴图窗口切换到新 id 即可显示，撤销时切回原来的 id
+// 新记录的 id 为 <贴图窗口 id>_edit_<时间戳>，贴图窗口销毁时一并释放
+pub mod ops;
+
+use std::path::Path;
+use image::{ImageEncoder, RgbaImage};
+use serde::Serialize;
+use tauri::{command, AppHandle, Manager};
+
+use super::pipeline;
+use super::store::{ScreenshotStore, StoredImage};
+use ops::EditOp;
+
+const EDIT_MARKER: &str = "_edit_";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EditResult {
+    pub id: String, // 新记录，可通过 convertFileSrc(id, 'screenshot') 加载
+    pub source_id: String,
+    pub width: u32,
+    pub height: u32,
+    pub mime: &'static str, // 始终为 PNG
+}
+
+// 编辑结果的 id 都挂在最初的贴图窗口 id 下，便于窗口销毁时统一释放
+fn edit_id(source_id: &str) -> String {
+    let root = source_id.split(EDIT_MARKER).next().unwrap_or(source_id);
+    format!("{}{}{}", root, EDIT_MARKER, chrono::Utc::now().timestamp_micros())
+}
+
+// 释放贴图窗口的所有编辑结果
+pub fn release_edits(store: &ScreenshotStore, window_id: &str) -> usize {
+    store.remove_prefix(&format!("{}{}", window_id, EDIT_MARKER))
+}
+
+// 读取要编辑的截图：贴图窗口中保存的是低质量预览图，有尺寸一致的存档文件时改用存档
+// 编辑结果本身是无损 PNG，没有存档文件，直接解码
+fn load_source(stored: &StoredImage) -> Result<RgbaImage, String> {
+    if let Some(path) = stored.file_path.as_deref().filter(|path| Path::new(path).exists()) {
+        if let Ok(image) = image::open(path) {
+            if image.width() == stored.width && image.height() == stored.height {
+                return Ok(image.to_rgba8());
+            }
+        }
+    }
+    Ok(image::load_from_memory(&stored.bytes)
+        .map_err(|e| format!("Failed to decode screenshot: {}", e))?
+        .to_rgba8())
+}
+
+// 编辑结果统一存为无损 PNG，连续编辑时不会因反复 JPEG 编码而逐步失真，也能保留透明像素
+fn encode(image: &RgbaImage) -> Result<Vec<u8>, String> {
+    let mut buffer = Vec::new();
+    image::codecs::png::PngEncoder::new(&mut buffer)
+        .write_image(image.as_raw(), image.width(), image.height(), image::ColorType::Rgba8)
+        .map_err(|e| format!("Failed to encode image: {}", e))?;
+    Ok(buffer)
+}
+
+// 依次应用编辑操作，结果存为新记录
+#[command]
+pub async fn edit_screenshot(app: AppHandle, id: String, ops: Vec<EditOp>) -> Result<EditResult, String> {
+    if ops.is_empty() {
+        return Err("No edit operations".to_string());
+    }
+    pipeline::run_blocking(move || {
+        let store = app.state::<ScreenshotStore>();
+        let stored = store.get(&id).ok_or_else(|| format!("Screenshot {} is not in memory", id))?;
+        let edited = ops::apply_all(load_source(&stored)?, &ops)?;
+        let image = StoredImage::new(encode(&edited)?, edited.width(), edited.height(), None);
+        let result = EditResult {
+            id: edit_id(&id),
+            source_id: id,
+            width: image.width,
+            height: image.height,
+            mime: image.mime,
+        };
+        store.insert(result.id.clone(), image);
+        Ok(result)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encoded_edits_are_lossless() {
+        // 连续编辑时每一步都从上一步的结果解码，编码必须无损
+        let image = RgbaImage::from_fn(64, 48, |x, y| image::Rgba([(x * 4) as u8, (y * 5) as u8, (x ^ y) as u8, 255 - x as u8]));
+        let decoded = image::load_from_memory(&encode(&image).unwrap()).unwrap().to_rgba8();
+        assert_eq!(decoded, image);
+
+        let stored = StoredImage::new(encode(&image).unwrap(), 64, 48, None);
+        assert_eq!(stored.mime, "image/png");
+        assert_eq!(load_source(&stored).unwrap(), image);
+    }
+}
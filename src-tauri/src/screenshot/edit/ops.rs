@@ -0,0 +1,502 @@
+// 截图的基本编辑操作：裁剪、旋转、翻转、缩放、边框、阴影、圆角，以及按比例补边
+// 旋转角度为顺时针；圆角、阴影、任意角度旋转和透明补边会产生透明像素
+use image::{imageops, GrayImage, Luma, Rgba, RgbaImage};
+use serde::Deserialize;
+
+// 编辑结果的最大边长，避免缩放或补边时分配过大的图像
+pub const MAX_DIMENSION: u32 = 16384;
+
+const DEFAULT_BORDER_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const DEFAULT_SHADOW_COLOR: Rgba<u8> = Rgba([0, 0, 0, 128]);
+const DEFAULT_SHADOW_BLUR: u32 = 16;
+const DEFAULT_SHADOW_OFFSET: (i32, i32) = (0, 6);
+const TRANSPARENT: Rgba<u8> = Rgba([0, 0, 0, 0]);
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FlipDirection {
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResizeFilter {
+    Nearest,
+    Triangle,
+    CatmullRom,
+    Gaussian,
+    #[default]
+    Lanczos3,
+}
+
+impl From<ResizeFilter> for imageops::FilterType {
+    fn from(filter: ResizeFilter) -> Self {
+        match filter {
+            ResizeFilter::Nearest => imageops::FilterType::Nearest,
+            ResizeFilter::Triangle => imageops::FilterType::Triangle,
+            ResizeFilter::CatmullRom => imageops::FilterType::CatmullRom,
+            ResizeFilter::Gaussian => imageops::FilterType::Gaussian,
+            ResizeFilter::Lanczos3 => imageops::FilterType::Lanczos3,
+        }
+    }
+}
+
+// 颜色为 #RGB、#RRGGBB 或 #RRGGBBAA
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditOp {
+    Crop { x: u32, y: u32, width: u32, height: u32 },
+    Rotate { degrees: f32, background: Option<String> }, // 非 90 度倍数时画布扩大，空出的部分默认透明
+    Flip(FlipDirection),
+    // 只指定宽或高时保持比例，scale 与宽高同时指定时以宽高为准
+    Resize { width: Option<u32>, height: Option<u32>, scale: Option<f32>, filter: Option<ResizeFilter> },
+    Border { width: u32, color: Option<String> }, // 默认白色
+    Shadow { blur: Option<u32>, offset_x: Option<i32>, offset_y: Option<i32>, color: Option<String> }, // 默认半透明黑色，向下偏移 6 像素
+    RoundCorners { radius: u32 },
+    PadAspect { width: u32, height: u32, color: Option<String> }, // 补边到 width:height，图片居中，默认透明
+}
+
+pub fn parse_color(value: &str) -> Result<Rgba<u8>, String> {
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    let digits: Option<Vec<u8>> = hex.chars().map(|c| c.to_digit(16).map(|digit| digit as u8)).collect();
+    let channels: Vec<u8> = match (digits, hex.len()) {
+        (Some(digits), 3) => digits.iter().map(|digit| digit * 17).collect(),
+        (Some(digits), 6 | 8) => digits.chunks(2).map(|pair| pair[0] * 16 + pair[1]).collect(),
+        _ => Vec::new(),
+    };
+    match channels[..] {
+        [r, g, b] => Ok(Rgba([r, g, b, 255])),
+        [r, g, b, a] => Ok(Rgba([r, g, b, a])),
+        _ => Err(format!("Invalid color: {}", value)),
+    }
+}
+
+fn color_or(value: &Option<String>, default: Rgba<u8>) -> Result<Rgba<u8>, String> {
+    value.as_deref().map(parse_color).unwrap_or(Ok(default))
+}
+
+fn check_size(width: u64, height: u64) -> Result<(u32, u32), String> {
+    if width == 0 || height == 0 {
+        return Err("Result image would be empty".to_string());
+    }
+    if width > MAX_DIMENSION as u64 || height > MAX_DIMENSION as u64 {
+        return Err(format!("Result image {} × {} exceeds {} pixels", width, height, MAX_DIMENSION));
+    }
+    Ok((width as u32, height as u32))
+}
+
+// 依次应用编辑操作
+pub fn apply_all(image: RgbaImage, ops: &[EditOp]) -> Result<RgbaImage, String> {
+    ops.iter().try_fold(image, apply)
+}
+
+pub fn apply(image: RgbaImage, op: &EditOp) -> Result<RgbaImage, String> {
+    match op {
+        EditOp::Crop { x, y, width, height } => {
+            let x = (*x).min(image.width());
+            let y = (*y).min(image.height());
+            let width = (*width).min(image.width() - x);
+            let height = (*height).min(image.height() - y);
+            if width == 0 || height == 0 {
+                return Err("Crop region is outside the image".to_string());
+            }
+            Ok(imageops::crop_imm(&image, x, y, width, height).to_image())
+        }
+        EditOp::Rotate { degrees, background } => {
+            let background = color_or(background, TRANSPARENT)?;
+            rotate(&image, *degrees, background)
+        }
+        EditOp::Flip(FlipDirection::Horizontal) => Ok(imageops::flip_horizontal(&image)),
+        EditOp::Flip(FlipDirection::Vertical) => Ok(imageops::flip_vertical(&image)),
+        EditOp::Resize { width, height, scale, filter } => {
+            let (source_width, source_height) = (image.width() as f64, image.height() as f64);
+            let (width, height) = match (width, height, scale) {
+                (Some(width), Some(height), _) => (*width as f64, *height as f64),
+                (Some(width), None, _) => (*width as f64, source_height * *width as f64 / source_width),
+                (None, Some(height), _) => (source_width * *height as f64 / source_height, *height as f64),
+                (None, None, Some(scale)) if *scale > 0.0 => (source_width * *scale as f64, source_height * *scale as f64),
+                _ => return Err("Resize requires a width, a height or a positive scale".to_string()),
+            };
+            let (width, height) = check_size(width.round().max(1.0) as u64, height.round().max(1.0) as u64)?;
+            if (width, height) == image.dimensions() {
+                return Ok(image);
+            }
+            Ok(imageops::resize(&image, width, height, filter.unwrap_or_default().into()))
+        }
+        EditOp::Border { width, color } => {
+            let color = color_or(color, DEFAULT_BORDER_COLOR)?;
+            let border = *width as u64;
+            let (canvas_width, canvas_height) =
+                check_size(image.width() as u64 + border * 2, image.height() as u64 + border * 2)?;
+            let mut canvas = RgbaImage::from_pixel(canvas_width, canvas_height, color);
+            imageops::overlay(&mut canvas, &image, border as i64, border as i64);
+            Ok(canvas)
+        }
+        EditOp::Shadow { blur, offset_x, offset_y, color } => {
+            let color = color_or(color, DEFAULT_SHADOW_COLOR)?;
+            let blur = blur.unwrap_or(DEFAULT_SHADOW_BLUR);
+            let limit = MAX_DIMENSION as i32;
+            let offset = (
+                offset_x.unwrap_or(DEFAULT_SHADOW_OFFSET.0).clamp(-limit, limit),
+                offset_y.unwrap_or(DEFAULT_SHADOW_OFFSET.1).clamp(-limit, limit),
+            );
+            shadow(&image, blur, offset, color)
+        }
+        EditOp::RoundCorners { radius } => Ok(round_corners(image, *radius)),
+        EditOp::PadAspect { width, height, color } => {
+            if *width == 0 || *height == 0 {
+                return Err("Aspect ratio must be positive".to_string());
+            }
+            let color = color_or(color, TRANSPARENT)?;
+            let ratio = *width as f64 / *height as f64;
+            let (source_width, source_height) = (image.width() as u64, image.height() as u64);
+            let (canvas_width, canvas_height) = if (source_width as f64 / source_height as f64) < ratio {
+                ((source_height as f64 * ratio).round() as u64, source_height)
+            } else {
+                (source_width, (source_width as f64 / ratio).round() as u64)
+            };
+            let (canvas_width, canvas_height) =
+                check_size(canvas_width.max(source_width), canvas_height.max(source_height))?;
+            let mut canvas = RgbaImage::from_pixel(canvas_width, canvas_height, color);
+            let left = (canvas_width - image.width()) / 2;
+            let top = (canvas_height - image.height()) / 2;
+            imageops::overlay(&mut canvas, &image, left as i64, top as i64);
+            Ok(canvas)
+        }
+    }
+}
+
+// 90 度的倍数直接转置像素，其他角度按双线性插值旋转并扩大画布
+fn rotate(image: &RgbaImage, degrees: f32, background: Rgba<u8>) -> Result<RgbaImage, String> {
+    if !degrees.is_finite() {
+        return Err("Rotation angle must be finite".to_string());
+    }
+    let normalized = degrees.rem_euclid(360.0);
+    let quarter = (normalized / 90.0).round();
+    if (normalized - quarter * 90.0).abs() < 1e-3 {
+        return Ok(match quarter as u32 % 4 {
+            1 => imageops::rotate90(image),
+            2 => imageops::rotate180(image),
+            3 => imageops::rotate270(image),
+            _ => image.clone(),
+        });
+    }
+
+    let (sin, cos) = (normalized as f64).to_radians().sin_cos();
+    let (width, height) = (image.width() as f64, image.height() as f64);
+    let (out_width, out_height) = check_size(
+        (width * cos.abs() + height * sin.abs()).ceil() as u64,
+        (width * sin.abs() + height * cos.abs()).ceil() as u64,
+    )?;
+    let (center_x, center_y) = (width / 2.0, height / 2.0);
+    let (out_center_x, out_center_y) = (out_width as f64 / 2.0, out_height as f64 / 2.0);
+    Ok(RgbaImage::from_fn(out_width, out_height, |x, y| {
+        // 目标像素中心逆时针转回原图
+        let (dx, dy) = (x as f64 + 0.5 - out_center_x, y as f64 + 0.5 - out_center_y);
+        let source_x = dx * cos + dy * sin + center_x - 0.5;
+        let source_y = -dx * sin + dy * cos + center_y - 0.5;
+        sample_bilinear(image, source_x, source_y, background)
+    }))
+}
+
+// 双线性插值，原图以外的部分视为背景色（边缘因此自然抗锯齿）
+fn sample_bilinear(image: &RgbaImage, x: f64, y: f64, background: Rgba<u8>) -> Rgba<u8> {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let pixel = |px: f64, py: f64| -> [f64; 4] {
+        let color = if px < 0.0 || py < 0.0 || px >= image.width() as f64 || py >= image.height() as f64 {
+            background
+        } else {
+            *image.get_pixel(px as u32, py as u32)
+        };
+        // 预乘 alpha 后插值，避免透明背景在边缘混入黑色
+        let alpha = color.0[3] as f64 / 255.0;
+        [color.0[0] as f64 * alpha, color.0[1] as f64 * alpha, color.0[2] as f64 * alpha, color.0[3] as f64]
+    };
+    let samples = [
+        (pixel(x0, y0), (1.0 - fx) * (1.0 - fy)),
+        (pixel(x0 + 1.0, y0), fx * (1.0 - fy)),
+        (pixel(x0, y0 + 1.0), (1.0 - fx) * fy),
+        (pixel(x0 + 1.0, y0 + 1.0), fx * fy),
+    ];
+    let mut sum = [0.0; 4];
+    for (values, weight) in samples {
+        for channel in 0..4 {
+            sum[channel] += values[channel] * weight;
+        }
+    }
+    let alpha = sum[3] / 255.0;
+    if alpha <= 0.0 {
+        return Rgba([background.0[0], background.0[1], background.0[2], 0]);
+    }
+    let channel = |value: f64| (value / alpha).round().clamp(0.0, 255.0) as u8;
+    Rgba([channel(sum[0]), channel(sum[1]), channel(sum[2]), sum[3].round().clamp(0.0, 255.0) as u8])
+}
+
+// 圆角：角上按像素中心到圆心的距离计算覆盖率，边缘 1 像素抗锯齿
+fn round_corners(mut image: RgbaImage, radius: u32) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let radius = radius.min(width / 2).min(height / 2);
+    if radius == 0 {
+        return image;
+    }
+    let r = radius as f32;
+    for y in 0..radius {
+        for x in 0..radius {
+            let (dx, dy) = (r - (x as f32 + 0.5), r - (y as f32 + 0.5));
+            let coverage = (r - (dx * dx + dy * dy).sqrt() + 0.5).clamp(0.0, 1.0);
+            if coverage >= 1.0 {
+                continue;
+            }
+            for (px, py) in [(x, y), (width - 1 - x, y), (x, height - 1 - y), (width - 1 - x, height - 1 - y)] {
+                let pixel = image.get_pixel_mut(px, py);
+                pixel.0[3] = (pixel.0[3] as f32 * coverage).round() as u8;
+            }
+        }
+    }
+    image
+}
+
+// 阴影：以图片的 alpha 为形状（圆角后同样适用），模糊后按偏移放在图片下方，画布按模糊半径和偏移扩大
+fn shadow(image: &RgbaImage, blur: u32, (offset_x, offset_y): (i32, i32), color: Rgba<u8>) -> Result<RgbaImage, String> {
+    let margin = blur as u64;
+    let (canvas_width, canvas_height) = check_size(
+        image.width() as u64 + margin * 2 + offset_x.unsigned_abs() as u64,
+        image.height() as u64 + margin * 2 + offset_y.unsigned_abs() as u64,
+    )?;
+    let image_x = margin as i64 + (-offset_x).max(0) as i64;
+    let image_y = margin as i64 + (-offset_y).max(0) as i64;
+
+    let mut mask = GrayImage::new(canvas_width, canvas_height);
+    let (shadow_x, shadow_y) = (image_x + offset_x as i64, image_y + offset_y as i64);
+    for (x, y, pixel) in image.enumerate_pixels() {
+        mask.put_pixel((shadow_x + x as i64) as u32, (shadow_y + y as i64) as u32, Luma([pixel.0[3]]));
+    }
+    // 三次盒式模糊近似高斯模糊，耗时与模糊半径无关
+    let radius = blur / 2;
+    for _ in 0..3 {
+        box_blur(&mut mask, radius);
+    }
+
+    let mut canvas = RgbaImage::from_fn(canvas_width, canvas_height, |x, y| {
+        let alpha = mask.get_pixel(x, y).0[0] as u32 * color.0[3] as u32 / 255;
+        Rgba([color.0[0], color.0[1], color.0[2], alpha as u8])
+    });
+    imageops::overlay(&mut canvas, image, image_x, image_y);
+    Ok(canvas)
+}
+
+// 横向和纵向各做一次滑动窗口平均
+fn box_blur(mask: &mut GrayImage, radius: u32) {
+    if radius == 0 {
+        return;
+    }
+    let (width, height) = (mask.width() as usize, mask.height() as usize);
+    let radius = radius as usize;
+    let window = (radius * 2 + 1) as u32;
+    let blur_line = |values: &mut [u8], line: &mut Vec<u8>| {
+        line.clear();
+        line.extend_from_slice(values);
+        let mut sum: u32 = 0;
+        // 窗口超出边界的部分按 0 计
+        for &value in line.iter().take(radius) {
+            sum += value as u32;
+        }
+        for index in 0..line.len() {
+            if index + radius < line.len() {
+                sum += line[index + radius] as u32;
+            }
+            values[index] = (sum / window) as u8;
+            if index >= radius {
+                sum -= line[index - radius] as u32;
+            }
+        }
+    };
+    let mut line = Vec::with_capacity(width.max(height));
+    let raw: &mut [u8] = mask;
+    for row in raw.chunks_mut(width) {
+        blur_line(row, &mut line);
+    }
+    let mut column = vec![0u8; height];
+    for x in 0..width {
+        for (y, value) in column.iter_mut().enumerate() {
+            *value = raw[y * width + x];
+        }
+        blur_line(&mut column, &mut line);
+        for (y, &value) in column.iter().enumerate() {
+            raw[y * width + x] = value;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Rgba<u8> = Rgba([230, 40, 40, 255]);
+    const BLUE: Rgba<u8> = Rgba([40, 60, 230, 255]);
+    const WHITE: Rgba<u8> = Rgba([250, 250, 250, 255]);
+
+    // 200 × 100 的测试图：左上角 20 × 10 的红块，右下角 20 × 10 的蓝块
+    fn test_image() -> RgbaImage {
+        RgbaImage::from_fn(200, 100, |x, y| {
+            if x < 20 && y < 10 {
+                RED
+            } else if x >= 180 && y >= 90 {
+                BLUE
+            } else {
+                WHITE
+            }
+        })
+    }
+
+    fn edit(op: EditOp) -> Result<RgbaImage, String> {
+        apply(test_image(), &op)
+    }
+
+    #[test]
+    fn parses_colors() {
+        assert_eq!(parse_color("#f00"), Ok(Rgba([255, 0, 0, 255])));
+        assert_eq!(parse_color("#11223380"), Ok(Rgba([0x11, 0x22, 0x33, 0x80])));
+        assert!(parse_color("#12345").is_err());
+        assert!(parse_color("red").is_err());
+    }
+
+    #[test]
+    fn crops_and_clamps_to_image() {
+        let cropped = edit(EditOp::Crop { x: 10, y: 5, width: 50, height: 30 }).unwrap();
+        assert_eq!(cropped.dimensions(), (50, 30));
+        assert_eq!(*cropped.get_pixel(0, 0), RED);
+        assert_eq!(*cropped.get_pixel(10, 5), WHITE);
+
+        // 裁剪区域超出图片时截断
+        let clamped = edit(EditOp::Crop { x: 150, y: 50, width: 500, height: 500 }).unwrap();
+        assert_eq!(clamped.dimensions(), (50, 50));
+        assert_eq!(*clamped.get_pixel(49, 49), BLUE);
+        assert!(edit(EditOp::Crop { x: 300, y: 0, width: 10, height: 10 }).is_err());
+    }
+
+    #[test]
+    fn rotates_right_angles() {
+        // 顺时针 90 度：左上角的红块到右上角
+        let rotated = edit(EditOp::Rotate { degrees: 90.0, background: None }).unwrap();
+        assert_eq!(rotated.dimensions(), (100, 200));
+        assert_eq!(*rotated.get_pixel(99, 0), RED);
+        assert_eq!(*rotated.get_pixel(0, 199), BLUE);
+
+        let rotated = edit(EditOp::Rotate { degrees: -90.0, background: None }).unwrap();
+        assert_eq!(rotated.dimensions(), (100, 200));
+        assert_eq!(*rotated.get_pixel(0, 199), RED);
+    }
+
+    #[test]
+    fn rotates_arbitrary_angles() {
+        // 45 度时扩大画布，四角透明
+        let rotated = edit(EditOp::Rotate { degrees: 45.0, background: None }).unwrap();
+        let expected = (300.0 / 2f64.sqrt()).ceil() as u32;
+        assert_eq!(rotated.dimensions(), (expected, expected));
+        assert_eq!(rotated.get_pixel(0, 0).0[3], 0);
+        assert_eq!(*rotated.get_pixel(expected / 2, expected / 2), WHITE);
+
+        let rotated = edit(EditOp::Rotate { degrees: 30.0, background: Some("#000".to_string()) }).unwrap();
+        assert_eq!(*rotated.get_pixel(0, 0), Rgba([0, 0, 0, 255]));
+        assert!(rotated.pixels().all(|pixel| pixel[3] == 255));
+    }
+
+    #[test]
+    fn flips() {
+        let flipped = edit(EditOp::Flip(FlipDirection::Horizontal)).unwrap();
+        assert_eq!(*flipped.get_pixel(199, 0), RED);
+        assert_eq!(*flipped.get_pixel(0, 99), BLUE);
+
+        let flipped = edit(EditOp::Flip(FlipDirection::Vertical)).unwrap();
+        assert_eq!(*flipped.get_pixel(0, 99), RED);
+        assert_eq!(*flipped.get_pixel(199, 0), BLUE);
+    }
+
+    #[test]
+    fn resizes() {
+        // 只指定宽度时保持比例
+        let resized = edit(EditOp::Resize { width: Some(100), height: None, scale: None, filter: None }).unwrap();
+        assert_eq!(resized.dimensions(), (100, 50));
+
+        let resized =
+            edit(EditOp::Resize { width: None, height: None, scale: Some(1.5), filter: Some(ResizeFilter::Nearest) }).unwrap();
+        assert_eq!(resized.dimensions(), (300, 150));
+        assert_eq!(*resized.get_pixel(0, 0), RED);
+
+        assert!(edit(EditOp::Resize { width: None, height: None, scale: None, filter: None }).is_err());
+        assert!(edit(EditOp::Resize { width: None, height: None, scale: Some(1000.0), filter: None }).is_err());
+    }
+
+    #[test]
+    fn adds_border() {
+        let bordered = edit(EditOp::Border { width: 8, color: Some("#102030".to_string()) }).unwrap();
+        assert_eq!(bordered.dimensions(), (216, 116));
+        assert_eq!(*bordered.get_pixel(0, 0), Rgba([0x10, 0x20, 0x30, 255]));
+        assert_eq!(*bordered.get_pixel(8, 8), RED);
+    }
+
+    #[test]
+    fn rounds_corners_with_antialiasing() {
+        let rounded = edit(EditOp::RoundCorners { radius: 20 }).unwrap();
+        assert_eq!(rounded.get_pixel(0, 0).0[3], 0);
+        assert_eq!(rounded.get_pixel(199, 99).0[3], 0);
+        assert_eq!(rounded.get_pixel(20, 20).0[3], 255);
+        assert_eq!(rounded.get_pixel(100, 0).0[3], 255);
+
+        let partial = (0..20)
+            .flat_map(|y| (0..20).map(move |x| (x, y)))
+            .any(|(x, y)| (1..255).contains(&rounded.get_pixel(x, y).0[3]));
+        assert!(partial);
+    }
+
+    #[test]
+    fn draws_shadow_below_image() {
+        let shadowed = edit(EditOp::Shadow { blur: Some(10), offset_x: Some(4), offset_y: Some(6), color: None }).unwrap();
+        assert_eq!(shadowed.dimensions(), (200 + 20 + 4, 100 + 20 + 6));
+        // 阴影上方的图片不变
+        assert_eq!(*shadowed.get_pixel(10, 10), RED);
+        assert_eq!(*shadowed.get_pixel(10 + 199, 10 + 99), BLUE);
+
+        let below = shadowed.get_pixel(110, 10 + 100 + 3).0[3];
+        let above = shadowed.get_pixel(110, 2).0[3];
+        assert!(below > 20 && below > above);
+        assert_eq!(shadowed.get_pixel(0, 0).0[3], 0);
+    }
+
+    #[test]
+    fn pads_to_aspect_ratio() {
+        let padded = edit(EditOp::PadAspect { width: 1, height: 1, color: None }).unwrap();
+        assert_eq!(padded.dimensions(), (200, 200));
+        assert_eq!(padded.get_pixel(0, 0).0[3], 0);
+        assert_eq!(*padded.get_pixel(0, 50), RED);
+
+        let padded = edit(EditOp::PadAspect { width: 16, height: 9, color: Some("#fff".to_string()) }).unwrap();
+        assert_eq!(padded.dimensions(), (200, 113));
+
+        let padded = edit(EditOp::PadAspect { width: 4, height: 1, color: None }).unwrap();
+        assert_eq!(padded.dimensions(), (400, 100));
+        assert_eq!(*padded.get_pixel(100, 0), RED);
+    }
+
+    #[test]
+    fn applies_ops_from_frontend_json() {
+        let json = r##"[
+            {"crop": {"x": 0, "y": 0, "width": 100, "height": 50}},
+            {"rotate": {"degrees": 180}},
+            {"flip": "horizontal"},
+            {"resize": {"width": 50, "filter": "catmullrom"}},
+            {"round_corners": {"radius": 6}},
+            {"shadow": {"blur": 8}},
+            {"border": {"width": 2, "color": "#ffffff80"}},
+            {"pad_aspect": {"width": 1, "height": 1}}
+        ]"##;
+        let parsed: Vec<EditOp> = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed.len(), 8);
+        let edited = apply_all(test_image(), &parsed).unwrap();
+        assert_eq!(edited.width(), edited.height());
+        assert!(edited.pixels().any(|pixel| pixel[3] < 255));
+    }
+}
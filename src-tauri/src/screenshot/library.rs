@@ -0,0 +1,167 @@
+// 截图库：在 Rust 端读写前端维护的 screenshots 表，并提供截图回收站
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use tauri::{command, AppHandle, Manager};
+
+use super::{path_to_string, screenshots_dir};
+
+// 与前端 db.ts 中的 sqlite:todos.db 为同一个文件（位于 app_config_dir）
+const DB_FILE: &str = "todos.db";
+// 回收站目录，位于 screenshots 目录下
+pub const TRASH_DIR: &str = ".trash";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScreenshotRecord {
+    pub id: i64,
+    pub file_path: String,
+    pub width: u32,
+    pub height: u32,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct TrashResult {
+    pub trashed: Vec<i64>,
+    pub failed: Vec<TrashFailure>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrashFailure {
+    pub id: i64,
+    pub error: String,
+}
+
+// 打开数据库连接，表不存在时按前端 initDb.ts 的结构创建
+pub fn open_db(app: &AppHandle) -> Result<Connection, String> {
+    let config_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Failed to get app config dir: {}", e))?;
+    fs::create_dir_all(&config_dir)
+        .map_err(|e| format!("Failed to create app config dir: {}", e))?;
+
+    let conn = Connection::open(config_dir.join(DB_FILE))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    // 前端通过 sqlx 连接池同时访问同一个文件，遇到锁时等待而不是直接失败
+    conn.busy_timeout(Duration::from_secs(5))
+        .map_err(|e| format!("Failed to set busy timeout: {}", e))?;
+
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS screenshots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            file_path TEXT NOT NULL,
+            width INTEGER NOT NULL,
+            height INTEGER NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )",
+    ).map_err(|e| format!("Failed to create screenshots table: {}", e))?;
+
+    Ok(conn)
+}
+
+// 为已有表补充列（用于现有数据库的升级），列已存在时忽略
+pub fn ensure_column(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<(), String> {
+    let sql = format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition);
+    match conn.execute(&sql, []) {
+        Ok(_) => Ok(()),
+        Err(e) if e.to_string().contains("duplicate column name") => Ok(()),
+        Err(e) => Err(format!("Failed to add column {}.{}: {}", table, column, e)),
+    }
+}
+
+pub fn list_screenshots(conn: &Connection) -> Result<Vec<ScreenshotRecord>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, file_path, width, height, created_at FROM screenshots ORDER BY created_at DESC")
+        .map_err(|e| format!("Failed to query screenshots: {}", e))?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(ScreenshotRecord {
+                id: row.get(0)?,
+                file_path: row.get(1)?,
+                width: row.get(2)?,
+                height: row.get(3)?,
+                created_at: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+            })
+        })
+        .map_err(|e| format!("Failed to query screenshots: {}", e))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read screenshot row: {}", e))
+}
+
+pub fn delete_screenshot_row(conn: &Connection, id: i64) -> Result<(), String> {
+    conn.execute("DELETE FROM screenshots WHERE id = ?1", params![id])
+        .map_err(|e| format!("Failed to delete screenshot {}: {}", id, e))?;
+    Ok(())
+}
+
+// 回收站目录（screenshots/.trash），不存在时自动创建
+pub fn trash_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = screenshots_dir(app)?.join(TRASH_DIR);
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create trash directory: {}", e))?;
+    Ok(dir)
+}
+
+// 将文件移动到回收站，文件名前加时间戳避免重名
+pub fn move_to_trash(app: &AppHandle, path: &Path) -> Result<PathBuf, String> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| format!("Invalid file path: {}", path_to_string(path)))?;
+    let target = trash_dir(app)?.join(format!(
+        "{}_{}",
+        chrono::Local::now().format("%Y%m%d%H%M%S%3f"),
+        file_name.to_string_lossy()
+    ));
+
+    // 跨分区时 rename 会失败，退回到复制后删除
+    if fs::rename(path, &target).is_err() {
+        fs::copy(path, &target)
+            .map_err(|e| format!("Failed to move {} to trash: {}", path_to_string(path), e))?;
+        fs::remove_file(path)
+            .map_err(|e| format!("Failed to remove {}: {}", path_to_string(path), e))?;
+    }
+
+    Ok(target)
+}
+
+// 将截图文件移入回收站并删除数据库记录；文件已不存在时只删除记录
+pub fn trash_records(app: &AppHandle, conn: &Connection, ids: &[i64]) -> Result<TrashResult, String> {
+    let records = list_screenshots(conn)?;
+    let mut result = TrashResult::default();
+
+    for &id in ids {
+        let Some(record) = records.iter().find(|r| r.id == id) else {
+            result.failed.push(TrashFailure { id, error: "Screenshot not found".to_string() });
+            continue;
+        };
+
+        let path = Path::new(&record.file_path);
+        if path.exists() {
+            if let Err(error) = move_to_trash(app, path) {
+                result.failed.push(TrashFailure { id, error });
+                continue;
+            }
+        }
+
+        match delete_screenshot_row(conn, id) {
+            Ok(()) => result.trashed.push(id),
+            Err(error) => result.failed.push(TrashFailure { id, error }),
+        }
+    }
+
+    Ok(result)
+}
+
+// 将指定截图移入回收站
+#[command]
+pub async fn trash_screenshots(app: AppHandle, ids: Vec<i64>) -> Result<TrashResult, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_db(&app)?;
+        trash_records(&app, &conn, &ids)
+    })
+    .await
+    .map_err(|e| format!("Trash task failed: {}", e))?
+}
@@ -0,0 +1,675 @@
+// 截图元数据：把截图时间（带时区）、显示器、截图模式、当时的应用和窗口标题、应用版本写入图片文件，
+// PNG 写入文本块（tEXt / iTXt），JPEG 写入 EXIF（时间和软件）与 XMP（全部字段），并可从任意图片读回
+use std::collections::BTreeMap;
+use std::io::Cursor;
+use png::text_metadata::{EncodableTextChunk, ITXtChunk, TEXtChunk};
+use serde::{Deserialize, Serialize};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+const EXIF_HEADER: &[u8] = b"Exif\0\0";
+const XMP_HEADER: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+// 自定义字段的 XMP 命名空间
+const CAPTURE_NAMESPACE: &str = "http://ns.tauri-app.app/capture/1.0/";
+
+// PNG 文本块的关键字
+const PNG_KEY_TIME: &str = "Creation Time";
+const PNG_KEY_SOFTWARE: &str = "Software";
+const PNG_KEY_MONITOR: &str = "Capture Monitor";
+const PNG_KEY_MODE: &str = "Capture Mode";
+const PNG_KEY_APP: &str = "Capture Application";
+const PNG_KEY_WINDOW: &str = "Capture Window Title";
+const PNG_KEY_VERSION: &str = "Capture App Version";
+
+// EXIF 标签
+const TAG_SOFTWARE: u16 = 0x0131;
+const TAG_DATE_TIME: u16 = 0x0132;
+const TAG_EXIF_IFD: u16 = 0x8769;
+const TAG_DATE_TIME_ORIGINAL: u16 = 0x9003;
+const TAG_OFFSET_TIME: u16 = 0x9010;
+const TAG_OFFSET_TIME_ORIGINAL: u16 = 0x9011;
+const TYPE_ASCII: u16 = 2;
+const TYPE_LONG: u16 = 4;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CaptureMetadata {
+    pub captured_at: Option<String>, // RFC 3339，带时区偏移
+    pub monitor: Option<String>,     // 显示器序号，拼接全部显示器时为 all
+    pub mode: Option<String>,        // fullscreen / window / region / scrolling
+    pub app_name: Option<String>,    // 截图时的前台应用（AppMonitor）
+    pub window_title: Option<String>,
+    pub app_version: Option<String>, // 截图工具的版本
+    pub software: Option<String>,    // 截图工具名称和版本
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageMetadata {
+    pub format: String, // png / jpeg
+    pub capture: CaptureMetadata,
+    pub entries: BTreeMap<String, String>, // 读到的全部文本元数据，如 PNG.Software、EXIF.DateTimeOriginal、XMP.xmp:CreateDate
+}
+
+enum ImageKind {
+    Png,
+    Jpeg,
+}
+
+fn image_kind(bytes: &[u8]) -> Option<ImageKind> {
+    if bytes.starts_with(PNG_SIGNATURE) {
+        Some(ImageKind::Png)
+    } else if bytes.starts_with(&[0xFF, 0xD8]) {
+        Some(ImageKind::Jpeg)
+    } else {
+        None
+    }
+}
+
+// 根据文件头判断扩展名
+pub fn image_extension(bytes: &[u8]) -> Option<&'static str> {
+    match image_kind(bytes)? {
+        ImageKind::Png => Some("png"),
+        ImageKind::Jpeg => Some("jpg"),
+    }
+}
+
+// 把元数据写入已编码的 PNG / JPEG，其他格式返回错误
+pub fn embed(bytes: &[u8], metadata: &CaptureMetadata) -> Result<Vec<u8>, String> {
+    match image_kind(bytes) {
+        Some(ImageKind::Png) => embed_png(bytes, metadata),
+        Some(ImageKind::Jpeg) => embed_jpeg(bytes, metadata),
+        None => Err("Unsupported image format for metadata".to_string()),
+    }
+}
+
+// 读取 PNG / JPEG 中的元数据，没有本应用写入的字段时 capture 中对应项为空
+pub fn read(bytes: &[u8]) -> Result<ImageMetadata, String> {
+    match image_kind(bytes) {
+        Some(ImageKind::Png) => read_png(bytes),
+        Some(ImageKind::Jpeg) => read_jpeg(bytes),
+        None => Err("Unsupported image format for metadata".to_string()),
+    }
+}
+
+fn fields(metadata: &CaptureMetadata) -> [(&'static str, &'static str, Option<&String>); 7] {
+    // (PNG 关键字, XMP 属性, 值)
+    [
+        (PNG_KEY_TIME, "xmp:CreateDate", metadata.captured_at.as_ref()),
+        (PNG_KEY_SOFTWARE, "xmp:CreatorTool", metadata.software.as_ref()),
+        (PNG_KEY_MONITOR, "capture:Monitor", metadata.monitor.as_ref()),
+        (PNG_KEY_MODE, "capture:Mode", metadata.mode.as_ref()),
+        (PNG_KEY_APP, "capture:Application", metadata.app_name.as_ref()),
+        (PNG_KEY_WINDOW, "capture:WindowTitle", metadata.window_title.as_ref()),
+        (PNG_KEY_VERSION, "capture:AppVersion", metadata.app_version.as_ref()),
+    ]
+}
+
+// 按字段表把读到的文本填入 CaptureMetadata
+fn capture_from(entries: &BTreeMap<String, String>, prefix: &str, use_png_keys: bool) -> CaptureMetadata {
+    let mut capture = CaptureMetadata::default();
+    let get = |png_key: &str, xmp_key: &str| {
+        let key = if use_png_keys { png_key } else { xmp_key };
+        entries.get(&format!("{}.{}", prefix, key)).cloned()
+    };
+    capture.captured_at = get(PNG_KEY_TIME, "xmp:CreateDate");
+    capture.software = get(PNG_KEY_SOFTWARE, "xmp:CreatorTool");
+    capture.monitor = get(PNG_KEY_MONITOR, "capture:Monitor");
+    capture.mode = get(PNG_KEY_MODE, "capture:Mode");
+    capture.app_name = get(PNG_KEY_APP, "capture:Application");
+    capture.window_title = get(PNG_KEY_WINDOW, "capture:WindowTitle");
+    capture.app_version = get(PNG_KEY_VERSION, "capture:AppVersion");
+    capture
+}
+
+// 文本块插入到第一个 IDAT 之前，解码器读取图像信息时即可读到；
+// 之前写入的同名文本块会被替换，其他文本块保留
+fn embed_png(bytes: &[u8], metadata: &CaptureMetadata) -> Result<Vec<u8>, String> {
+    let keys = fields(metadata).map(|(key, _, _)| key);
+    let mut kept = Vec::with_capacity(bytes.len());
+    let mut position = PNG_SIGNATURE.len();
+    loop {
+        let header = bytes
+            .get(position..position + 8)
+            .ok_or("Invalid PNG: missing IDAT chunk")?;
+        let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let kind = &header[4..8];
+        if kind == b"IDAT" {
+            break;
+        }
+        let chunk = bytes
+            .get(position..position + 12 + length)
+            .ok_or("Invalid PNG: truncated chunk")?;
+        // 关键字位于数据开头，以 \0 结尾
+        let keyword = chunk[8..8 + length].split(|&b| b == 0).next().unwrap_or_default();
+        let replaced = matches!(kind, b"tEXt" | b"zTXt" | b"iTXt")
+            && keys.iter().any(|key| key.as_bytes() == keyword);
+        if !replaced {
+            kept.extend_from_slice(chunk);
+        }
+        position += 12 + length;
+    }
+
+    let mut chunks = Vec::new();
+    for (key, _, value) in fields(metadata) {
+        let Some(value) = value else { continue };
+        // 只含 ASCII 时使用 tEXt（兼容性最好），否则使用 UTF-8 的 iTXt
+        let encoded = if value.is_ascii() {
+            TEXtChunk::new(key, value.as_str()).encode(&mut chunks)
+        } else {
+            ITXtChunk::new(key, value.as_str()).encode(&mut chunks)
+        };
+        encoded.map_err(|e| format!("Failed to encode PNG text chunk: {}", e))?;
+    }
+
+    let mut output = Vec::with_capacity(bytes.len() + chunks.len());
+    output.extend_from_slice(PNG_SIGNATURE);
+    output.extend_from_slice(&kept);
+    output.extend_from_slice(&chunks);
+    output.extend_from_slice(&bytes[position..]);
+    Ok(output)
+}
+
+// 只读取图像数据之前的文本块（本应用写入的位置）
+fn read_png(bytes: &[u8]) -> Result<ImageMetadata, String> {
+    let reader = png::Decoder::new(Cursor::new(bytes))
+        .read_info()
+        .map_err(|e| format!("Failed to read PNG: {}", e))?;
+    let info = reader.info();
+
+    let mut entries = BTreeMap::new();
+    for chunk in &info.uncompressed_latin1_text {
+        entries.insert(format!("PNG.{}", chunk.keyword), chunk.text.clone());
+    }
+    for chunk in &info.compressed_latin1_text {
+        let mut chunk = chunk.clone();
+        if chunk.decompress_text().is_ok() {
+            if let Ok(text) = chunk.get_text() {
+                entries.insert(format!("PNG.{}", chunk.keyword), text);
+            }
+        }
+    }
+    for chunk in &info.utf8_text {
+        let mut chunk = chunk.clone();
+        if chunk.decompress_text().is_ok() {
+            if let Ok(text) = chunk.get_text() {
+                entries.insert(format!("PNG.{}", chunk.keyword), text);
+            }
+        }
+    }
+
+    Ok(ImageMetadata {
+        format: "png".to_string(),
+        capture: capture_from(&entries, "PNG", true),
+        entries,
+    })
+}
+
+// 遍历 SOS 之前的标记段，返回 (标记, 段数据的起止位置)
+fn jpeg_segments(bytes: &[u8]) -> Result<Vec<(u8, usize, usize)>, String> {
+    let mut segments = Vec::new();
+    let mut position = 2;
+    loop {
+        let header = bytes.get(position..position + 4).ok_or("Invalid JPEG: truncated header")?;
+        if header[0] != 0xFF {
+            return Err("Invalid JPEG: bad marker".to_string());
+        }
+        let marker = header[1];
+        // SOS 之后为图像数据
+        if marker == 0xDA {
+            return Ok(segments);
+        }
+        let length = u16::from_be_bytes([header[2], header[3]]) as usize;
+        if length < 2 || position + 2 + length > bytes.len() {
+            return Err("Invalid JPEG: truncated segment".to_string());
+        }
+        segments.push((marker, position + 4, position + 2 + length));
+        position += 2 + length;
+    }
+}
+
+// EXIF 与 XMP 放在 JFIF（APP0）之后，原有的 EXIF / XMP 段会被替换
+fn embed_jpeg(bytes: &[u8], metadata: &CaptureMetadata) -> Result<Vec<u8>, String> {
+    let segments = jpeg_segments(bytes)?;
+
+    let mut app1 = Vec::new();
+    if let Some(exif) = build_exif(metadata) {
+        write_jpeg_segment(&mut app1, EXIF_HEADER, &exif)?;
+    }
+    write_jpeg_segment(&mut app1, XMP_HEADER, build_xmp(metadata).as_bytes())?;
+
+    // 图像数据（SOS）开始的位置与开头 JFIF 段的数量
+    let image_data = segments.last().map(|segment| segment.2).unwrap_or(2);
+    let leading = segments.iter().take_while(|segment| segment.0 == 0xE0).count();
+
+    let mut output = Vec::with_capacity(bytes.len() + app1.len());
+    output.extend_from_slice(&bytes[..2]);
+    for (index, &(marker, start, end)) in segments.iter().enumerate() {
+        if index == leading {
+            output.extend_from_slice(&app1);
+        }
+        let data = &bytes[start..end];
+        if marker == 0xE1 && (data.starts_with(EXIF_HEADER) || data.starts_with(XMP_HEADER)) {
+            continue;
+        }
+        output.extend_from_slice(&bytes[start - 4..end]);
+    }
+    if leading == segments.len() {
+        output.extend_from_slice(&app1);
+    }
+    output.extend_from_slice(&bytes[image_data..]);
+    Ok(output)
+}
+
+fn write_jpeg_segment(output: &mut Vec<u8>, header: &[u8], data: &[u8]) -> Result<(), String> {
+    let length = 2 + header.len() + data.len();
+    if length > u16::MAX as usize {
+        return Err("Image metadata is too large".to_string());
+    }
+    output.extend_from_slice(&[0xFF, 0xE1]);
+    output.extend_from_slice(&(length as u16).to_be_bytes());
+    output.extend_from_slice(header);
+    output.extend_from_slice(data);
+    Ok(())
+}
+
+fn read_jpeg(bytes: &[u8]) -> Result<ImageMetadata, String> {
+    let mut entries = BTreeMap::new();
+    for (marker, start, end) in jpeg_segments(bytes)? {
+        let data = &bytes[start..end];
+        if marker != 0xE1 {
+            continue;
+        }
+        if let Some(tiff) = data.strip_prefix(EXIF_HEADER) {
+            read_exif(tiff, &mut entries);
+        } else if let Some(xmp) = data.strip_prefix(XMP_HEADER) {
+            read_xmp(&String::from_utf8_lossy(xmp), &mut entries);
+        }
+    }
+
+    // 优先使用 XMP，没有时从 EXIF 的标准字段补充时间和软件
+    let mut capture = capture_from(&entries, "XMP", false);
+    if capture.captured_at.is_none() {
+        capture.captured_at = exif_time(&entries);
+    }
+    if capture.software.is_none() {
+        capture.software = entries.get("EXIF.Software").cloned();
+    }
+    Ok(ImageMetadata {
+        format: "jpeg".to_string(),
+        capture,
+        entries,
+    })
+}
+
+struct IfdEntry {
+    tag: u16,
+    kind: u16,
+    count: u32,
+    value: Vec<u8>,
+}
+
+impl IfdEntry {
+    fn ascii(tag: u16, text: &str) -> Self {
+        let mut value = text.as_bytes().to_vec();
+        value.push(0);
+        Self { tag, kind: TYPE_ASCII, count: value.len() as u32, value }
+    }
+
+    fn long(tag: u16, value: u32) -> Self {
+        Self { tag, kind: TYPE_LONG, count: 1, value: value.to_be_bytes().to_vec() }
+    }
+
+    // 超过 4 字节的值存放在 IFD 之后，按偶数对齐
+    fn data_size(&self) -> usize {
+        if self.value.len() > 4 {
+            self.value.len().next_multiple_of(2)
+        } else {
+            0
+        }
+    }
+}
+
+fn ifd_size(entries: &[IfdEntry]) -> usize {
+    2 + 12 * entries.len() + 4 + entries.iter().map(IfdEntry::data_size).sum::<usize>()
+}
+
+// 在 TIFF 数据末尾写入一个 IFD（大端），偏移量相对 TIFF 头
+fn write_ifd(tiff: &mut Vec<u8>, entries: &[IfdEntry]) {
+    let mut data_offset = tiff.len() + 2 + 12 * entries.len() + 4;
+    tiff.extend_from_slice(&(entries.len() as u16).to_be_bytes());
+    for entry in entries {
+        tiff.extend_from_slice(&entry.tag.to_be_bytes());
+        tiff.extend_from_slice(&entry.kind.to_be_bytes());
+        tiff.extend_from_slice(&entry.count.to_be_bytes());
+        if entry.value.len() > 4 {
+            tiff.extend_from_slice(&(data_offset as u32).to_be_bytes());
+            data_offset += entry.data_size();
+        } else {
+            let mut inline = [0u8; 4];
+            inline[..entry.value.len()].copy_from_slice(&entry.value);
+            tiff.extend_from_slice(&inline);
+        }
+    }
+    tiff.extend_from_slice(&0u32.to_be_bytes());
+    for entry in entries.iter().filter(|entry| entry.value.len() > 4) {
+        tiff.extend_from_slice(&entry.value);
+        tiff.resize(tiff.len() + entry.data_size() - entry.value.len(), 0);
+    }
+}
+
+// EXIF 只写入标准的时间与软件字段（ASCII），其余字段在 XMP 中
+fn build_exif(metadata: &CaptureMetadata) -> Option<Vec<u8>> {
+    let time = metadata
+        .captured_at
+        .as_deref()
+        .and_then(|time| chrono::DateTime::parse_from_rfc3339(time).ok());
+    if time.is_none() && metadata.software.is_none() {
+        return None;
+    }
+
+    let mut ifd0 = Vec::new();
+    if let Some(software) = metadata.software.as_deref().filter(|software| software.is_ascii()) {
+        ifd0.push(IfdEntry::ascii(TAG_SOFTWARE, software));
+    }
+    let mut exif_ifd = Vec::new();
+    if let Some(time) = time {
+        let date_time = time.format("%Y:%m:%d %H:%M:%S").to_string();
+        let offset = time.format("%:z").to_string();
+        ifd0.push(IfdEntry::ascii(TAG_DATE_TIME, &date_time));
+        exif_ifd.push(IfdEntry::ascii(TAG_DATE_TIME_ORIGINAL, &date_time));
+        exif_ifd.push(IfdEntry::ascii(TAG_OFFSET_TIME, &offset));
+        exif_ifd.push(IfdEntry::ascii(TAG_OFFSET_TIME_ORIGINAL, &offset));
+    }
+
+    let mut tiff = b"MM\0\x2A\0\0\0\x08".to_vec();
+    if !exif_ifd.is_empty() {
+        // 指针本身占 4 字节内联，先加入再计算 IFD0 大小
+        ifd0.push(IfdEntry::long(TAG_EXIF_IFD, 0));
+        let exif_offset = 8 + ifd_size(&ifd0);
+        ifd0.last_mut()?.value = (exif_offset as u32).to_be_bytes().to_vec();
+    }
+    write_ifd(&mut tiff, &ifd0);
+    if !exif_ifd.is_empty() {
+        write_ifd(&mut tiff, &exif_ifd);
+    }
+    Some(tiff)
+}
+
+fn exif_tag_name(tag: u16) -> Option<&'static str> {
+    Some(match tag {
+        0x010E => "ImageDescription",
+        0x010F => "Make",
+        0x0110 => "Model",
+        TAG_SOFTWARE => "Software",
+        TAG_DATE_TIME => "DateTime",
+        0x013B => "Artist",
+        0x8298 => "Copyright",
+        TAG_DATE_TIME_ORIGINAL => "DateTimeOriginal",
+        0x9004 => "DateTimeDigitized",
+        TAG_OFFSET_TIME => "OffsetTime",
+        TAG_OFFSET_TIME_ORIGINAL => "OffsetTimeOriginal",
+        0x9012 => "OffsetTimeDigitized",
+        _ => return None,
+    })
+}
+
+// 读取 IFD0 和 EXIF IFD 中的常见 ASCII 字段，数据不完整时跳过
+fn read_exif(tiff: &[u8], entries: &mut BTreeMap<String, String>) {
+    let big_endian = match tiff.get(..2) {
+        Some(b"MM") => true,
+        Some(b"II") => false,
+        _ => return,
+    };
+    let u16_at = |offset: usize| {
+        tiff.get(offset..offset + 2).map(|b| {
+            if big_endian { u16::from_be_bytes([b[0], b[1]]) } else { u16::from_le_bytes([b[0], b[1]]) }
+        })
+    };
+    let u32_at = |offset: usize| {
+        tiff.get(offset..offset + 4).map(|b| {
+            let b = [b[0], b[1], b[2], b[3]];
+            if big_endian { u32::from_be_bytes(b) } else { u32::from_le_bytes(b) }
+        })
+    };
+
+    let mut pending = vec![u32_at(4).unwrap_or(0) as usize];
+    let mut visited = Vec::new();
+    while let Some(ifd) = pending.pop() {
+        if ifd == 0 || visited.contains(&ifd) {
+            continue;
+        }
+        visited.push(ifd);
+        let Some(count) = u16_at(ifd) else { continue };
+        for index in 0..count as usize {
+            let entry = ifd + 2 + index * 12;
+            let (Some(tag), Some(kind), Some(length)) = (u16_at(entry), u16_at(entry + 2), u32_at(entry + 4)) else {
+                break;
+            };
+            if tag == TAG_EXIF_IFD {
+                pending.extend(u32_at(entry + 8).map(|offset| offset as usize));
+                continue;
+            }
+            let Some(name) = exif_tag_name(tag) else { continue };
+            if kind != TYPE_ASCII {
+                continue;
+            }
+            let length = length as usize;
+            let start = if length > 4 { u32_at(entry + 8).unwrap_or(0) as usize } else { entry + 8 };
+            let Some(value) = tiff.get(start..start.saturating_add(length)) else { continue };
+            let text = String::from_utf8_lossy(value).trim_end_matches('\0').trim().to_string();
+            if !text.is_empty() {
+                entries.insert(format!("EXIF.{}", name), text);
+            }
+        }
+    }
+}
+
+// EXIF 的 DateTimeOriginal + OffsetTimeOriginal 转为 RFC 3339
+fn exif_time(entries: &BTreeMap<String, String>) -> Option<String> {
+    let date_time = entries.get("EXIF.DateTimeOriginal").or(entries.get("EXIF.DateTime"))?;
+    let naive = chrono::NaiveDateTime::parse_from_str(date_time, "%Y:%m:%d %H:%M:%S").ok()?;
+    match entries.get("EXIF.OffsetTimeOriginal").or(entries.get("EXIF.OffsetTime")) {
+        Some(offset) => chrono::DateTime::parse_from_rfc3339(&format!("{}{}", naive.format("%Y-%m-%dT%H:%M:%S"), offset))
+            .ok()
+            .map(|time| time.to_rfc3339()),
+        // 没有时区信息
+        None => Some(naive.format("%Y-%m-%dT%H:%M:%S").to_string()),
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            // 控制字符在 XML 1.0 中无效
+            c if c.is_control() && c != '\t' => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn xml_unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(index) = rest.find('&') {
+        unescaped.push_str(&rest[..index]);
+        rest = &rest[index..];
+        let Some(end) = rest.find(';') else { break };
+        let entity = &rest[1..end];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                unescaped.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+fn build_xmp(metadata: &CaptureMetadata) -> String {
+    let attributes: String = fields(metadata)
+        .iter()
+        .filter_map(|(_, name, value)| value.map(|value| format!("\n    {}=\"{}\"", name, xml_escape(value))))
+        .collect();
+    format!(
+        concat!(
+            "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n",
+            "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n",
+            " <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n",
+            "  <rdf:Description rdf:about=\"\"\n",
+            "    xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\"\n",
+            "    xmlns:capture=\"{}\"{}/>\n",
+            " </rdf:RDF>\n",
+            "</x:xmpmeta>\n",
+            "<?xpacket end=\"w\"?>"
+        ),
+        CAPTURE_NAMESPACE, attributes
+    )
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, ':' | '_' | '-' | '.')
+}
+
+// 简单解析 XMP：读取带前缀的属性（prefix:Name="value"）和只含文本的元素（<prefix:Name>value</prefix:Name>），
+// 跳过命名空间声明和 RDF / xmpmeta 自身的结构
+fn read_xmp(xmp: &str, entries: &mut BTreeMap<String, String>) {
+    let is_structural = |name: &str| name.starts_with("xmlns") || name.starts_with("rdf:") || name.starts_with("x:");
+
+    let mut rest = xmp;
+    while let Some(index) = rest.find("=\"").or_else(|| rest.find("='")) {
+        let quote = &rest[index + 1..index + 2];
+        let name_start = rest[..index]
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| is_name_char(*c))
+            .last()
+            .map(|(start, _)| start)
+            .unwrap_or(index);
+        let name = &rest[name_start..index];
+        let value_start = index + 2;
+        let Some(length) = rest[value_start..].find(quote) else { break };
+        if name.contains(':') && !is_structural(name) {
+            entries.insert(format!("XMP.{}", name), xml_unescape(&rest[value_start..value_start + length]));
+        }
+        rest = &rest[value_start + length + 1..];
+    }
+
+    let mut rest = xmp;
+    while let Some(index) = rest.find('<') {
+        rest = &rest[index + 1..];
+        let name_length = rest.find(|c: char| !is_name_char(c)).unwrap_or(rest.len());
+        let name = &rest[..name_length];
+        if name.is_empty() || !name.contains(':') || is_structural(name) || !rest[name_length..].starts_with('>') {
+            continue;
+        }
+        let content = &rest[name_length + 1..];
+        let Some(end) = content.find("</") else { break };
+        let text = content[..end].trim();
+        if !text.is_empty() && !text.contains('<') {
+            entries.insert(format!("XMP.{}", name), xml_unescape(text));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageOutputFormat, Rgba, RgbaImage};
+
+    fn sample_metadata() -> CaptureMetadata {
+        CaptureMetadata {
+            captured_at: Some("2026-10-18T09:30:15+08:00".to_string()),
+            monitor: Some("1".to_string()),
+            mode: Some("region".to_string()),
+            app_name: Some("firefox".to_string()),
+            window_title: Some("截图 & \"元数据\" <测试> — Mozilla Firefox".to_string()),
+            app_version: Some("0.1.0".to_string()),
+            software: Some("tauri-app 0.1.0".to_string()),
+        }
+    }
+
+    fn encode(format: ImageOutputFormat) -> Vec<u8> {
+        let image = RgbaImage::from_fn(64, 48, |x, y| Rgba([(x * 4) as u8, (y * 5) as u8, 128, 255]));
+        let mut bytes = Vec::new();
+        // JPEG 不支持透明通道
+        image::DynamicImage::ImageRgba8(image)
+            .to_rgb8()
+            .write_to(&mut Cursor::new(&mut bytes), format)
+            .unwrap();
+        bytes
+    }
+
+    // 原始数据中某个块类型 / 段头出现的次数
+    fn count(bytes: &[u8], needle: &[u8]) -> usize {
+        bytes.windows(needle.len()).filter(|window| *window == needle).count()
+    }
+
+    fn assert_round_trip(bytes: Vec<u8>, extension: &str, markers: &[&[u8]]) {
+        let expected = sample_metadata();
+        let embedded = embed(&bytes, &expected).unwrap();
+        // 写入元数据后仍是有效图片
+        let decoded = image::load_from_memory(&embedded).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (64, 48));
+        assert_eq!(image_extension(&embedded), Some(extension));
+        assert_eq!(read(&embedded).unwrap().capture, expected);
+
+        // 再次写入时替换而不是重复
+        let twice = embed(&embedded, &expected).unwrap();
+        assert_eq!(read(&twice).unwrap().capture, expected);
+        for marker in markers {
+            assert!(count(&embedded, marker) > 0);
+            assert_eq!(count(&twice, marker), count(&embedded, marker));
+        }
+    }
+
+    #[test]
+    fn png_round_trip() {
+        assert_round_trip(encode(ImageOutputFormat::Png), "png", &[b"tEXt", b"iTXt"]);
+    }
+
+    #[test]
+    fn jpeg_round_trip() {
+        assert_round_trip(encode(ImageOutputFormat::Jpeg(90)), "jpg", &[EXIF_HEADER, XMP_HEADER]);
+    }
+
+    #[test]
+    fn png_keeps_unrelated_text_chunks() {
+        let bytes = encode(ImageOutputFormat::Png);
+        let mut chunk = Vec::new();
+        TEXtChunk::new("Comment", "keep me").encode(&mut chunk).unwrap();
+        // 插入到 IHDR（13 字节数据）之后
+        let ihdr_end = PNG_SIGNATURE.len() + 12 + 13;
+        let with_comment = [&bytes[..ihdr_end], &chunk, &bytes[ihdr_end..]].concat();
+
+        let twice = embed(&embed(&with_comment, &sample_metadata()).unwrap(), &sample_metadata()).unwrap();
+        assert_eq!(count(&twice, b"Comment\0keep me"), 1);
+        assert_eq!(read(&twice).unwrap().entries.get("PNG.Comment").map(String::as_str), Some("keep me"));
+    }
+
+    #[test]
+    fn xml_escape_round_trip() {
+        let text = "a & b <c> \"d\" 'e' 截图";
+        assert_eq!(xml_unescape(&xml_escape(text)), text);
+    }
+}
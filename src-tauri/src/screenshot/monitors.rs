@@ -0,0 +1,294 @@
+// 多显示器：列出显示器信息，截取指定显示器或将整个虚拟桌面拼接为一张图
+use std::thread;
+use std::time::Instant;
+use image::{imageops, Rgba, RgbaImage};
+use screenshots::Screen;
+use serde::{Deserialize, Serialize};
+use tauri::{command, PhysicalPosition, PhysicalSize, WebviewWindow};
+
+use super::pipeline::{self, elapsed_ms, Capture, CaptureTimings};
+use super::{backend, capture_screen_image, cursor, get_cursor_screen};
+
+#[cfg(target_os = "linux")]
+use xcb::randr::GetOutputInfo;
+#[cfg(windows)]
+use windows::Win32::Graphics::Gdi::{GetMonitorInfoW, HMONITOR, MONITORINFO, MONITORINFOEXW};
+
+// 截图目标：鼠标所在显示器（默认）、指定索引的显示器，或全部显示器拼接
+#[derive(Debug, Clone, Copy, Deserialize, Default)]
+pub enum MonitorTarget {
+    #[default]
+    #[serde(rename = "cursor")]
+    Cursor,
+    #[serde(rename = "index")]
+    Index(usize),
+    #[serde(rename = "all")]
+    All,
+}
+
+// 显示器内的区域（逻辑坐标，相对于显示器左上角）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CaptureRegion {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitorInfo {
+    pub index: usize,
+    pub id: u32,
+    pub name: String,
+    // 逻辑坐标（与 screenshots / display-info 返回的一致）
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    // 实际截图的像素尺寸
+    pub physical_width: u32,
+    pub physical_height: u32,
+    pub scale_factor: f32,
+    pub rotation: f32,
+    pub frequency: f32,
+    pub is_primary: bool,
+}
+
+pub fn list_screens() -> Result<Vec<Screen>, String> {
+    let screens = Screen::all().map_err(|e| format!("Failed to get screens: {}", e))?;
+    if screens.is_empty() {
+        return Err("No screens found".to_string());
+    }
+    Ok(screens)
+}
+
+// X11 下通过 RandR 读取输出名称（如 HDMI-1、eDP-1）
+#[cfg(target_os = "linux")]
+fn monitor_names(screens: &[Screen]) -> Vec<Option<String>> {
+    let Ok((conn, _)) = xcb::Connection::connect(None) else {
+        return vec![None; screens.len()];
+    };
+    screens
+        .iter()
+        .map(|screen| {
+            let cookie = conn.send_request(&GetOutputInfo {
+                output: screen.display_info.raw_handle,
+                config_timestamp: 0,
+            });
+            conn.wait_for_reply(cookie)
+                .ok()
+                .map(|reply| String::from_utf8_lossy(reply.name()).into_owned())
+        })
+        .collect()
+}
+
+// Windows 下读取设备名（如 \\.\DISPLAY1）
+#[cfg(windows)]
+fn monitor_names(screens: &[Screen]) -> Vec<Option<String>> {
+    screens
+        .iter()
+        .map(|screen| unsafe {
+            let mut info = MONITORINFOEXW::default();
+            info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+            let handle = HMONITOR(screen.display_info.raw_handle.0);
+            if !GetMonitorInfoW(handle, &mut info as *mut MONITORINFOEXW as *mut MONITORINFO).as_bool() {
+                return None;
+            }
+            let len = info.szDevice.iter().position(|&c| c == 0).unwrap_or(info.szDevice.len());
+            Some(String::from_utf16_lossy(&info.szDevice[..len]))
+        })
+        .collect()
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn monitor_names(screens: &[Screen]) -> Vec<Option<String>> {
+    vec![None; screens.len()]
+}
+
+pub fn monitor_infos(screens: &[Screen]) -> Vec<MonitorInfo> {
+    let names = monitor_names(screens);
+    screens
+        .iter()
+        .zip(names)
+        .enumerate()
+        .map(|(index, (screen, name))| {
+            let info = &screen.display_info;
+            MonitorInfo {
+                index,
+                id: info.id,
+                name: name.unwrap_or_else(|| format!("Display {}", index + 1)),
+                x: info.x,
+                y: info.y,
+                width: info.width,
+                height: info.height,
+                physical_width: (info.width as f32 * info.scale_factor).round() as u32,
+                physical_height: (info.height as f32 * info.scale_factor).round() as u32,
+                scale_factor: info.scale_factor,
+                rotation: info.rotation,
+                frequency: info.frequency,
+                is_primary: info.is_primary,
+            }
+        })
+        .collect()
+}
+
+// 截取显示器内的区域
+pub fn capture_region_image(screen: &Screen, region: &CaptureRegion) -> Result<RgbaImage, String> {
+    if region.width == 0 || region.height == 0 {
+        return Err("Region must not be empty".to_string());
+    }
+    backend::capture_area(screen, region)
+}
+
+// 显示器左上角在桌面上的物理像素坐标
+// X11 / macOS 下 display-info 返回逻辑坐标，需乘以缩放比例；Windows 下即为物理坐标
+pub fn physical_origin(screen: &Screen) -> (i32, i32) {
+    let info = &screen.display_info;
+    if cfg!(windows) {
+        (info.x, info.y)
+    } else {
+        (
+            (info.x as f32 * info.scale_factor).round() as i32,
+            (info.y as f32 * info.scale_factor).round() as i32,
+        )
+    }
+}
+
+// 按物理像素放置窗口并显示。构建窗口时的逻辑坐标按创建时所在显示器的缩放换算，
+// 目标显示器缩放不同时位置和大小都会偏差，因此窗口先隐藏创建，放置后再显示
+pub fn place_window(window: &WebviewWindow, (x, y): (i32, i32), (width, height): (u32, u32)) -> Result<(), String> {
+    window
+        .set_position(PhysicalPosition::new(x, y))
+        .map_err(|e| format!("Failed to position window: {}", e))?;
+    window
+        .set_size(PhysicalSize::new(width, height))
+        .map_err(|e| format!("Failed to resize window: {}", e))?;
+    window.show().map_err(|e| format!("Failed to show window: {}", e))
+}
+
+// 并行截取所有显示器
+pub fn capture_all_screens(screens: &[Screen]) -> Result<Vec<RgbaImage>, String> {
+    thread::scope(|scope| {
+        let workers: Vec<_> = screens
+            .iter()
+            .map(|screen| scope.spawn(move || capture_screen_image(screen)))
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().map_err(|_| "Capture worker panicked".to_string())?)
+            .collect::<Result<Vec<_>, String>>()
+    })
+}
+
+// 将所有显示器拼接成一张图
+// 各显示器的位置都按逻辑坐标计算，再统一乘以最大的缩放比例，
+// 缩放比例较低的显示器放大到同一比例，混合 DPI 布局下也不会出现重叠或错位
+pub fn capture_virtual_desktop(screens: &[Screen]) -> Result<RgbaImage, String> {
+    let scale = screens
+        .iter()
+        .map(|s| s.display_info.scale_factor)
+        .fold(1.0_f32, f32::max);
+
+    let min_x = screens.iter().map(|s| s.display_info.x).min().unwrap_or(0);
+    let min_y = screens.iter().map(|s| s.display_info.y).min().unwrap_or(0);
+    let max_x = screens.iter().map(|s| s.display_info.x + s.display_info.width as i32).max().unwrap_or(0);
+    let max_y = screens.iter().map(|s| s.display_info.y + s.display_info.height as i32).max().unwrap_or(0);
+    let to_canvas = |v: i32| (v as f32 * scale).round() as i64;
+
+    let images = capture_all_screens(screens)?;
+
+    let canvas_width = to_canvas(max_x - min_x).max(1) as u32;
+    let canvas_height = to_canvas(max_y - min_y).max(1) as u32;
+    // 显示器未覆盖的区域填充为不透明黑色
+    let mut canvas = RgbaImage::from_pixel(canvas_width, canvas_height, Rgba([0, 0, 0, 255]));
+
+    for (screen, image) in screens.iter().zip(images) {
+        let info = &screen.display_info;
+        let left = to_canvas(info.x - min_x);
+        let top = to_canvas(info.y - min_y);
+        let width = (to_canvas(info.x + info.width as i32 - min_x) - left).max(1) as u32;
+        let height = (to_canvas(info.y + info.height as i32 - min_y) - top).max(1) as u32;
+
+        // 尺寸相差超过 1 像素（取整误差）时才缩放
+        if image.width().abs_diff(width) > 1 || image.height().abs_diff(height) > 1 {
+            let resized = imageops::resize(&image, width, height, imageops::FilterType::Triangle);
+            imageops::replace(&mut canvas, &resized, left, top);
+        } else {
+            imageops::replace(&mut canvas, &image, left, top);
+        }
+    }
+
+    Ok(canvas)
+}
+
+// 把鼠标指针合成到截图中，截图坐标系与 capture_virtual_desktop 的拼接方式一致
+// 当前仅 X11 可获取指针图像，其他平台保持原图
+fn composite_cursor(image: &mut RgbaImage, screens: &[Screen], screen_index: Option<usize>) {
+    let Some(cursor) = cursor::cursor_image() else {
+        return;
+    };
+    let desktop_scale = screens[0].display_info.scale_factor.max(f32::EPSILON);
+
+    let (origin, logical_width) = match screen_index {
+        Some(index) => {
+            let info = &screens[index].display_info;
+            ((info.x, info.y), info.width)
+        }
+        None => {
+            let min_x = screens.iter().map(|s| s.display_info.x).min().unwrap_or(0);
+            let min_y = screens.iter().map(|s| s.display_info.y).min().unwrap_or(0);
+            let max_x = screens.iter().map(|s| s.display_info.x + s.display_info.width as i32).max().unwrap_or(0);
+            ((min_x, min_y), (max_x - min_x).max(1) as u32)
+        }
+    };
+    let pixel_scale = image.width() as f32 / logical_width.max(1) as f32;
+
+    cursor::draw_cursor(image, &cursor, origin, pixel_scale, desktop_scale);
+}
+
+// 按目标截图（阻塞），include_cursor 为 true 时把鼠标指针合成到截图中
+pub fn capture_target(target: MonitorTarget, include_cursor: bool) -> Result<Capture, String> {
+    let mut timings = CaptureTimings::default();
+
+    let start = Instant::now();
+    let screens = list_screens()?;
+    timings.list_screens_ms = elapsed_ms(start);
+
+    let screen_index = match target {
+        MonitorTarget::Cursor => {
+            let start = Instant::now();
+            let index = get_cursor_screen(&screens).unwrap_or(0);
+            timings.locate_cursor_ms = elapsed_ms(start);
+            Some(index)
+        }
+        MonitorTarget::Index(index) if index < screens.len() => Some(index),
+        MonitorTarget::Index(index) => {
+            return Err(format!("Monitor {} not found ({} available)", index, screens.len()));
+        }
+        MonitorTarget::All => None,
+    };
+
+    let mut image = match screen_index {
+        Some(index) => pipeline::capture_screen_timed(&screens[index], &mut timings)?,
+        None => {
+            let start = Instant::now();
+            let image = capture_virtual_desktop(&screens)?;
+            timings.capture_ms = elapsed_ms(start);
+            image
+        }
+    };
+
+    if include_cursor {
+        let start = Instant::now();
+        composite_cursor(&mut image, &screens, screen_index);
+        timings.cursor_ms = Some(elapsed_ms(start));
+    }
+
+    Ok(Capture { image, timings, monitor: screen_index })
+}
+
+// 列出所有显示器
+#[command]
+pub async fn list_monitors() -> Result<Vec<MonitorInfo>, String> {
+    pipeline::run_blocking(|| Ok(monitor_infos(&list_screens()?))).await
+}
@@ -0,0 +1,104 @@
+// 截图流水线：截图、格式转换和编码都在阻塞线程池中执行，不占用 Tauri 异步运行时
+// 各阶段耗时以 CaptureTimings 返回给前端，替代原来的 println! 性能日志
+use std::thread;
+use std::time::Instant;
+use image::RgbaImage;
+use screenshots::Screen;
+use serde::{Deserialize, Serialize};
+
+use super::{backend, encode_jpeg};
+
+// 预览用 JPEG 质量（贴图窗口显示，速度优先）
+pub const PREVIEW_QUALITY: u8 = 50;
+// 存档用 JPEG 质量（写入 screenshots 目录）
+pub const ARCHIVE_QUALITY: u8 = 90;
+
+// 各阶段耗时（毫秒）
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CaptureTimings {
+    pub list_screens_ms: f64,
+    pub locate_cursor_ms: f64,
+    pub capture_ms: f64, // 含像素格式转换（MIT-SHM 后端在截图时完成转换）
+    pub crop_ms: Option<f64>, // 区域或窗口截图的裁剪
+    pub stitch_ms: Option<f64>, // 滚动截图的拼接
+    pub cursor_ms: Option<f64>, // 合成鼠标指针
+    pub preview_encode_ms: f64,
+    pub archive_encode_ms: Option<f64>,
+    pub encode_ms: f64, // 预览与存档并行编码的总耗时
+    pub base64_ms: Option<f64>,
+    pub save_ms: Option<f64>,
+    pub window_ms: Option<f64>,
+    pub total_ms: f64,
+}
+
+pub struct Capture {
+    pub image: RgbaImage,
+    pub timings: CaptureTimings,
+    pub monitor: Option<usize>, // 截取的显示器，拼接全部显示器时为空
+}
+
+pub struct EncodedOutputs {
+    pub preview: Vec<u8>,
+    pub archive: Option<Vec<u8>>,
+}
+
+pub fn elapsed_ms(start: Instant) -> f64 {
+    start.elapsed().as_secs_f64() * 1000.0
+}
+
+// 在阻塞线程池中执行截图相关任务
+pub async fn run_blocking<T, F>(task: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|e| format!("Capture worker failed: {}", e))?
+}
+
+// 截取单个屏幕并记录截图耗时（阻塞），X11 下的 MIT-SHM 后端在截图时完成像素格式转换
+pub fn capture_screen_timed(screen: &Screen, timings: &mut CaptureTimings) -> Result<RgbaImage, String> {
+    let start = Instant::now();
+    let image = backend::capture_screen(screen)?;
+    timings.capture_ms = elapsed_ms(start);
+
+    Ok(image)
+}
+
+// 并行编码预览图和存档图（阻塞）
+pub fn encode_outputs(
+    image: &RgbaImage,
+    with_archive: bool,
+    timings: &mut CaptureTimings,
+) -> Result<EncodedOutputs, String> {
+    let start = Instant::now();
+
+    let (preview, archive) = thread::scope(|scope| {
+        let archive_worker = with_archive.then(|| {
+            scope.spawn(|| {
+                let start = Instant::now();
+                (encode_jpeg(image, ARCHIVE_QUALITY), elapsed_ms(start))
+            })
+        });
+
+        let preview_start = Instant::now();
+        let preview = encode_jpeg(image, PREVIEW_QUALITY);
+        timings.preview_encode_ms = elapsed_ms(preview_start);
+
+        let archive = match archive_worker {
+            Some(worker) => {
+                let (archive, ms) = worker.join().map_err(|_| "Archive encoder panicked".to_string())?;
+                timings.archive_encode_ms = Some(ms);
+                Some(archive?)
+            }
+            None => None,
+        };
+
+        Ok::<_, String>((preview?, archive))
+    })?;
+
+    timings.encode_ms = elapsed_ms(start);
+
+    Ok(EncodedOutputs { preview, archive })
+}
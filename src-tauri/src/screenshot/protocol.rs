@@ -0,0 +1,211 @@
+// screenshot:// 自定义协议：webview 直接加载截图字节，避免 JPEG → base64 → JSON 的 IPC 开销
+//
+// 地址格式（前端用 convertFileSrc(path, 'screenshot') 生成）：
+//   screenshot://localhost/<id>                 ScreenshotStore 中的截图
+//   screenshot://localhost/file/<编码后的路径>   screenshots 目录下已保存的文件
+// Windows / Android 上对应 http://screenshot.localhost/...
+use std::borrow::Cow;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::{AppHandle, Manager, UriSchemeContext, UriSchemeResponder};
+
+use super::store::{guess_mime, ScreenshotStore};
+use super::screenshots_dir;
+
+pub const SCHEME: &str = "screenshot";
+
+// 图片数据与 MIME 类型，或错误状态码与说明
+type Loaded = Result<(Arc<Vec<u8>>, &'static str), (StatusCode, &'static str)>;
+
+// 注册到 Builder::register_asynchronous_uri_scheme_protocol，读取和切片在后台线程完成
+pub fn handle(ctx: UriSchemeContext<'_, tauri::Wry>, request: Request<Vec<u8>>, responder: UriSchemeResponder) {
+    let app = ctx.app_handle().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        responder.respond(build_response(&app, &request));
+    });
+}
+
+fn build_response(app: &AppHandle, request: &Request<Vec<u8>>) -> Response<Cow<'static, [u8]>> {
+    let path = request.uri().path().trim_start_matches('/');
+    let path = match urlencoding::decode(path) {
+        Ok(path) => path.into_owned(),
+        Err(_) => return error_response(StatusCode::BAD_REQUEST, "Invalid path"),
+    };
+
+    let loaded = match path.strip_prefix("file/") {
+        Some(file_path) => load_file(app, file_path),
+        None => app
+            .state::<ScreenshotStore>()
+            .get(&path)
+            .map(|image| (image.bytes, image.mime))
+            .ok_or((StatusCode::NOT_FOUND, "Screenshot not found")),
+    };
+    let (bytes, mime) = match loaded {
+        Ok(loaded) => loaded,
+        Err((status, message)) => return error_response(status, message),
+    };
+
+    let range = request
+        .headers()
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok());
+    respond_with_range(bytes, mime, range)
+}
+
+// 只允许访问 screenshots 目录下的文件
+fn load_file(app: &AppHandle, file_path: &str) -> Loaded {
+    let root = screenshots_dir(app)
+        .and_then(|dir| dir.canonicalize().map_err(|e| e.to_string()))
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Screenshots directory unavailable"))?;
+    let path = PathBuf::from(file_path)
+        .canonicalize()
+        .map_err(|_| (StatusCode::NOT_FOUND, "File not found"))?;
+    if !path.starts_with(&root) {
+        return Err((StatusCode::FORBIDDEN, "Access denied"));
+    }
+
+    let bytes = fs::read(&path).map_err(|_| (StatusCode::NOT_FOUND, "File not found"))?;
+    let mime = guess_mime(&bytes);
+    Ok((Arc::new(bytes), mime))
+}
+
+enum ByteRange {
+    Full,
+    Partial(usize, usize), // 闭区间 [start, end]
+    Unsatisfiable,
+}
+
+// 解析单段 Range 头（bytes=start-end / bytes=start- / bytes=-suffix）；
+// 其他单位、多段范围和格式错误的头按规范忽略，返回完整内容
+fn parse_range(range: Option<&str>, len: usize) -> ByteRange {
+    let Some(spec) = range.and_then(|range| range.trim().strip_prefix("bytes=")) else {
+        return ByteRange::Full;
+    };
+    if spec.contains(',') {
+        return ByteRange::Full; // 不支持多段范围
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return ByteRange::Full;
+    };
+
+    let (start, end) = if start.is_empty() {
+        // 最后 suffix 个字节
+        let Ok(suffix) = end.parse::<usize>() else {
+            return ByteRange::Full;
+        };
+        if suffix == 0 {
+            return ByteRange::Unsatisfiable;
+        }
+        (len.saturating_sub(suffix), usize::MAX)
+    } else {
+        let Ok(start) = start.parse::<usize>() else {
+            return ByteRange::Full;
+        };
+        let end = match end {
+            "" => usize::MAX,
+            end => match end.parse::<usize>() {
+                Ok(end) if end >= start => end,
+                _ => return ByteRange::Full,
+            },
+        };
+        (start, end)
+    };
+
+    if start >= len {
+        return ByteRange::Unsatisfiable;
+    }
+    ByteRange::Partial(start, end.min(len - 1))
+}
+
+// 完整响应需要一份自有数据；范围请求只复制请求的部分
+fn respond_with_range(bytes: Arc<Vec<u8>>, mime: &'static str, range: Option<&str>) -> Response<Cow<'static, [u8]>> {
+    let len = bytes.len();
+    let builder = Response::builder()
+        .header(header::CONTENT_TYPE, mime)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*");
+
+    match parse_range(range, len) {
+        ByteRange::Full => builder
+            .header(header::CONTENT_LENGTH, len)
+            .body(Cow::Owned(Arc::try_unwrap(bytes).unwrap_or_else(|shared| shared.to_vec())))
+            .unwrap(),
+        ByteRange::Partial(start, end) => builder
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, len))
+            .header(header::CONTENT_LENGTH, end - start + 1)
+            .body(Cow::Owned(bytes[start..=end].to_vec()))
+            .unwrap(),
+        ByteRange::Unsatisfiable => builder
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .header(header::CONTENT_RANGE, format!("bytes */{}", len))
+            .body(Cow::Borrowed(&[][..]))
+            .unwrap(),
+    }
+}
+
+fn error_response(status: StatusCode, message: &'static str) -> Response<Cow<'static, [u8]>> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain")
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(Cow::Borrowed(message.as_bytes()))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn partial(range: &str, len: usize) -> Option<(usize, usize)> {
+        match parse_range(Some(range), len) {
+            ByteRange::Partial(start, end) => Some((start, end)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn parses_single_ranges() {
+        assert_eq!(partial("bytes=0-", 100), Some((0, 99)));
+        assert_eq!(partial("bytes=10-19", 100), Some((10, 19)));
+        assert_eq!(partial("bytes=-10", 100), Some((90, 99)));
+        // 后缀长度超过文件时返回整个文件
+        assert_eq!(partial("bytes=-500", 100), Some((0, 99)));
+        // 结束位置超过文件末尾时截断
+        assert_eq!(partial("bytes=50-1000", 100), Some((50, 99)));
+    }
+
+    #[test]
+    fn unsatisfiable_ranges() {
+        assert!(matches!(parse_range(Some("bytes=-0"), 100), ByteRange::Unsatisfiable));
+        assert!(matches!(parse_range(Some("bytes=100-"), 100), ByteRange::Unsatisfiable));
+        assert!(matches!(parse_range(Some("bytes=0-"), 0), ByteRange::Unsatisfiable));
+    }
+
+    #[test]
+    fn ignored_ranges_return_full_content() {
+        for range in ["bytes=0-9,20-29", "items=0-9", "bytes=abc", "bytes=9-0", "bytes=5"] {
+            assert!(matches!(parse_range(Some(range), 100), ByteRange::Full), "{}", range);
+        }
+        assert!(matches!(parse_range(None, 100), ByteRange::Full));
+    }
+
+    #[test]
+    fn responses_use_expected_status() {
+        let bytes = Arc::new((0..100u8).collect::<Vec<u8>>());
+        let full = respond_with_range(bytes.clone(), "image/png", Some("items=0-9"));
+        assert_eq!(full.status(), StatusCode::OK);
+        assert_eq!(full.body().len(), 100);
+
+        let partial = respond_with_range(bytes.clone(), "image/png", Some("bytes=-10"));
+        assert_eq!(partial.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(partial.body().as_ref(), &bytes[90..]);
+        assert_eq!(partial.headers()[header::CONTENT_RANGE], "bytes 90-99/100");
+
+        let unsatisfiable = respond_with_range(bytes, "image/png", Some("bytes=200-"));
+        assert_eq!(unsatisfiable.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+    }
+}
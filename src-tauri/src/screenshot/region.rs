@@ -0,0 +1,317 @@
+// 区域截图：按下快捷键时冻结所有显示器的画面，在每个显示器上打开一个全屏遮罩窗口显示冻结画面，
+// 用户框选后从冻结画面中裁剪，保证保存的内容与框选时看到的完全一致
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use image::{imageops, RgbaImage};
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle, Manager, State};
+use tauri::webview::WebviewWindowBuilder;
+
+use super::pipeline::{self, elapsed_ms, CaptureTimings};
+use super::store::{ScreenshotStore, StoredImage};
+use super::{capture_metadata, encode_jpeg, monitors, save_and_show, ScreenshotResult};
+
+// 遮罩窗口 label 前缀（匹配 capabilities 中的 screenshot_*）
+const OVERLAY_WINDOW_PREFIX: &str = "screenshot_region_";
+// 遮罩中显示的冻结画面质量
+const OVERLAY_QUALITY: u8 = 85;
+
+// 选区，坐标均为物理像素
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionSelection {
+    pub monitor: usize,
+    pub x: u32, // 相对于显示器左上角
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub desktop_x: i32, // 在整个桌面上的位置
+    pub desktop_y: i32,
+}
+
+// 遮罩窗口提交的选区，坐标为遮罩内的 CSS 像素
+#[derive(Debug, Clone, Deserialize)]
+pub struct OverlayRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub viewport_width: f64,
+    pub viewport_height: f64,
+}
+
+// 冻结的单个显示器画面
+struct FrozenFrame {
+    image: Arc<RgbaImage>,
+    origin: (i32, i32), // 显示器左上角的物理坐标
+    store_id: String,
+}
+
+struct FrozenDesktop {
+    frames: Vec<FrozenFrame>,
+    previews: Vec<StoredImage>,
+    monitors: Vec<monitors::MonitorInfo>,
+}
+
+struct RegionSession {
+    id: u64,
+    frames: Vec<FrozenFrame>,
+    sender: Sender<Option<RegionSelection>>,
+}
+
+pub struct RegionSelector {
+    session: Mutex<Option<RegionSession>>,
+    counter: Mutex<u64>,
+}
+
+impl RegionSelector {
+    pub fn new() -> Self {
+        Self {
+            session: Mutex::new(None),
+            counter: Mutex::new(0),
+        }
+    }
+
+    fn next_id(&self) -> u64 {
+        let mut counter = self.counter.lock().unwrap();
+        *counter += 1;
+        *counter
+    }
+
+    // 结束当前会话并把结果发给等待中的截图任务（None 表示取消）
+    fn complete(&self, session_id: Option<u64>, selection: Option<RegionSelection>) -> bool {
+        let mut session = self.session.lock().unwrap();
+        match session.as_ref() {
+            Some(current) if session_id.is_none_or(|id| id == current.id) => {
+                let current = session.take().unwrap();
+                let _ = current.sender.send(selection);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.session.lock().unwrap().is_some()
+    }
+}
+
+fn overlay_label(monitor: usize) -> String {
+    format!("{}{}", OVERLAY_WINDOW_PREFIX, monitor)
+}
+
+// 冻结所有显示器的画面（阻塞）
+fn freeze_screens(session_id: u64, timings: &mut CaptureTimings) -> Result<FrozenDesktop, String> {
+    let start = Instant::now();
+    let screens = monitors::list_screens()?;
+    timings.list_screens_ms = elapsed_ms(start);
+
+    let start = Instant::now();
+    let images = monitors::capture_all_screens(&screens)?;
+    timings.capture_ms = elapsed_ms(start);
+
+    // 遮罩窗口通过 screenshot:// 协议加载冻结画面
+    let start = Instant::now();
+    let previews = std::thread::scope(|scope| {
+        let workers: Vec<_> = images
+            .iter()
+            .map(|image| scope.spawn(move || encode_jpeg(image, OVERLAY_QUALITY)))
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().map_err(|_| "Encoder panicked".to_string())?)
+            .collect::<Result<Vec<_>, String>>()
+    })?;
+    timings.preview_encode_ms = elapsed_ms(start);
+
+    let mut frames = Vec::new();
+    let mut stored = Vec::new();
+    for (index, (image, preview)) in images.into_iter().zip(previews).enumerate() {
+        stored.push(StoredImage::new(preview, image.width(), image.height(), None));
+        frames.push(FrozenFrame {
+            origin: monitors::physical_origin(&screens[index]),
+            image: Arc::new(image),
+            store_id: format!("region_{}_{}", session_id, index),
+        });
+    }
+
+    Ok(FrozenDesktop {
+        frames,
+        previews: stored,
+        monitors: monitors::monitor_infos(&screens),
+    })
+}
+
+fn open_overlay(
+    app: &AppHandle,
+    session_id: u64,
+    monitor: &monitors::MonitorInfo,
+    store_id: &str,
+    origin: (i32, i32),
+    size: (u32, u32),
+) -> Result<(), String> {
+    let label = overlay_label(monitor.index);
+    if let Some(window) = app.get_webview_window(&label) {
+        let _ = window.destroy();
+    }
+
+    let url = format!(
+        "/screenshot-region?session={}&monitor={}&frame={}",
+        session_id, monitor.index, store_id
+    );
+    let window = WebviewWindowBuilder::new(app, &label, tauri::WebviewUrl::App(url.into()))
+        .title("区域截图")
+        .visible(false)
+        .resizable(false)
+        .decorations(false)
+        .shadow(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .focused(monitor.is_primary)
+        .build()
+        .map_err(|e| format!("Failed to create region overlay: {}", e))?;
+    // 遮罩覆盖整个显示器，按冻结画面的物理像素尺寸放置
+    monitors::place_window(&window, origin, size)?;
+    if monitor.is_primary {
+        let _ = window.set_focus();
+    }
+
+    // 遮罩被意外关闭（如 Alt+F4）时取消本次选择
+    let app_handle = app.clone();
+    window.on_window_event(move |event| {
+        if let tauri::WindowEvent::Destroyed = event {
+            app_handle.state::<RegionSelector>().complete(Some(session_id), None);
+        }
+    });
+
+    Ok(())
+}
+
+fn close_overlays(app: &AppHandle, monitors: usize) {
+    for index in 0..monitors {
+        if let Some(window) = app.get_webview_window(&overlay_label(index)) {
+            let _ = window.destroy();
+        }
+    }
+}
+
+// 冻结画面、打开遮罩并等待用户框选，返回裁剪后的图片；用户取消时返回错误
+pub async fn select_region(app: &AppHandle) -> Result<(RgbaImage, RegionSelection, CaptureTimings), String> {
+    let selector = app.state::<RegionSelector>();
+    // 上一次选择还没结束时先取消
+    selector.complete(None, None);
+    let session_id = selector.next_id();
+
+    let (desktop, mut timings) = pipeline::run_blocking(move || {
+        let mut timings = CaptureTimings::default();
+        let desktop = freeze_screens(session_id, &mut timings)?;
+        Ok((desktop, timings))
+    })
+    .await?;
+    let FrozenDesktop { frames, previews, monitors: infos } = desktop;
+
+    let store = app.state::<ScreenshotStore>();
+    for (frame, image) in frames.iter().zip(previews) {
+        store.insert(frame.store_id.clone(), image);
+    }
+    let store_ids: Vec<String> = frames.iter().map(|frame| frame.store_id.clone()).collect();
+    let placements: Vec<((i32, i32), (u32, u32))> =
+        frames.iter().map(|frame| (frame.origin, frame.image.dimensions())).collect();
+    let images: Vec<Arc<RgbaImage>> = frames.iter().map(|frame| frame.image.clone()).collect();
+
+    let (sender, receiver) = mpsc::channel::<Option<RegionSelection>>();
+    *selector.session.lock().unwrap() = Some(RegionSession { id: session_id, frames, sender });
+
+    let start = Instant::now();
+    let opened = infos
+        .iter()
+        .zip(store_ids.iter().zip(&placements))
+        .map(|(info, (store_id, &(origin, size)))| open_overlay(app, session_id, info, store_id, origin, size))
+        .collect::<Result<Vec<_>, String>>();
+
+    let selection = match opened {
+        Ok(_) => pipeline::run_blocking(move || Ok(receiver.recv().ok().flatten())).await,
+        Err(e) => {
+            selector.complete(Some(session_id), None);
+            Err(e)
+        }
+    };
+    timings.window_ms = Some(elapsed_ms(start));
+
+    close_overlays(app, infos.len());
+    for store_id in &store_ids {
+        store.remove(store_id);
+    }
+
+    let selection = selection?.ok_or("Region selection cancelled")?;
+
+    let start = Instant::now();
+    let frame = &images[selection.monitor];
+    let image = imageops::crop_imm(frame.as_ref(), selection.x, selection.y, selection.width, selection.height)
+        .to_image();
+    timings.crop_ms = Some(elapsed_ms(start));
+
+    Ok((image, selection, timings))
+}
+
+// 遮罩窗口提交选区，按冻结画面与视口的比例换算为物理像素
+#[command]
+pub fn submit_region_selection(
+    selector: State<RegionSelector>,
+    session: u64,
+    monitor: usize,
+    rect: OverlayRect,
+) -> Result<(), String> {
+    let OverlayRect { x, y, width, height, viewport_width, viewport_height } = rect;
+    let selection = {
+        let guard = selector.session.lock().unwrap();
+        let current = guard
+            .as_ref()
+            .filter(|current| current.id == session)
+            .ok_or("Region selection is no longer active")?;
+        let frame = current.frames.get(monitor).ok_or_else(|| format!("Monitor {} not found", monitor))?;
+
+        let (frame_width, frame_height) = (frame.image.width(), frame.image.height());
+        let scale_x = frame_width as f64 / viewport_width.max(1.0);
+        let scale_y = frame_height as f64 / viewport_height.max(1.0);
+
+        let left = ((x * scale_x).round().max(0.0) as u32).min(frame_width.saturating_sub(1));
+        let top = ((y * scale_y).round().max(0.0) as u32).min(frame_height.saturating_sub(1));
+        let right = ((x + width) * scale_x).round().clamp(0.0, frame_width as f64) as u32;
+        let bottom = ((y + height) * scale_y).round().clamp(0.0, frame_height as f64) as u32;
+        if right <= left || bottom <= top {
+            return Err("Selection is empty".to_string());
+        }
+
+        RegionSelection {
+            monitor,
+            x: left,
+            y: top,
+            width: right - left,
+            height: bottom - top,
+            desktop_x: frame.origin.0 + left as i32,
+            desktop_y: frame.origin.1 + top as i32,
+        }
+    };
+
+    selector.complete(Some(session), Some(selection));
+    Ok(())
+}
+
+// 区域截图：框选完成后保存并打开贴图窗口
+#[command]
+pub async fn region_capture_and_show(app: AppHandle) -> Result<ScreenshotResult, String> {
+    let total_start = Instant::now();
+    let mut metadata = capture_metadata(&app, Some("region"), None);
+    let (image, selection, timings) = select_region(&app).await?;
+    metadata.monitor = Some(selection.monitor.to_string());
+    let mut result = save_and_show(&app, image, timings, total_start, metadata).await?;
+    result.region = Some(selection);
+    Ok(result)
+}
+
+// 取消区域选择（遮罩中按 ESC）
+#[command]
+pub fn cancel_region_selection(selector: State<RegionSelector>) -> Result<bool, String> {
+    Ok(selector.complete(None, None))
+}
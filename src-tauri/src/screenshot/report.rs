@@ -0,0 +1,338 @@
+// 截图报告：把选中的截图（按 id 或时间范围）连同截图时间、应用和窗口信息、说明文字导出为
+// 分页的 PDF（每页一张截图）或自包含的 HTML 文件（图片以 data URI 内嵌），用于提交问题或审计
+// 截图上下文优先读取文件中的元数据（save_screenshot_to_file 写入），没有时使用 screenshots 表的记录时间
+pub mod pdf;
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use base64::{engine::general_purpose, Engine};
+use image::imageops;
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle, Manager};
+
+use super::library::{list_screenshots, open_db, ScreenshotRecord};
+use super::metadata::{self, CaptureMetadata};
+use super::{encode_jpeg, path_to_string, pipeline, screenshots_dir};
+use pdf::{PdfImage, PdfPage, PAGE_HEIGHT, PAGE_WIDTH};
+
+const REPORTS_DIR: &str = "reports";
+// 报告中的图片缩小到该宽度以内并重新编码为 JPEG，控制文件大小
+const MAX_IMAGE_WIDTH: u32 = 1600;
+const IMAGE_QUALITY: u8 = 85;
+
+const MARGIN: f32 = 40.0;
+const TITLE_SIZE: f32 = 14.0;
+const TEXT_SIZE: f32 = 10.0;
+const CAPTION_SIZE: f32 = 11.0;
+const LINE_SPACING: f32 = 1.4;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Pdf,
+    Html,
+}
+
+impl ReportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ReportFormat::Pdf => "pdf",
+            ReportFormat::Html => "html",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReportConfig {
+    #[serde(default)]
+    pub ids: Vec<i64>,      // 按给定顺序导出，为空时使用时间范围
+    pub start: Option<i64>, // 毫秒时间戳，含两端，按记录时间升序导出
+    pub end: Option<i64>,
+    #[serde(default)]
+    pub format: ReportFormat,
+    pub title: Option<String>,
+    #[serde(default)]
+    pub captions: HashMap<i64, String>, // 截图 id 到说明文字
+    pub output_path: Option<String>,    // 为空时保存到 screenshots/reports
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportResult {
+    pub file_path: String,
+    pub format: ReportFormat,
+    pub screenshots: u32,
+    pub skipped: Vec<i64>, // 记录不存在、文件缺失或无法解码的截图
+    pub size_bytes: u64,
+}
+
+// 报告中的一张截图
+struct ReportEntry {
+    id: i64,
+    image: PdfImage, // 缩小后的 JPEG
+    details: Vec<String>,
+    caption: Option<String>,
+}
+
+// 按 id 或时间范围选出截图记录
+fn select_records(records: Vec<ScreenshotRecord>, config: &ReportConfig) -> Result<(Vec<ScreenshotRecord>, Vec<i64>), String> {
+    if !config.ids.is_empty() {
+        let mut selected = Vec::new();
+        let mut missing = Vec::new();
+        for &id in &config.ids {
+            match records.iter().find(|record| record.id == id) {
+                Some(record) => selected.push(record.clone()),
+                None => missing.push(id),
+            }
+        }
+        return Ok((selected, missing));
+    }
+
+    let (Some(start), Some(end)) = (config.start, config.end) else {
+        return Err("Either screenshot ids or a time range is required".to_string());
+    };
+    let mut selected: Vec<(i64, ScreenshotRecord)> = records
+        .into_iter()
+        .filter_map(|record| Some((record_time(&record)?.timestamp_millis(), record)))
+        .filter(|(timestamp, _)| (start..=end).contains(timestamp))
+        .collect();
+    selected.sort_by_key(|(timestamp, record)| (*timestamp, record.id));
+    Ok((selected.into_iter().map(|(_, record)| record).collect(), Vec::new()))
+}
+
+// screenshots 表的 created_at 为 SQLite CURRENT_TIMESTAMP（UTC）
+fn record_time(record: &ScreenshotRecord) -> Option<chrono::DateTime<chrono::Local>> {
+    let naive = chrono::NaiveDateTime::parse_from_str(&record.created_at, "%Y-%m-%d %H:%M:%S").ok()?;
+    Some(naive.and_utc().with_timezone(&chrono::Local))
+}
+
+fn format_time(time: chrono::DateTime<chrono::FixedOffset>) -> String {
+    time.format("%Y-%m-%d %H:%M:%S (UTC%:z)").to_string()
+}
+
+// 截图时间、应用与窗口、显示器与模式、尺寸和文件名
+fn entry_details(record: &ScreenshotRecord, capture: &CaptureMetadata, width: u32, height: u32) -> Vec<String> {
+    let mut details = Vec::new();
+    let time = capture
+        .captured_at
+        .as_deref()
+        .and_then(|time| chrono::DateTime::parse_from_rfc3339(time).ok())
+        .or_else(|| record_time(record).map(|time| time.fixed_offset()));
+    details.push(format!("时间: {}", time.map(format_time).unwrap_or_else(|| record.created_at.clone())));
+
+    match (&capture.app_name, &capture.window_title) {
+        (Some(app), Some(title)) => details.push(format!("应用: {} — {}", app, title)),
+        (Some(app), None) => details.push(format!("应用: {}", app)),
+        (None, Some(title)) => details.push(format!("窗口: {}", title)),
+        (None, None) => {}
+    }
+
+    let mut source = Vec::new();
+    if let Some(monitor) = &capture.monitor {
+        source.push(format!("显示器 {}", monitor));
+    }
+    if let Some(mode) = &capture.mode {
+        source.push(format!("模式 {}", mode));
+    }
+    source.push(format!("{} × {}", width, height));
+    details.push(source.join(" · "));
+
+    let file_name = Path::new(&record.file_path).file_name().map(|name| name.to_string_lossy().into_owned());
+    details.push(format!("文件: {}", file_name.unwrap_or_else(|| record.file_path.clone())));
+    details
+}
+
+// 读取截图文件和元数据，图片缩小后重新编码（阻塞）
+fn load_entry(record: &ScreenshotRecord, caption: Option<String>) -> Result<ReportEntry, String> {
+    let bytes = fs::read(&record.file_path).map_err(|e| format!("Failed to read {}: {}", record.file_path, e))?;
+    let capture = metadata::read(&bytes).map(|metadata| metadata.capture).unwrap_or_default();
+    let image = image::load_from_memory(&bytes)
+        .map_err(|e| format!("Failed to decode {}: {}", record.file_path, e))?
+        .to_rgba8();
+    let (width, height) = image.dimensions();
+    let details = entry_details(record, &capture, width, height);
+
+    let image = if width > MAX_IMAGE_WIDTH {
+        let scaled_height = (height as u64 * MAX_IMAGE_WIDTH as u64 / width as u64).max(1) as u32;
+        imageops::resize(&image, MAX_IMAGE_WIDTH, scaled_height, imageops::FilterType::Triangle)
+    } else {
+        image
+    };
+    let jpeg = encode_jpeg(&image, IMAGE_QUALITY)?;
+
+    Ok(ReportEntry {
+        id: record.id,
+        image: PdfImage { jpeg, width: image.width(), height: image.height() },
+        details,
+        caption: caption.map(|caption| caption.trim().to_string()).filter(|caption| !caption.is_empty()),
+    })
+}
+
+// 每页一张截图：页眉为标题和页码，其下为截图信息和说明，图片占满剩余空间（不放大）
+fn pdf_page(entry: ReportEntry, title: &str, number: usize, total: usize) -> PdfPage {
+    let mut page = PdfPage::default();
+    let content_width = PAGE_WIDTH - MARGIN * 2.0;
+    let mut y = PAGE_HEIGHT - MARGIN - TITLE_SIZE;
+
+    // 标题过长时截断，给页码留出位置
+    let page_label = format!("{} / {}", number, total);
+    let title_width = content_width - pdf::text_width(&page_label, TEXT_SIZE) - TEXT_SIZE;
+    let title = pdf::wrap_text(title, TITLE_SIZE, title_width).into_iter().next().unwrap_or_default();
+    page.text(MARGIN, y, TITLE_SIZE, 0.0, &title);
+    page.text(PAGE_WIDTH - MARGIN - pdf::text_width(&page_label, TEXT_SIZE), y, TEXT_SIZE, 0.4, &page_label);
+    y -= TITLE_SIZE * 0.6;
+    page.line(MARGIN, y, PAGE_WIDTH - MARGIN, y, 0.7);
+    y -= TEXT_SIZE * LINE_SPACING;
+
+    for detail in &entry.details {
+        for line in pdf::wrap_text(detail, TEXT_SIZE, content_width) {
+            page.text(MARGIN, y, TEXT_SIZE, 0.3, &line);
+            y -= TEXT_SIZE * LINE_SPACING;
+        }
+    }
+    if let Some(caption) = &entry.caption {
+        y -= TEXT_SIZE * 0.4;
+        for line in pdf::wrap_text(caption, CAPTION_SIZE, content_width) {
+            page.text(MARGIN, y, CAPTION_SIZE, 0.0, &line);
+            y -= CAPTION_SIZE * LINE_SPACING;
+        }
+    }
+
+    // 图片按 1 像素 = 1 pt 显示，超出可用区域时等比缩小
+    // y 为下一行文本的基线，图片顶部与上一行留出半行间距
+    let top = y + TEXT_SIZE * 0.5;
+    let available_height = (top - MARGIN).max(1.0);
+    let (width, height) = (entry.image.width as f32, entry.image.height as f32);
+    let scale = (content_width / width).min(available_height / height).min(1.0);
+    let (draw_width, draw_height) = (width * scale, height * scale);
+    page.image(entry.image, MARGIN + (content_width - draw_width) / 2.0, top - draw_height, draw_width, draw_height);
+    page
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const HTML_STYLE: &str = "body{font-family:-apple-system,'Segoe UI','Microsoft YaHei','PingFang SC',sans-serif;max-width:1000px;margin:40px auto;padding:0 20px;color:#222}\
+h1{font-size:22px;margin-bottom:4px}.summary{color:#888;font-size:13px;margin-top:0}\
+section{margin:32px 0;padding-top:16px;border-top:1px solid #ddd}h2{font-size:15px;margin:0 0 8px}\
+ul{list-style:none;padding:0;margin:0 0 8px;color:#555;font-size:13px;line-height:1.6}\
+.caption{font-size:14px;white-space:pre-wrap;background:#f5f7fa;border-left:3px solid #667eea;padding:8px 12px}\
+img{max-width:100%;height:auto;border:1px solid #eee}\
+@media print{body{margin:0}section{break-inside:avoid;page-break-inside:avoid}}";
+
+fn html_section(html: &mut String, entry: &ReportEntry, number: usize) {
+    let _ = write!(html, "<section><h2>#{}</h2><ul>", number);
+    for detail in &entry.details {
+        let _ = write!(html, "<li>{}</li>", html_escape(detail));
+    }
+    html.push_str("</ul>");
+    if let Some(caption) = &entry.caption {
+        let _ = write!(html, "<p class=\"caption\">{}</p>", html_escape(caption));
+    }
+    let _ = writeln!(
+        html,
+        "<img src=\"data:image/jpeg;base64,{}\" width=\"{}\" height=\"{}\" alt=\"screenshot {}\"></section>",
+        general_purpose::STANDARD.encode(&entry.image.jpeg),
+        entry.image.width,
+        entry.image.height,
+        entry.id
+    );
+}
+
+fn output_path(app: &AppHandle, config: &ReportConfig) -> Result<PathBuf, String> {
+    if let Some(path) = config.output_path.as_deref().filter(|path| !path.trim().is_empty()) {
+        return Ok(PathBuf::from(path));
+    }
+    let dir = screenshots_dir(app)?.join(REPORTS_DIR);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create reports directory: {}", e))?;
+    Ok(dir.join(format!(
+        "report_{}.{}",
+        chrono::Local::now().format("%Y%m%d_%H%M%S"),
+        config.format.extension()
+    )))
+}
+
+fn build_report(app: &AppHandle, config: &ReportConfig) -> Result<ReportResult, String> {
+    let conn = open_db(app)?;
+    let (records, mut skipped) = select_records(list_screenshots(&conn)?, config)?;
+    drop(conn);
+
+    let now = chrono::Local::now();
+    let title = config
+        .title
+        .clone()
+        .filter(|title| !title.trim().is_empty())
+        .unwrap_or_else(|| format!("截图报告 {}", now.format("%Y-%m-%d %H:%M")));
+
+    // 图片已缩小并压缩为 JPEG，全部读取后再排版
+    let mut entries = Vec::new();
+    for record in &records {
+        match load_entry(record, config.captions.get(&record.id).cloned()) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => {
+                eprintln!("报告跳过截图 {}: {}", record.id, e);
+                skipped.push(record.id);
+            }
+        }
+    }
+    if entries.is_empty() {
+        return Err("No screenshots available for the report".to_string());
+    }
+
+    let count = entries.len();
+    let package = app.package_info();
+    let bytes = match config.format {
+        ReportFormat::Pdf => {
+            let pages = entries
+                .into_iter()
+                .enumerate()
+                .map(|(index, entry)| pdf_page(entry, &title, index + 1, count))
+                .collect();
+            let offset = now.format("%:z").to_string().replace(':', "'");
+            let creation_date = format!("D:{}{}'", now.format("%Y%m%d%H%M%S"), offset);
+            pdf::write(&title, &format!("{} {}", package.name, package.version), &creation_date, pages)
+        }
+        ReportFormat::Html => {
+            let mut html = format!(
+                "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n\
+                 <meta name=\"generator\" content=\"{1} {2}\">\n<style>{3}</style>\n</head>\n<body>\n<h1>{0}</h1>\n\
+                 <p class=\"summary\">生成于 {4} · {5} 张截图</p>\n",
+                html_escape(&title),
+                html_escape(&package.name),
+                package.version,
+                HTML_STYLE,
+                format_time(now.fixed_offset()),
+                count
+            );
+            for (index, entry) in entries.iter().enumerate() {
+                html_section(&mut html, entry, index + 1);
+            }
+            html.push_str("</body>\n</html>\n");
+            html.into_bytes()
+        }
+    };
+
+    let path = output_path(app, config)?;
+    fs::write(&path, &bytes).map_err(|e| format!("Failed to write report {}: {}", path_to_string(&path), e))?;
+    let file_path = path_to_string(&path);
+
+    Ok(ReportResult {
+        file_path,
+        format: config.format,
+        screenshots: count as u32,
+        skipped,
+        size_bytes: bytes.len() as u64,
+    })
+}
+
+// 导出截图报告（PDF 或 HTML），返回文件路径
+#[command]
+pub async fn export_screenshot_report(app: AppHandle, config: ReportConfig) -> Result<ReportResult, String> {
+    pipeline::run_blocking(move || build_report(&app, &config)).await
+}
@@ -0,0 +1,369 @@
+// 最小的 PDF 生成：A4 页面，绘制文本、分隔线和 JPEG 图片（DCTDecode，直接写入 JPEG 数据）
+// 文本使用 Adobe 预定义的中文字体 STSong-Light（UniGB-UCS2-H 编码），不嵌入字体文件，
+// 由阅读器替换为本机字体（Acrobat、浏览器、poppler 均支持）
+use std::fmt::Write as _;
+
+pub const PAGE_WIDTH: f32 = 595.0;
+pub const PAGE_HEIGHT: f32 = 842.0;
+
+// 固定的对象编号，页面对象从 FIRST_PAGE_OBJECT 开始
+const CATALOG_OBJECT: usize = 1;
+const PAGES_OBJECT: usize = 2;
+const INFO_OBJECT: usize = 3;
+const FONT_OBJECT: usize = 4;
+const CID_FONT_OBJECT: usize = 5;
+const FONT_DESCRIPTOR_OBJECT: usize = 6;
+const FIRST_PAGE_OBJECT: usize = 7;
+
+// 已编码的 RGB JPEG
+pub struct PdfImage {
+    pub jpeg: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Default)]
+pub struct PdfPage {
+    content: String,
+    images: Vec<PdfImage>,
+}
+
+impl PdfPage {
+    // 在 (x, y) 处绘制一行文本，y 为基线到页面底部的距离，gray 为 0（黑）到 1（白）
+    pub fn text(&mut self, x: f32, y: f32, size: f32, gray: f32, text: &str) {
+        let _ = writeln!(
+            self.content,
+            "BT {:.2} g /F1 {:.2} Tf {:.2} {:.2} Td <{}> Tj ET",
+            gray,
+            size,
+            x,
+            y,
+            encode_text(text)
+        );
+    }
+
+    pub fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, gray: f32) {
+        let _ = writeln!(
+            self.content,
+            "{:.2} G 0.5 w {:.2} {:.2} m {:.2} {:.2} l S",
+            gray, x1, y1, x2, y2
+        );
+    }
+
+    // 把图片绘制到 (x, y) 为左下角、宽高为 width × height 的区域
+    pub fn image(&mut self, image: PdfImage, x: f32, y: f32, width: f32, height: f32) {
+        let _ = writeln!(
+            self.content,
+            "q {:.2} 0 0 {:.2} {:.2} {:.2} cm /Im{} Do Q",
+            width,
+            height,
+            x,
+            y,
+            self.images.len()
+        );
+        self.images.push(image);
+    }
+}
+
+// ASCII 字符按半角（0.5 em）计算，其余按全角计算，与字体的 /W 宽度一致
+pub fn text_width(text: &str, size: f32) -> f32 {
+    text.chars().map(|c| if c.is_ascii() { 0.5 } else { 1.0 }).sum::<f32>() * size
+}
+
+// 按宽度折行，英文优先在空格处断开，已有的换行符保留
+pub fn wrap_text(text: &str, size: f32, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for c in paragraph.chars() {
+            line.push(c);
+            if text_width(&line, size) <= max_width || line.chars().count() == 1 {
+                continue;
+            }
+            line.pop();
+            let break_at = line.rfind(' ').filter(|&index| index > 0 && c.is_ascii_graphic());
+            match break_at {
+                Some(index) => {
+                    let rest = line.split_off(index);
+                    lines.push(line);
+                    line = rest.trim_start().to_string();
+                }
+                None => lines.push(std::mem::take(&mut line)),
+            }
+            line.push(c);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+// UniGB-UCS2-H 编码为 UTF-16BE（不含 BOM），只支持基本多文种平面
+fn encode_text(text: &str) -> String {
+    let mut hex = String::with_capacity(text.len() * 4);
+    for c in text.chars() {
+        let code = if c.is_control() {
+            ' ' as u32
+        } else if c as u32 > 0xFFFF {
+            '?' as u32
+        } else {
+            c as u32
+        };
+        let _ = write!(hex, "{:04X}", code);
+    }
+    hex
+}
+
+// 文档信息中的文本（UTF-16BE，带 BOM）
+fn info_string(text: &str) -> String {
+    let mut hex = String::from("<FEFF");
+    for unit in text.encode_utf16() {
+        let _ = write!(hex, "{:04X}", unit);
+    }
+    hex.push('>');
+    hex
+}
+
+struct Writer {
+    output: Vec<u8>,
+    offsets: Vec<usize>, // 下标为对象编号
+}
+
+impl Writer {
+    fn object(&mut self, number: usize, body: &str) {
+        self.begin(number);
+        self.output.extend_from_slice(body.as_bytes());
+        self.output.extend_from_slice(b"\nendobj\n");
+    }
+
+    fn stream(&mut self, number: usize, dictionary: &str, data: &[u8]) {
+        self.begin(number);
+        let header = format!("<< {} /Length {} >>\nstream\n", dictionary, data.len());
+        self.output.extend_from_slice(header.as_bytes());
+        self.output.extend_from_slice(data);
+        self.output.extend_from_slice(b"\nendstream\nendobj\n");
+    }
+
+    fn begin(&mut self, number: usize) {
+        if self.offsets.len() <= number {
+            self.offsets.resize(number + 1, 0);
+        }
+        self.offsets[number] = self.output.len();
+        self.output.extend_from_slice(format!("{} 0 obj\n", number).as_bytes());
+    }
+}
+
+// 生成 PDF 文件内容，creation_date 为 PDF 日期格式（如 D:20261018093015+08'00'）
+pub fn write(title: &str, producer: &str, creation_date: &str, pages: Vec<PdfPage>) -> Vec<u8> {
+    let mut writer = Writer { output: b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec(), offsets: vec![0] };
+
+    // 每页依次为页面、内容流、图片
+    let mut page_objects = Vec::with_capacity(pages.len());
+    let mut next = FIRST_PAGE_OBJECT;
+    for page in &pages {
+        page_objects.push(next);
+        next += 2 + page.images.len();
+    }
+
+    writer.object(CATALOG_OBJECT, &format!("<< /Type /Catalog /Pages {} 0 R >>", PAGES_OBJECT));
+    let kids: Vec<String> = page_objects.iter().map(|number| format!("{} 0 R", number)).collect();
+    writer.object(
+        PAGES_OBJECT,
+        &format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()),
+    );
+    writer.object(
+        INFO_OBJECT,
+        &format!(
+            "<< /Title {} /Producer {} /CreationDate ({}) >>",
+            info_string(title),
+            info_string(producer),
+            creation_date
+        ),
+    );
+    writer.object(
+        FONT_OBJECT,
+        &format!(
+            "<< /Type /Font /Subtype /Type0 /BaseFont /STSong-Light /Encoding /UniGB-UCS2-H /DescendantFonts [{} 0 R] >>",
+            CID_FONT_OBJECT
+        ),
+    );
+    // CID 1-95 为 ASCII 字符，按半角宽度显示
+    writer.object(
+        CID_FONT_OBJECT,
+        &format!(
+            "<< /Type /Font /Subtype /CIDFontType0 /BaseFont /STSong-Light \
+             /CIDSystemInfo << /Registry (Adobe) /Ordering (GB1) /Supplement 2 >> \
+             /FontDescriptor {} 0 R /DW 1000 /W [1 95 500] >>",
+            FONT_DESCRIPTOR_OBJECT
+        ),
+    );
+    writer.object(
+        FONT_DESCRIPTOR_OBJECT,
+        "<< /Type /FontDescriptor /FontName /STSong-Light /Flags 6 /FontBBox [-25 -254 1000 880] \
+         /ItalicAngle 0 /Ascent 880 /Descent -120 /CapHeight 880 /StemV 93 >>",
+    );
+
+    for (page, &number) in pages.into_iter().zip(&page_objects) {
+        let content_object = number + 1;
+        let images: Vec<String> = (0..page.images.len())
+            .map(|index| format!("/Im{} {} 0 R", index, content_object + 1 + index))
+            .collect();
+        writer.object(
+            number,
+            &format!(
+                "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {} {}] /Contents {} 0 R \
+                 /Resources << /Font << /F1 {} 0 R >> /XObject << {} >> >> >>",
+                PAGES_OBJECT,
+                PAGE_WIDTH,
+                PAGE_HEIGHT,
+                content_object,
+                FONT_OBJECT,
+                images.join(" ")
+            ),
+        );
+        writer.stream(content_object, "", page.content.as_bytes());
+        for (index, image) in page.images.iter().enumerate() {
+            writer.stream(
+                content_object + 1 + index,
+                &format!(
+                    "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB \
+                     /BitsPerComponent 8 /Filter /DCTDecode",
+                    image.width, image.height
+                ),
+                &image.jpeg,
+            );
+        }
+    }
+
+    // 交叉引用表，每行固定 20 字节
+    let xref_offset = writer.output.len();
+    let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", writer.offsets.len());
+    for offset in &writer.offsets[1..] {
+        let _ = writeln!(xref, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(
+        xref,
+        "trailer\n<< /Size {} /Root {} 0 R /Info {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+        writer.offsets.len(),
+        CATALOG_OBJECT,
+        INFO_OBJECT,
+        xref_offset
+    );
+    writer.output.extend_from_slice(xref.as_bytes());
+    writer.output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        haystack.windows(needle.len()).rposition(|window| window == needle)
+    }
+
+    fn sample_pdf() -> Vec<u8> {
+        let mut first = PdfPage::default();
+        first.text(40.0, 800.0, 14.0, 0.0, "截图报告 Screenshot report");
+        first.line(40.0, 790.0, 555.0, 790.0, 0.7);
+        // 图片数据原样写入流中，包含 endstream 之类的字节也不影响偏移
+        first.image(PdfImage { jpeg: b"\xFF\xD8 endobj\n\x00\xFF\xD9".to_vec(), width: 2, height: 1 }, 40.0, 400.0, 200.0, 100.0);
+        first.image(PdfImage { jpeg: vec![0xAB; 300], width: 10, height: 30 }, 40.0, 100.0, 100.0, 300.0);
+        let mut second = PdfPage::default();
+        second.text(40.0, 800.0, 11.0, 0.4, "第二页");
+        write("报告", "tauri-app 0.1.0", "D:20261018093015+08'00'", vec![first, second])
+    }
+
+    #[test]
+    fn xref_offsets_point_at_objects() {
+        let pdf = sample_pdf();
+        assert!(pdf.starts_with(b"%PDF-1.4\n"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+
+        let startxref = find(&pdf, b"startxref\n").unwrap() + b"startxref\n".len();
+        let tail = std::str::from_utf8(&pdf[startxref..]).unwrap();
+        let xref_offset: usize = tail.lines().next().unwrap().parse().unwrap();
+        let xref = std::str::from_utf8(&pdf[xref_offset..]).unwrap();
+        let mut lines = xref.lines();
+        assert_eq!(lines.next(), Some("xref"));
+
+        // 固定对象 6 个，第一页为页面、内容和两张图片，第二页为页面和内容
+        let count: usize = lines.next().unwrap().strip_prefix("0 ").unwrap().parse().unwrap();
+        assert_eq!(count, 1 + 6 + 4 + 2);
+        assert!(xref.contains(&format!("/Size {} ", count)));
+
+        let entries = &xref["xref\n".len() + format!("0 {}\n", count).len()..];
+        for (number, entry) in entries.as_bytes().chunks(20).take(count).enumerate() {
+            let entry = std::str::from_utf8(entry).unwrap();
+            assert_eq!(entry.len(), 20);
+            if number == 0 {
+                assert_eq!(entry, "0000000000 65535 f \n");
+                continue;
+            }
+            assert!(entry.ends_with(" 00000 n \n"), "entry {}: {:?}", number, entry);
+            let offset: usize = entry[..10].parse().unwrap();
+            let header = format!("{} 0 obj\n", number);
+            assert!(pdf[offset..].starts_with(header.as_bytes()), "object {} not at {}", number, offset);
+        }
+    }
+
+    #[test]
+    fn stream_lengths_match_data() {
+        let pdf = sample_pdf();
+        let mut checked = 0;
+        let mut position = 0;
+        while let Some(index) = pdf[position..].windows(8).position(|window| window == b"/Length ") {
+            let rest = &pdf[position + index + 8..];
+            let digits = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+            let length: usize = std::str::from_utf8(&rest[..digits]).unwrap().parse().unwrap();
+            let data = &rest[digits..];
+            assert!(data.starts_with(b" >>\nstream\n"));
+            assert!(data[b" >>\nstream\n".len() + length..].starts_with(b"\nendstream\n"));
+            position += index + 8;
+            checked += 1;
+        }
+        assert_eq!(checked, 4);
+    }
+
+    #[test]
+    fn text_width_counts_ascii_as_half_width() {
+        assert_eq!(text_width("", 10.0), 0.0);
+        assert_eq!(text_width("abcd", 10.0), 20.0);
+        assert_eq!(text_width("截图", 10.0), 20.0);
+        assert_eq!(text_width("截图 ok", 12.0), 42.0);
+    }
+
+    #[test]
+    fn wrap_text_breaks_english_at_spaces() {
+        assert_eq!(wrap_text("hello world foo", 10.0, 40.0), ["hello", "world", "foo"]);
+        assert_eq!(wrap_text("截图abc def", 10.0, 40.0), ["截图abc", "def"]);
+    }
+
+    #[test]
+    fn wrap_text_breaks_cjk_anywhere() {
+        assert_eq!(wrap_text("截图报告测试", 10.0, 30.0), ["截图报", "告测试"]);
+        // 中文字符超宽时直接断开，不回退到前面的空格
+        assert_eq!(wrap_text("ab 中文字", 10.0, 30.0), ["ab 中", "文字"]);
+    }
+
+    #[test]
+    fn wrap_text_keeps_lines_within_width() {
+        let text = "说明：点击“保存”按钮后窗口没有响应，日志中出现 database is locked 错误。\n\
+                    This line is long enough to wrap at a space so that English words are not split.";
+        let lines = wrap_text(text, 11.0, 200.0);
+        assert!(lines.len() > 3);
+        for line in &lines {
+            assert!(text_width(line, 11.0) <= 200.0, "{:?} is too wide", line);
+        }
+        for word in ["database", "locked", "English", "split."] {
+            assert!(lines.iter().any(|line| line.split(' ').any(|part| part == word)), "{} was split", word);
+        }
+        let joined: String = lines.concat().split_whitespace().collect();
+        let original: String = text.split_whitespace().collect();
+        assert_eq!(joined, original);
+    }
+
+    #[test]
+    fn wrap_text_keeps_paragraphs_and_wide_characters() {
+        assert_eq!(wrap_text("a\nb", 10.0, 100.0), ["a", "b"]);
+        assert_eq!(wrap_text("中", 10.0, 5.0), ["中"]);
+    }
+}
@@ -0,0 +1,509 @@
+// 截图保留策略：定期清理过期截图、限制总占用空间，并保证 screenshots 表中没有指向缺失文件的记录
+// 清理时先移入回收站（screenshots/.trash），回收站中的文件超过保留天数后才会被真正删除
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle, Emitter, Manager, State};
+
+use super::library::{
+    delete_screenshot_row, ensure_column, move_to_trash, open_db, trash_dir, TRASH_DIR,
+};
+use super::timed::prune_index;
+use super::{path_to_string, pipeline, screenshots_dir};
+
+// 策略保存在 app_config_dir 下
+const POLICY_FILE: &str = "screenshot_retention.json";
+// 参与清理的图片扩展名（timed 目录下的 index.jsonl 不直接清理，随图片清理同步更新）
+const IMAGE_EXTENSIONS: [&str; 5] = ["jpg", "jpeg", "png", "gif", "webp"];
+const BYTES_PER_GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    pub enabled: bool,
+    pub max_age_days: Option<u32>,  // 超过天数的截图移入回收站
+    pub max_total_gb: Option<f64>,  // 总占用超过该值时从最旧的开始清理
+    pub keep_pinned: bool,          // 已固定的截图不参与清理
+    pub keep_tagged: bool,          // 有标签的截图不参与清理
+    pub trash_retention_days: u32,  // 回收站中文件保留天数
+    pub interval_minutes: u64,      // 后台检查间隔
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_age_days: Some(30),
+            max_total_gb: None,
+            keep_pinned: true,
+            keep_tagged: true,
+            trash_retention_days: 7,
+            interval_minutes: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct RetentionReport {
+    pub expired: usize,        // 因超过保留天数而移入回收站的文件数
+    pub over_quota: usize,     // 因超出空间限制而移入回收站的文件数
+    pub freed_bytes: u64,
+    pub total_bytes: u64,      // 清理后 screenshots 目录（不含回收站）的占用
+    pub reconciled_rows: usize, // 删除的指向缺失文件的记录数
+    pub purged_trash: usize,   // 从回收站永久删除的文件数
+    pub errors: Vec<String>,
+    pub finished_at: i64,      // 毫秒时间戳
+}
+
+// 截图目录中的一个图片文件
+struct StoredFile {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+    row_id: Option<i64>,
+    protected: bool,
+}
+
+pub struct RetentionService {
+    session: Arc<Mutex<u64>>,
+    policy: Arc<Mutex<RetentionPolicy>>,
+    last_report: Arc<Mutex<Option<RetentionReport>>>,
+    // 避免后台任务与手动触发同时执行
+    running: Arc<Mutex<()>>,
+}
+
+impl RetentionService {
+    pub fn new() -> Self {
+        Self {
+            session: Arc::new(Mutex::new(0)),
+            policy: Arc::new(Mutex::new(RetentionPolicy::default())),
+            last_report: Arc::new(Mutex::new(None)),
+            running: Arc::new(Mutex::new(())),
+        }
+    }
+
+    // 读取已保存的策略并启动后台检查
+    pub fn start(&self, app_handle: AppHandle) {
+        match load_policy(&app_handle) {
+            Ok(policy) => *self.policy.lock().unwrap() = policy,
+            Err(e) => eprintln!("读取截图保留策略失败: {}", e),
+        }
+        self.restart(app_handle);
+    }
+
+    fn restart(&self, app_handle: AppHandle) {
+        let session_id = {
+            let mut session = self.session.lock().unwrap();
+            *session += 1;
+            *session
+        };
+
+        let policy = self.policy.lock().unwrap().clone();
+        if !policy.enabled {
+            return;
+        }
+
+        let session = self.session.clone();
+        let last_report = self.last_report.clone();
+        let running = self.running.clone();
+
+        thread::spawn(move || {
+            let interval = Duration::from_secs(policy.interval_minutes.max(1) * 60);
+            let is_current = || *session.lock().unwrap() == session_id;
+
+            while is_current() {
+                let started = Instant::now();
+
+                let report = {
+                    let _guard = running.lock().unwrap();
+                    apply_policy(&app_handle, &policy)
+                };
+                match report {
+                    Ok(report) => {
+                        let _ = app_handle.emit("screenshot-retention", &report);
+                        *last_report.lock().unwrap() = Some(report);
+                    }
+                    Err(e) => eprintln!("截图清理失败: {}", e),
+                }
+
+                while is_current() && started.elapsed() < interval {
+                    thread::sleep(Duration::from_secs(1));
+                }
+            }
+        });
+    }
+
+    pub fn set_policy(&self, app_handle: AppHandle, policy: RetentionPolicy) -> Result<(), String> {
+        save_policy(&app_handle, &policy)?;
+        *self.policy.lock().unwrap() = policy;
+        self.restart(app_handle);
+        Ok(())
+    }
+
+    pub fn get_policy(&self) -> RetentionPolicy {
+        self.policy.lock().unwrap().clone()
+    }
+
+    pub fn run_now(&self, app_handle: &AppHandle) -> Result<RetentionReport, String> {
+        let policy = self.get_policy();
+        let report = {
+            let _guard = self.running.lock().unwrap();
+            apply_policy(app_handle, &policy)?
+        };
+        *self.last_report.lock().unwrap() = Some(report.clone());
+        Ok(report)
+    }
+
+    pub fn get_last_report(&self) -> Option<RetentionReport> {
+        self.last_report.lock().unwrap().clone()
+    }
+}
+
+fn policy_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let config_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Failed to get app config dir: {}", e))?;
+    fs::create_dir_all(&config_dir)
+        .map_err(|e| format!("Failed to create app config dir: {}", e))?;
+    Ok(config_dir.join(POLICY_FILE))
+}
+
+fn load_policy(app: &AppHandle) -> Result<RetentionPolicy, String> {
+    let path = policy_path(app)?;
+    if !path.exists() {
+        return Ok(RetentionPolicy::default());
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read retention policy: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse retention policy: {}", e))
+}
+
+fn save_policy(app: &AppHandle, policy: &RetentionPolicy) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(policy)
+        .map_err(|e| format!("Failed to serialize retention policy: {}", e))?;
+    fs::write(policy_path(app)?, content)
+        .map_err(|e| format!("Failed to write retention policy: {}", e))
+}
+
+// 为 screenshots 表补充固定与标签列
+pub fn ensure_retention_columns(conn: &Connection) -> Result<(), String> {
+    ensure_column(conn, "screenshots", "pinned", "INTEGER NOT NULL DEFAULT 0")?;
+    ensure_column(conn, "screenshots", "tags", "TEXT")
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+// 递归收集截图目录中的图片（跳过回收站）
+fn collect_files(dir: &Path, files: &mut Vec<(PathBuf, fs::Metadata)>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if metadata.is_dir() {
+            if entry.file_name() != TRASH_DIR {
+                collect_files(&path, files);
+            }
+        } else if is_image(&path) {
+            files.push((path, metadata));
+        }
+    }
+}
+
+// 删除空的子目录（例如已清空的定时截图日期目录）
+fn remove_empty_dirs(dir: &Path, is_root: bool) -> bool {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return false;
+    };
+    let mut empty = true;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() && entry.file_name() != TRASH_DIR {
+            if !remove_empty_dirs(&path, false) {
+                empty = false;
+            }
+        } else {
+            empty = false;
+        }
+    }
+    if empty && !is_root {
+        return fs::remove_dir(dir).is_ok();
+    }
+    empty
+}
+
+// 回收站文件名以移入时间开头（见 move_to_trash），据此判断是否超过保留天数
+fn purge_trash(app: &AppHandle, retention_days: u32) -> Result<usize, String> {
+    let cutoff = chrono::Local::now().naive_local() - chrono::Duration::days(retention_days as i64);
+    let mut purged = 0;
+
+    for entry in fs::read_dir(trash_dir(app)?).map_err(|e| format!("Failed to read trash: {}", e))?.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let trashed_at = name
+            .get(..17)
+            .and_then(|prefix| chrono::NaiveDateTime::parse_from_str(prefix, "%Y%m%d%H%M%S%3f").ok());
+
+        if matches!(trashed_at, Some(time) if time < cutoff) && fs::remove_file(entry.path()).is_ok() {
+            purged += 1;
+        }
+    }
+
+    Ok(purged)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CleanupReason {
+    Expired,
+    OverQuota,
+}
+
+// 选出需要清理的文件（files 已从旧到新排序），返回下标和原因：
+// 先选出超过保留天数的文件，剩余占用仍超出上限时再从最旧的开始清理，受保护的文件始终保留
+fn select_for_cleanup(
+    files: &[StoredFile],
+    age_cutoff: Option<SystemTime>,
+    max_bytes: Option<u64>,
+) -> Vec<(usize, CleanupReason)> {
+    let expired = |file: &StoredFile| !file.protected && matches!(age_cutoff, Some(cutoff) if file.modified < cutoff);
+    let mut total: u64 = files.iter().filter(|file| !expired(file)).map(|file| file.size).sum();
+
+    let mut selected = Vec::new();
+    for (index, file) in files.iter().enumerate() {
+        if expired(file) {
+            selected.push((index, CleanupReason::Expired));
+        } else if !file.protected && max_bytes.is_some_and(|max_bytes| total > max_bytes) {
+            total -= file.size;
+            selected.push((index, CleanupReason::OverQuota));
+        }
+    }
+    selected
+}
+
+pub fn apply_policy(app: &AppHandle, policy: &RetentionPolicy) -> Result<RetentionReport, String> {
+    let conn = open_db(app)?;
+    ensure_retention_columns(&conn)?;
+    let mut report = RetentionReport::default();
+
+    // 读取记录，同时删除指向缺失文件的记录
+    let mut rows: HashMap<String, (i64, bool)> = HashMap::new();
+    {
+        let mut stmt = conn
+            .prepare("SELECT id, file_path, pinned, tags FROM screenshots")
+            .map_err(|e| format!("Failed to query screenshots: {}", e))?;
+        let records = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)? != 0,
+                    row.get::<_, Option<String>>(3)?,
+                ))
+            })
+            .map_err(|e| format!("Failed to query screenshots: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read screenshot row: {}", e))?;
+
+        for (id, file_path, pinned, tags) in records {
+            if !Path::new(&file_path).exists() {
+                delete_screenshot_row(&conn, id)?;
+                report.reconciled_rows += 1;
+                continue;
+            }
+            let tagged = tags.map(|t| !t.trim().is_empty() && t.trim() != "[]").unwrap_or(false);
+            let protected = (policy.keep_pinned && pinned) || (policy.keep_tagged && tagged);
+            rows.insert(file_path, (id, protected));
+        }
+    }
+
+    let root = screenshots_dir(app)?;
+    let mut collected = Vec::new();
+    collect_files(&root, &mut collected);
+
+    let mut files: Vec<StoredFile> = collected
+        .into_iter()
+        .map(|(path, metadata)| {
+            let row = rows.get(&path_to_string(&path)).copied();
+            StoredFile {
+                size: metadata.len(),
+                modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                row_id: row.map(|(id, _)| id),
+                protected: row.map(|(_, protected)| protected).unwrap_or(false),
+                path,
+            }
+        })
+        .collect();
+    // 从旧到新排序，超出空间时优先清理最旧的
+    files.sort_by_key(|file| file.modified);
+
+    // 定时截图按日期目录记录被清理的文件，清理后同步更新对应的 index.jsonl
+    let timed_root = root.join("timed");
+    let mut trashed_timed: HashMap<PathBuf, HashSet<String>> = HashMap::new();
+    let mut trash = |file: &StoredFile, report: &mut RetentionReport| -> bool {
+        if let Err(e) = move_to_trash(app, &file.path) {
+            report.errors.push(e);
+            return false;
+        }
+        if let (true, Some(day_dir)) = (file.path.starts_with(&timed_root), file.path.parent()) {
+            trashed_timed
+                .entry(day_dir.to_path_buf())
+                .or_default()
+                .insert(path_to_string(&file.path));
+        }
+        if let Some(id) = file.row_id {
+            if let Err(e) = delete_screenshot_row(&conn, id) {
+                report.errors.push(e);
+            }
+        }
+        report.freed_bytes += file.size;
+        true
+    };
+
+    let age_cutoff = policy
+        .max_age_days
+        .map(|days| SystemTime::now() - Duration::from_secs(days as u64 * 24 * 3600));
+    let max_bytes = policy.max_total_gb.map(|max_gb| (max_gb * BYTES_PER_GB) as u64);
+    let mut total: u64 = files.iter().map(|file| file.size).sum();
+    for (index, reason) in select_for_cleanup(&files, age_cutoff, max_bytes) {
+        let file = &files[index];
+        if trash(file, &mut report) {
+            total -= file.size;
+            match reason {
+                CleanupReason::Expired => report.expired += 1,
+                CleanupReason::OverQuota => report.over_quota += 1,
+            }
+        }
+    }
+    report.total_bytes = total;
+
+    for (day_dir, removed) in &trashed_timed {
+        if let Err(e) = prune_index(day_dir, removed) {
+            report.errors.push(e);
+        }
+    }
+    remove_empty_dirs(&root, true);
+    report.purged_trash = purge_trash(app, policy.trash_retention_days)?;
+    report.finished_at = chrono::Local::now().timestamp_millis();
+
+    Ok(report)
+}
+
+// Tauri 命令
+#[command]
+pub fn get_retention_policy(retention: State<RetentionService>) -> Result<RetentionPolicy, String> {
+    Ok(retention.get_policy())
+}
+
+#[command]
+pub fn set_retention_policy(
+    app_handle: AppHandle,
+    retention: State<RetentionService>,
+    policy: RetentionPolicy,
+) -> Result<(), String> {
+    retention.set_policy(app_handle, policy)
+}
+
+// 立即按当前策略执行一次清理（策略未启用时同样执行）
+#[command]
+pub async fn run_retention_now(app_handle: AppHandle) -> Result<RetentionReport, String> {
+    pipeline::run_blocking(move || app_handle.state::<RetentionService>().run_now(&app_handle)).await
+}
+
+#[command]
+pub fn get_last_retention_report(retention: State<RetentionService>) -> Result<Option<RetentionReport>, String> {
+    Ok(retention.get_last_report())
+}
+
+// 固定截图，固定后不会被保留策略清理
+#[command]
+pub async fn set_screenshot_pinned(app_handle: AppHandle, id: i64, pinned: bool) -> Result<(), String> {
+    pipeline::run_blocking(move || {
+        let conn = open_db(&app_handle)?;
+        ensure_retention_columns(&conn)?;
+        conn.execute("UPDATE screenshots SET pinned = ?1 WHERE id = ?2", params![pinned as i64, id])
+            .map_err(|e| format!("Failed to update screenshot {}: {}", id, e))?;
+        Ok(())
+    })
+    .await
+}
+
+// 设置截图标签（以 JSON 数组保存），有标签的截图不会被保留策略清理
+#[command]
+pub async fn set_screenshot_tags(app_handle: AppHandle, id: i64, tags: Vec<String>) -> Result<(), String> {
+    pipeline::run_blocking(move || {
+        let conn = open_db(&app_handle)?;
+        ensure_retention_columns(&conn)?;
+        let tags = serde_json::to_string(&tags).map_err(|e| format!("Failed to serialize tags: {}", e))?;
+        conn.execute("UPDATE screenshots SET tags = ?1 WHERE id = ?2", params![tags, id])
+            .map_err(|e| format!("Failed to update screenshot {}: {}", id, e))?;
+        Ok(())
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 按修改时间（秒）从旧到新排列的文件
+    fn files(specs: &[(u64, u64, bool)]) -> Vec<StoredFile> {
+        specs
+            .iter()
+            .map(|&(modified, size, protected)| StoredFile {
+                path: PathBuf::new(),
+                size,
+                modified: SystemTime::UNIX_EPOCH + Duration::from_secs(modified),
+                row_id: None,
+                protected,
+            })
+            .collect()
+    }
+
+    fn cutoff(secs: u64) -> Option<SystemTime> {
+        Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+    }
+
+    #[test]
+    fn expires_old_unprotected_files() {
+        let files = files(&[(10, 100, true), (20, 100, false), (200, 100, false)]);
+        assert_eq!(select_for_cleanup(&files, cutoff(100), None), vec![(1, CleanupReason::Expired)]);
+        assert!(select_for_cleanup(&files, None, None).is_empty());
+    }
+
+    #[test]
+    fn quota_trims_oldest_and_skips_protected() {
+        let files = files(&[(1, 100, false), (2, 100, true), (3, 100, false), (4, 100, false), (5, 100, false)]);
+        assert_eq!(
+            select_for_cleanup(&files, None, Some(250)),
+            vec![(0, CleanupReason::OverQuota), (2, CleanupReason::OverQuota), (3, CleanupReason::OverQuota)]
+        );
+        // 未超出上限时不清理
+        assert!(select_for_cleanup(&files, None, Some(500)).is_empty());
+    }
+
+    #[test]
+    fn quota_counts_after_expired_files() {
+        let files = files(&[(10, 300, false), (20, 100, false), (200, 100, false), (300, 100, false)]);
+        // 过期文件清理后剩余 300，仍超出上限 250，再清理一个最旧的
+        assert_eq!(
+            select_for_cleanup(&files, cutoff(15), Some(250)),
+            vec![(0, CleanupReason::Expired), (1, CleanupReason::OverQuota)]
+        );
+    }
+
+    #[test]
+    fn protected_files_are_kept_even_over_quota() {
+        let files = files(&[(10, 500, true), (20, 500, true)]);
+        assert!(select_for_cleanup(&files, cutoff(100), Some(100)).is_empty());
+    }
+}
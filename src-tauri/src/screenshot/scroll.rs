@@ -0,0 +1,632 @@
+// 滚动截图：内容滚动期间反复截取同一区域，检测相邻两帧的重叠部分后拼接为一张长图
+// 滚动可以由程序发送模拟滚轮事件（auto_scroll），也可以由用户手动滚动
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use image::RgbaImage;
+use screenshots::Screen;
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle, Emitter, Manager, State};
+
+use super::monitors::{self, CaptureRegion};
+use super::pipeline::{self, elapsed_ms, CaptureTimings};
+use super::{capture_metadata, capture_screen_image, get_cursor_screen, save_and_show, ScreenshotResult};
+
+#[cfg(target_os = "linux")]
+use xcb::{x, xtest, Extension};
+#[cfg(windows)]
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    SendInput, INPUT, INPUT_0, INPUT_MOUSE, MOUSEEVENTF_WHEEL, MOUSEINPUT,
+};
+#[cfg(windows)]
+use windows::Win32::UI::WindowsAndMessaging::SetCursorPos;
+
+// 每行按列分成若干块取灰度均值，用于比较两帧
+const PROFILE_BLOCKS: usize = 32;
+// 忽略右侧的滚动条区域（占宽度的比例）
+const SCROLLBAR_RATIO: f32 = 0.03;
+// 行特征的平均差异低于该值视为相同（灰度 0-255）
+const MATCH_THRESHOLD: f32 = 3.0;
+// 判断为静止（未滚动）的阈值
+const STATIC_THRESHOLD: f32 = 0.5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrollCaptureConfig {
+    pub monitor: Option<usize>,         // 为空时使用鼠标所在的显示器
+    pub region: Option<CaptureRegion>,  // 为空时截取整个显示器
+    #[serde(default = "default_auto_scroll")]
+    pub auto_scroll: bool,              // 是否发送模拟滚轮事件
+    #[serde(default = "default_scroll_clicks")]
+    pub scroll_clicks: u32,             // 每次滚动的滚轮格数
+    #[serde(default = "default_interval_ms")]
+    pub interval_ms: u64,               // 两次截取的间隔，需留出页面渲染时间
+    #[serde(default = "default_max_height")]
+    pub max_height: u32,                // 拼接结果的最大高度（像素）
+    #[serde(default = "default_max_frames")]
+    pub max_frames: u32,
+    #[serde(default = "default_end_after_unchanged")]
+    pub end_after_unchanged: u32,       // 自动滚动时连续多少帧无变化视为到底
+}
+
+fn default_auto_scroll() -> bool {
+    true
+}
+
+fn default_scroll_clicks() -> u32 {
+    3
+}
+
+fn default_interval_ms() -> u64 {
+    300
+}
+
+fn default_max_height() -> u32 {
+    20000
+}
+
+fn default_max_frames() -> u32 {
+    300
+}
+
+fn default_end_after_unchanged() -> u32 {
+    3
+}
+
+impl Default for ScrollCaptureConfig {
+    fn default() -> Self {
+        Self {
+            monitor: None,
+            region: None,
+            auto_scroll: default_auto_scroll(),
+            scroll_clicks: default_scroll_clicks(),
+            interval_ms: default_interval_ms(),
+            max_height: default_max_height(),
+            max_frames: default_max_frames(),
+            end_after_unchanged: default_end_after_unchanged(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScrollStopReason {
+    Stopped,    // 用户停止
+    ReachedEnd, // 自动滚动到底
+    MaxHeight,
+    MaxFrames,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScrollCaptureEvent {
+    pub frames: u32,
+    pub height: u32,
+    pub appended: u32,         // 本帧新增的行数
+    pub matched: bool,         // 是否找到了重叠区域（false 表示滚动过快，直接拼接整帧）
+    pub finished: Option<ScrollStopReason>,
+}
+
+pub struct ScrollCapture {
+    // 每次开始或停止都递增会话号，截取线程发现会话号变化后结束并输出结果
+    session: Arc<Mutex<u64>>,
+    running: Arc<Mutex<bool>>,
+}
+
+impl ScrollCapture {
+    pub fn new() -> Self {
+        Self {
+            session: Arc::new(Mutex::new(0)),
+            running: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    fn begin(&self) -> Result<u64, String> {
+        let mut running = self.running.lock().unwrap();
+        if *running {
+            return Err("Scrolling capture is already running".to_string());
+        }
+        *running = true;
+        let mut session = self.session.lock().unwrap();
+        *session += 1;
+        Ok(*session)
+    }
+
+    fn finish(&self) {
+        *self.running.lock().unwrap() = false;
+    }
+
+    fn is_current(&self, session_id: u64) -> bool {
+        *self.session.lock().unwrap() == session_id
+    }
+
+    // 停止滚动截图，已截取的内容仍会拼接输出
+    pub fn stop(&self) -> bool {
+        *self.session.lock().unwrap() += 1;
+        *self.running.lock().unwrap()
+    }
+}
+
+// 每行的特征：按列分块的灰度均值
+fn row_profiles(image: &RgbaImage) -> Vec<[f32; PROFILE_BLOCKS]> {
+    let width = image.width() as usize;
+    let usable = ((width as f32 * (1.0 - SCROLLBAR_RATIO)) as usize).max(1);
+    let block_width = (usable / PROFILE_BLOCKS).max(1);
+
+    image
+        .rows()
+        .map(|row| {
+            let mut profile = [0.0; PROFILE_BLOCKS];
+            let mut counts = [0u32; PROFILE_BLOCKS];
+            for (col, pixel) in row.take(usable).enumerate() {
+                let block = (col / block_width).min(PROFILE_BLOCKS - 1);
+                let [r, g, b, _] = pixel.0;
+                profile[block] += 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+                counts[block] += 1;
+            }
+            for (value, count) in profile.iter_mut().zip(counts) {
+                if count > 0 {
+                    *value /= count as f32;
+                }
+            }
+            profile
+        })
+        .collect()
+}
+
+fn row_diff(a: &[f32; PROFILE_BLOCKS], b: &[f32; PROFILE_BLOCKS]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| (x - y).abs()).sum::<f32>() / PROFILE_BLOCKS as f32
+}
+
+// 上下两端位置不变的行数（固定的标题栏、工具栏等），这些行不参与匹配和拼接
+fn static_margins(prev: &[[f32; PROFILE_BLOCKS]], next: &[[f32; PROFILE_BLOCKS]]) -> (usize, usize) {
+    let len = prev.len().min(next.len());
+    let is_static = |row: usize| row_diff(&prev[row], &next[row]) < STATIC_THRESHOLD;
+    let top = (0..len).take_while(|&row| is_static(row)).count();
+    if top == len {
+        return (len, 0); // 整帧都没有变化
+    }
+    let bottom = (0..len).rev().take_while(|&row| is_static(row)).count();
+    // 最多把三分之一的高度视为固定区域，避免大面积留白被误判
+    (top.min(len / 3), bottom.min(len / 3))
+}
+
+// 在 [top, len - bottom) 范围内查找内容向上滚动的行数，返回 0 表示没有滚动（只有局部变化，如光标闪烁）
+fn find_scroll_offset(
+    prev: &[[f32; PROFILE_BLOCKS]],
+    next: &[[f32; PROFILE_BLOCKS]],
+    top: usize,
+    bottom: usize,
+) -> Option<usize> {
+    let band = prev.len().min(next.len()).saturating_sub(top + bottom);
+    let min_overlap = (band / 8).max(8);
+    if band <= min_overlap {
+        return None;
+    }
+
+    let mut best: Option<(usize, f32)> = None;
+    for offset in 0..=(band - min_overlap) {
+        let overlap = band - offset;
+        let step = (overlap / 200).max(1); // 行数较多时隔行采样
+        let mut total = 0.0;
+        let mut samples = 0;
+        for i in (0..overlap).step_by(step) {
+            total += row_diff(&prev[top + offset + i], &next[top + i]);
+            samples += 1;
+        }
+        let score = total / samples as f32;
+        if best.is_none_or(|(_, best_score)| score < best_score) {
+            best = Some((offset, score));
+        }
+    }
+
+    best.filter(|&(_, score)| score < MATCH_THRESHOLD).map(|(offset, _)| offset)
+}
+
+// 逐步拼接的长图，底部固定区域（如输入框、页脚）在结束时只追加一次
+struct Stitcher {
+    width: u32,
+    pixels: Vec<u8>,
+    height: u32,
+    footer: Vec<u8>,
+    footer_height: u32,
+}
+
+impl Stitcher {
+    fn new(first: &RgbaImage) -> Self {
+        Self {
+            width: first.width(),
+            pixels: first.as_raw().clone(),
+            height: first.height(),
+            footer: Vec::new(),
+            footer_height: 0,
+        }
+    }
+
+    fn row_bytes(&self) -> usize {
+        self.width as usize * 4
+    }
+
+    // 追加 frame 中 [start, end) 行
+    fn append_rows(&mut self, frame: &RgbaImage, start: u32, end: u32) {
+        let row_bytes = self.row_bytes();
+        let raw = frame.as_raw();
+        self.pixels
+            .extend_from_slice(&raw[start as usize * row_bytes..end as usize * row_bytes]);
+        self.height += end - start;
+    }
+
+    // 第一次检测到底部固定区域时，把它从已拼接的内容中移出，留到最后追加
+    fn detach_footer(&mut self, frame: &RgbaImage, footer_height: u32) {
+        if footer_height <= self.footer_height {
+            return;
+        }
+        let row_bytes = self.row_bytes();
+        let cut = (footer_height - self.footer_height).min(self.height);
+        self.height -= cut;
+        self.pixels.truncate(self.height as usize * row_bytes);
+        self.footer_height = footer_height;
+        let raw = frame.as_raw();
+        self.footer = raw[(frame.height() - footer_height) as usize * row_bytes..].to_vec();
+    }
+
+    fn total_height(&self) -> u32 {
+        self.height + self.footer_height
+    }
+
+    fn into_image(mut self, max_height: u32) -> Result<RgbaImage, String> {
+        let row_bytes = self.row_bytes();
+        let body_height = self.height.min(max_height.saturating_sub(self.footer_height).max(1));
+        self.pixels.truncate(body_height as usize * row_bytes);
+        self.pixels.extend_from_slice(&self.footer);
+        let height = body_height + self.footer_height;
+        RgbaImage::from_raw(self.width, height, self.pixels)
+            .ok_or_else(|| "Failed to create stitched image".to_string())
+    }
+}
+
+// 把逻辑坐标（相对于显示器）换算为系统指针坐标（物理像素）
+fn pointer_position(screen: &Screen, x: i32, y: i32) -> (i32, i32) {
+    let (origin_x, origin_y) = monitors::physical_origin(screen);
+    let scale = screen.display_info.scale_factor;
+    (
+        origin_x + (x as f32 * scale) as i32,
+        origin_y + (y as f32 * scale) as i32,
+    )
+}
+
+// XTest FakeInput 的事件类型（X11 核心事件编号 ButtonPress / ButtonRelease）
+#[cfg(target_os = "linux")]
+const XTEST_BUTTON_PRESS: u8 = 4;
+#[cfg(target_os = "linux")]
+const XTEST_BUTTON_RELEASE: u8 = 5;
+
+// 在 (x, y) 处发送向下的滚轮事件（X11 通过 XTest 模拟按键 5）
+#[cfg(target_os = "linux")]
+fn send_scroll(x: i32, y: i32, clicks: u32) -> Result<(), String> {
+    let (conn, screen_num) = xcb::Connection::connect_with_extensions(None, &[Extension::Test], &[])
+        .map_err(|e| format!("Failed to connect to X server: {}", e))?;
+    let root = conn
+        .get_setup()
+        .roots()
+        .nth(screen_num as usize)
+        .ok_or("X screen not found")?
+        .root();
+
+    conn.send_request(&x::WarpPointer {
+        src_window: x::WINDOW_NONE,
+        dst_window: root,
+        src_x: 0,
+        src_y: 0,
+        src_width: 0,
+        src_height: 0,
+        dst_x: x as i16,
+        dst_y: y as i16,
+    });
+    for _ in 0..clicks {
+        for event_type in [XTEST_BUTTON_PRESS, XTEST_BUTTON_RELEASE] {
+            conn.send_request(&xtest::FakeInput {
+                r#type: event_type,
+                detail: 5, // 滚轮向下
+                time: x::CURRENT_TIME,
+                root: x::WINDOW_NONE,
+                root_x: 0,
+                root_y: 0,
+                deviceid: 0,
+            });
+        }
+    }
+    conn.flush().map_err(|e| format!("Failed to send scroll event: {}", e))
+}
+
+#[cfg(windows)]
+fn send_scroll(x: i32, y: i32, clicks: u32) -> Result<(), String> {
+    const WHEEL_DELTA: i32 = 120;
+    unsafe {
+        SetCursorPos(x, y).map_err(|e| format!("Failed to move cursor: {}", e))?;
+        let input = INPUT {
+            r#type: INPUT_MOUSE,
+            Anonymous: INPUT_0 {
+                mi: MOUSEINPUT {
+                    dx: 0,
+                    dy: 0,
+                    mouseData: (-WHEEL_DELTA * clicks as i32) as u32,
+                    dwFlags: MOUSEEVENTF_WHEEL,
+                    time: 0,
+                    dwExtraInfo: 0,
+                },
+            },
+        };
+        if SendInput(&[input], std::mem::size_of::<INPUT>() as i32) == 0 {
+            return Err("Failed to send scroll event".to_string());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn send_scroll(_x: i32, _y: i32, _clicks: u32) -> Result<(), String> {
+    Err("Automatic scrolling is not supported on this platform".to_string())
+}
+
+// 滚动截图主循环（阻塞），结束原因通过 scroll-capture 事件的 finished 字段通知前端
+fn run_scroll_capture(
+    app: &AppHandle,
+    session_id: u64,
+    config: &ScrollCaptureConfig,
+) -> Result<(RgbaImage, CaptureTimings), String> {
+    let mut timings = CaptureTimings::default();
+    let screens = monitors::list_screens()?;
+    let index = match config.monitor {
+        Some(index) if index < screens.len() => index,
+        Some(index) => return Err(format!("Monitor {} not found", index)),
+        None => get_cursor_screen(&screens).unwrap_or(0),
+    };
+    let screen = &screens[index];
+    let grab = || match &config.region {
+        Some(region) => monitors::capture_region_image(screen, region),
+        None => capture_screen_image(screen),
+    };
+
+    // 滚轮事件发送到区域中心
+    let info = &screen.display_info;
+    let (center_x, center_y) = match &config.region {
+        Some(r) => (r.x + r.width as i32 / 2, r.y + r.height as i32 / 2),
+        None => (info.width as i32 / 2, info.height as i32 / 2),
+    };
+    let (pointer_x, pointer_y) = pointer_position(screen, center_x, center_y);
+
+    let scroll_capture = app.state::<ScrollCapture>();
+    let interval = Duration::from_millis(config.interval_ms.max(50));
+    let start = Instant::now();
+
+    let first = grab()?;
+    let mut prev_profiles = row_profiles(&first);
+    let mut stitcher = Stitcher::new(&first);
+    let mut frames = 1;
+    let mut unchanged = 0;
+    let frame_height = first.height();
+
+    let reason = loop {
+        if !scroll_capture.is_current(session_id) {
+            break ScrollStopReason::Stopped;
+        }
+        if frames >= config.max_frames {
+            break ScrollStopReason::MaxFrames;
+        }
+
+        if config.auto_scroll {
+            send_scroll(pointer_x, pointer_y, config.scroll_clicks.max(1))?;
+        }
+
+        // 分段休眠，保证停止命令能及时生效
+        let wait_start = Instant::now();
+        while scroll_capture.is_current(session_id) && wait_start.elapsed() < interval {
+            thread::sleep(Duration::from_millis(50).min(interval));
+        }
+
+        let frame = grab()?;
+        if frame.width() != stitcher.width || frame.height() != frame_height {
+            return Err("Capture size changed during scrolling capture".to_string());
+        }
+        let profiles = row_profiles(&frame);
+        let (top, bottom) = static_margins(&prev_profiles, &profiles);
+
+        let offset = if top >= profiles.len() {
+            Some(0)
+        } else {
+            find_scroll_offset(&prev_profiles, &profiles, top, bottom)
+        };
+
+        if offset == Some(0) {
+            // 内容没有滚动
+            unchanged += 1;
+            if config.auto_scroll && unchanged >= config.end_after_unchanged {
+                break ScrollStopReason::ReachedEnd;
+            }
+            continue;
+        }
+        unchanged = 0;
+
+        stitcher.detach_footer(&frame, bottom as u32);
+        let body_end = frame_height - bottom as u32;
+        let appended = match offset {
+            Some(offset) => offset as u32,
+            None => body_end - top as u32, // 找不到重叠时追加整帧（滚动过快）
+        };
+        stitcher.append_rows(&frame, body_end - appended, body_end);
+        prev_profiles = profiles;
+        frames += 1;
+
+        let reached_max = stitcher.total_height() >= config.max_height;
+        let _ = app.emit(
+            "scroll-capture",
+            ScrollCaptureEvent {
+                frames,
+                height: stitcher.total_height().min(config.max_height),
+                appended,
+                matched: offset.is_some(),
+                finished: None,
+            },
+        );
+        if reached_max {
+            break ScrollStopReason::MaxHeight;
+        }
+    };
+    timings.capture_ms = elapsed_ms(start);
+
+    let start = Instant::now();
+    let image = stitcher.into_image(config.max_height)?;
+    timings.stitch_ms = Some(elapsed_ms(start));
+
+    let _ = app.emit(
+        "scroll-capture",
+        ScrollCaptureEvent {
+            frames,
+            height: image.height(),
+            appended: 0,
+            matched: true,
+            finished: Some(reason),
+        },
+    );
+
+    Ok((image, timings))
+}
+
+// 滚动截图并返回拼接后的图片（阻塞直到停止或达到上限）
+pub async fn capture_scrolling(
+    app: &AppHandle,
+    config: ScrollCaptureConfig,
+) -> Result<(RgbaImage, CaptureTimings), String> {
+    let session_id = app.state::<ScrollCapture>().begin()?;
+    let worker_app = app.clone();
+    let result = pipeline::run_blocking(move || {
+        run_scroll_capture(&worker_app, session_id, &config)
+    })
+    .await;
+    app.state::<ScrollCapture>().finish();
+
+    result
+}
+
+// 开始滚动截图，结束后保存并打开贴图窗口
+#[command]
+pub async fn start_scroll_capture(
+    app: AppHandle,
+    config: Option<ScrollCaptureConfig>,
+) -> Result<ScreenshotResult, String> {
+    let total_start = Instant::now();
+    let config = config.unwrap_or_default();
+    let metadata = capture_metadata(&app, Some("scrolling"), config.monitor.map(|index| index.to_string()));
+    let (image, timings) = capture_scrolling(&app, config).await?;
+    save_and_show(&app, image, timings, total_start, metadata).await
+}
+
+// 停止滚动截图，返回是否有正在进行的滚动截图
+#[command]
+pub fn stop_scroll_capture(scroll: State<ScrollCapture>) -> Result<bool, String> {
+    Ok(scroll.stop())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{imageops, Rgba};
+
+    const WIDTH: u32 = 128;
+
+    // 每行内容各不相同的合成页面
+    fn document(height: u32, seed: u32) -> RgbaImage {
+        RgbaImage::from_fn(WIDTH, height, |x, y| {
+            let hash = (y.wrapping_add(seed).wrapping_mul(73_856_093) ^ (x / 4).wrapping_mul(19_349_663))
+                .wrapping_mul(2_654_435_761);
+            let value = (hash >> 24) as u8;
+            Rgba([value, value.wrapping_mul(3), 255 - value, 255])
+        })
+    }
+
+    fn rows(image: &RgbaImage, top: u32, height: u32) -> RgbaImage {
+        imageops::crop_imm(image, 0, top, image.width(), height).to_image()
+    }
+
+    // 自上而下拼接若干图片
+    fn stack(parts: &[&RgbaImage]) -> RgbaImage {
+        let raw: Vec<u8> = parts.iter().flat_map(|part| part.as_raw().iter().copied()).collect();
+        let height = parts.iter().map(|part| part.height()).sum();
+        RgbaImage::from_raw(WIDTH, height, raw).unwrap()
+    }
+
+    #[test]
+    fn row_profiles_ignore_scrollbar() {
+        let mut image = RgbaImage::from_pixel(WIDTH, 4, Rgba([100, 100, 100, 255]));
+        // 最右侧一列模拟滚动条
+        for y in 0..4 {
+            image.put_pixel(WIDTH - 1, y, Rgba([255, 0, 0, 255]));
+        }
+        let profiles = row_profiles(&image);
+        assert_eq!(profiles.len(), 4);
+        assert!(profiles.iter().flatten().all(|value| (value - 100.0).abs() < 0.01));
+    }
+
+    #[test]
+    fn finds_known_scroll_offset() {
+        let page = document(400, 0);
+        let prev = row_profiles(&rows(&page, 50, 120));
+        let next = row_profiles(&rows(&page, 80, 120));
+        assert_eq!(static_margins(&prev, &next), (0, 0));
+        assert_eq!(find_scroll_offset(&prev, &next, 0, 0), Some(30));
+
+        // 同一帧没有滚动
+        assert_eq!(static_margins(&prev, &prev), (120, 0));
+        assert_eq!(find_scroll_offset(&prev, &prev, 0, 0), Some(0));
+    }
+
+    #[test]
+    fn no_overlap_returns_none() {
+        let page = document(500, 0);
+        let prev = row_profiles(&rows(&page, 0, 120));
+        let next = row_profiles(&rows(&page, 300, 120));
+        assert_eq!(find_scroll_offset(&prev, &next, 0, 0), None);
+    }
+
+    #[test]
+    fn stitches_around_sticky_header_and_footer() {
+        let page = document(400, 0);
+        let header = document(10, 1000);
+        let footer = document(8, 2000);
+        let frame_at = |scroll: u32| stack(&[&header, &rows(&page, scroll, 100), &footer]);
+
+        let first = frame_at(0);
+        let mut stitcher = Stitcher::new(&first);
+        let mut prev = row_profiles(&first);
+        for scroll in [40, 75] {
+            let frame = frame_at(scroll);
+            let profiles = row_profiles(&frame);
+            let (top, bottom) = static_margins(&prev, &profiles);
+            assert_eq!((top, bottom), (10, 8));
+            let offset = find_scroll_offset(&prev, &profiles, top, bottom).unwrap() as u32;
+
+            stitcher.detach_footer(&frame, bottom as u32);
+            let body_end = frame.height() - bottom as u32;
+            stitcher.append_rows(&frame, body_end - offset, body_end);
+            prev = profiles;
+        }
+        assert_eq!(stitcher.total_height(), 10 + 175 + 8);
+
+        let expected = stack(&[&header, &rows(&page, 0, 175), &footer]);
+        assert!(stitcher.into_image(10_000).unwrap() == expected);
+    }
+
+    #[test]
+    fn height_cap_keeps_footer() {
+        let page = document(100, 0);
+        let footer = document(8, 2000);
+        let first = stack(&[&rows(&page, 0, 60), &footer]);
+        let mut stitcher = Stitcher::new(&first);
+        stitcher.detach_footer(&first, 8);
+        stitcher.append_rows(&page, 60, 100);
+        assert_eq!(stitcher.total_height(), 108);
+
+        // 超出上限时截掉正文末尾，页脚保留
+        let image = stitcher.into_image(50).unwrap();
+        assert!(image == stack(&[&rows(&page, 0, 42), &footer]));
+    }
+}
@@ -0,0 +1,278 @@
+// 截图数据的内存存储：每个贴图窗口一条记录，窗口销毁时移除，总占用超过上限时按 LRU 淘汰
+// 保存编码后的原始图片字节，由 screenshot:// 协议直接提供给 webview，不再经过 base64
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use base64::{engine::general_purpose, Engine};
+use serde::Serialize;
+use tauri::{command, AppHandle, Manager};
+
+use super::ScreenshotResult;
+
+// 默认内存上限 256 MB
+const DEFAULT_MAX_BYTES: usize = 256 * 1024 * 1024;
+
+// 编码后的图片（JPEG / PNG 等）
+#[derive(Debug, Clone)]
+pub struct StoredImage {
+    pub bytes: Arc<Vec<u8>>,
+    pub mime: &'static str,
+    pub width: u32,
+    pub height: u32,
+    pub file_path: Option<String>,
+}
+
+impl StoredImage {
+    pub fn new(bytes: Vec<u8>, width: u32, height: u32, file_path: Option<String>) -> Self {
+        let mime = guess_mime(&bytes);
+        Self {
+            bytes: Arc::new(bytes),
+            mime,
+            width,
+            height,
+            file_path,
+        }
+    }
+
+    // 转换为旧的 base64 结果，供 get_screenshot_data 使用
+    pub fn to_result(&self, id: &str) -> ScreenshotResult {
+        ScreenshotResult {
+            data: general_purpose::STANDARD.encode(self.bytes.as_slice()),
+            width: self.width,
+            height: self.height,
+            file_path: self.file_path.clone(),
+            id: Some(id.to_string()),
+            timings: None,
+            region: None,
+        }
+    }
+}
+
+// 根据文件头判断图片类型
+pub fn guess_mime(bytes: &[u8]) -> &'static str {
+    image::guess_format(bytes)
+        .map(|format| format.to_mime_type())
+        .unwrap_or("application/octet-stream")
+}
+
+struct StoreEntry {
+    image: StoredImage,
+    bytes: usize,
+    last_access: u64,
+}
+
+#[derive(Default)]
+struct StoreInner {
+    entries: HashMap<String, StoreEntry>,
+    total_bytes: usize,
+    tick: u64, // 单调递增的访问计数，用于 LRU
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StoreEntryInfo {
+    pub id: String,
+    pub width: u32,
+    pub height: u32,
+    pub bytes: usize,
+    pub last_access: u64,
+    pub has_window: bool, // 对应的贴图窗口是否还在
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StoreUsage {
+    pub entries: Vec<StoreEntryInfo>,
+    pub total_bytes: usize,
+    pub max_bytes: usize,
+}
+
+// 全局状态存储截图数据
+pub struct ScreenshotStore {
+    inner: Mutex<StoreInner>,
+    max_bytes: usize,
+}
+
+impl ScreenshotStore {
+    pub fn new() -> Self {
+        Self::with_max_bytes(DEFAULT_MAX_BYTES)
+    }
+
+    pub fn with_max_bytes(max_bytes: usize) -> Self {
+        Self {
+            inner: Mutex::new(StoreInner::default()),
+            max_bytes,
+        }
+    }
+
+    // 插入记录，超出上限时淘汰最久未访问的其他记录
+    pub fn insert(&self, id: String, image: StoredImage) -> Vec<String> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.tick += 1;
+        let bytes = image.bytes.len();
+        let entry = StoreEntry { image, bytes, last_access: inner.tick };
+
+        if let Some(old) = inner.entries.insert(id.clone(), entry) {
+            inner.total_bytes -= old.bytes;
+        }
+        inner.total_bytes += bytes;
+
+        let mut evicted = Vec::new();
+        while inner.total_bytes > self.max_bytes {
+            let oldest = inner
+                .entries
+                .iter()
+                .filter(|(key, _)| **key != id)
+                .min_by_key(|(_, entry)| entry.last_access)
+                .map(|(key, _)| key.clone());
+            let Some(oldest) = oldest else {
+                break; // 只剩刚插入的这一条，即使超过上限也保留
+            };
+            if let Some(entry) = inner.entries.remove(&oldest) {
+                inner.total_bytes -= entry.bytes;
+            }
+            evicted.push(oldest);
+        }
+
+        evicted
+    }
+
+    // 插入前端传来的 base64 数据
+    pub fn insert_base64(&self, id: String, result: ScreenshotResult) -> Result<Vec<String>, String> {
+        let bytes = general_purpose::STANDARD
+            .decode(&result.data)
+            .map_err(|e| format!("Failed to decode base64: {}", e))?;
+        Ok(self.insert(id, StoredImage::new(bytes, result.width, result.height, result.file_path)))
+    }
+
+    pub fn get(&self, id: &str) -> Option<StoredImage> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.tick += 1;
+        let tick = inner.tick;
+        inner.entries.get_mut(id).map(|entry| {
+            entry.last_access = tick;
+            entry.image.clone()
+        })
+    }
+
+    pub fn remove(&self, id: &str) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.entries.remove(id) {
+            Some(entry) => {
+                inner.total_bytes -= entry.bytes;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // 移除 id 以 prefix 开头的所有记录，返回移除的数量
+    pub fn remove_prefix(&self, prefix: &str) -> usize {
+        let mut inner = self.inner.lock().unwrap();
+        let ids: Vec<String> = inner.entries.keys().filter(|id| id.starts_with(prefix)).cloned().collect();
+        for id in &ids {
+            if let Some(entry) = inner.entries.remove(id) {
+                inner.total_bytes -= entry.bytes;
+            }
+        }
+        ids.len()
+    }
+
+    pub fn usage(&self, app: &AppHandle) -> StoreUsage {
+        let inner = self.inner.lock().unwrap();
+        let mut entries: Vec<StoreEntryInfo> = inner
+            .entries
+            .iter()
+            .map(|(id, entry)| StoreEntryInfo {
+                id: id.clone(),
+                width: entry.image.width,
+                height: entry.image.height,
+                bytes: entry.bytes,
+                last_access: entry.last_access,
+                has_window: app.get_webview_window(id).is_some(),
+            })
+            .collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.last_access));
+
+        StoreUsage {
+            entries,
+            total_bytes: inner.total_bytes,
+            max_bytes: self.max_bytes,
+        }
+    }
+}
+
+// 主动释放截图数据（例如贴图窗口已加载完图片）
+#[command]
+pub fn release_screenshot(app: AppHandle, window_id: String) -> Result<bool, String> {
+    Ok(app.state::<ScreenshotStore>().remove(&window_id))
+}
+
+// 列出当前缓存的截图及其占用
+#[command]
+pub fn list_screenshot_store(app: AppHandle) -> Result<StoreUsage, String> {
+    Ok(app.state::<ScreenshotStore>().usage(&app))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(size: usize) -> StoredImage {
+        StoredImage::new(vec![0; size], 1, 1, None)
+    }
+
+    fn total_bytes(store: &ScreenshotStore) -> usize {
+        store.inner.lock().unwrap().total_bytes
+    }
+
+    #[test]
+    fn evicts_least_recently_used_first() {
+        let store = ScreenshotStore::with_max_bytes(300);
+        assert!(store.insert("a".to_string(), image(100)).is_empty());
+        assert!(store.insert("b".to_string(), image(100)).is_empty());
+        assert!(store.insert("c".to_string(), image(100)).is_empty());
+
+        // 超出上限 150 字节，按插入顺序淘汰 a、b
+        assert_eq!(store.insert("d".to_string(), image(150)), vec!["a", "b"]);
+        assert!(store.get("a").is_none() && store.get("b").is_none());
+        assert_eq!(total_bytes(&store), 250);
+
+        // 单条超过上限时仍保留刚插入的记录
+        assert_eq!(store.insert("e".to_string(), image(400)), vec!["c", "d"]);
+        assert!(store.get("e").is_some());
+        assert_eq!(total_bytes(&store), 400);
+    }
+
+    #[test]
+    fn get_refreshes_recency() {
+        let store = ScreenshotStore::with_max_bytes(300);
+        store.insert("a".to_string(), image(100));
+        store.insert("b".to_string(), image(100));
+        store.insert("c".to_string(), image(100));
+
+        // 访问 a 之后最久未访问的是 b
+        assert!(store.get("a").is_some());
+        assert_eq!(store.insert("d".to_string(), image(100)), vec!["b"]);
+        assert!(store.get("a").is_some());
+    }
+
+    #[test]
+    fn replacing_entry_updates_total() {
+        let store = ScreenshotStore::with_max_bytes(300);
+        store.insert("a".to_string(), image(100));
+        assert!(store.insert("a".to_string(), image(200)).is_empty());
+        assert_eq!(total_bytes(&store), 200);
+    }
+
+    #[test]
+    fn remove_prefix_only_matches_prefix() {
+        let store = ScreenshotStore::with_max_bytes(1000);
+        store.insert("pin_1".to_string(), image(100));
+        store.insert("pin_2".to_string(), image(100));
+        store.insert("edit_pin_3".to_string(), image(100));
+
+        assert_eq!(store.remove_prefix("pin_"), 2);
+        assert!(store.get("pin_1").is_none() && store.get("pin_2").is_none());
+        assert!(store.get("edit_pin_3").is_some());
+        assert_eq!(total_bytes(&store), 100);
+        assert_eq!(store.remove_prefix("pin_"), 0);
+    }
+}
@@ -0,0 +1,413 @@
+// 定时截图：后台线程按固定间隔截图，按日期分目录保存
+// 与上一帧相比变化不大时不保存图片，只在当天的 index.jsonl 中记录一次"未变化"心跳
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use chrono::TimeZone;
+use image::{imageops, GrayImage, Luma, RgbaImage};
+use screenshots::Screen;
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle, Emitter, State};
+
+use super::{capture_metadata, capture_screen_image, encode_jpeg, screenshots_dir, write_screenshot_file};
+
+// 最小截图间隔（秒），避免误配置导致磁盘被迅速写满
+const MIN_INTERVAL_SECS: u64 = 1;
+
+// 变化检测用的缩略图尺寸
+const DIFF_THUMB_WIDTH: u32 = 160;
+const DIFF_THUMB_HEIGHT: u32 = 90;
+// 缩略图单个像素亮度差超过该值才视为变化，过滤 JPEG 噪声和抗锯齿抖动
+const DIFF_PIXEL_TOLERANCE: u8 = 12;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimedCaptureConfig {
+    pub interval_secs: u64,
+    pub monitor: Option<usize>, // None 表示截取所有显示器
+    #[serde(default = "default_quality")]
+    pub quality: u8, // JPEG 质量
+    #[serde(default = "default_change_threshold")]
+    pub change_threshold: f32, // 变化像素占比（百分比）低于该值时跳过保存，0 表示每次都保存
+}
+
+fn default_quality() -> u8 {
+    80
+}
+
+fn default_change_threshold() -> f32 {
+    0.5
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct TimedCaptureStatus {
+    pub running: bool,
+    pub config: Option<TimedCaptureConfig>,
+    pub capture_count: u64,
+    pub unchanged_count: u64, // 因画面未变化而跳过保存的次数
+    pub last_capture_at: Option<i64>, // 毫秒时间戳
+    pub last_error: Option<String>,
+}
+
+// 每次截图后发送给前端的 timed-capture 事件，同时作为 index.jsonl 的一行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimedCaptureEvent {
+    pub monitor: usize,
+    pub file_path: Option<String>, // 未变化时为该显示器上一次保存的文件
+    pub width: u32,
+    pub height: u32,
+    pub timestamp: i64, // 毫秒时间戳
+    pub changed: bool,
+    pub diff: f32, // 与上一帧相比的变化像素占比（百分比）
+}
+
+// 每个显示器上一次保存的帧
+struct LastFrame {
+    thumb: GrayImage,
+    file_path: String,
+}
+
+pub struct TimedCapture {
+    // 每次启动递增，旧线程发现会话号变化后自行退出
+    session: Arc<Mutex<u64>>,
+    status: Arc<Mutex<TimedCaptureStatus>>,
+}
+
+impl TimedCapture {
+    pub fn new() -> Self {
+        Self {
+            session: Arc::new(Mutex::new(0)),
+            status: Arc::new(Mutex::new(TimedCaptureStatus::default())),
+        }
+    }
+
+    pub fn start(&self, app_handle: AppHandle, config: TimedCaptureConfig) -> Result<(), String> {
+        if config.interval_secs < MIN_INTERVAL_SECS {
+            return Err(format!("Interval must be at least {} second(s)", MIN_INTERVAL_SECS));
+        }
+        if let Some(monitor) = config.monitor {
+            let screens = Screen::all().map_err(|e| format!("Failed to get screens: {}", e))?;
+            if monitor >= screens.len() {
+                return Err(format!("Monitor {} not found", monitor));
+            }
+        }
+
+        // 重新启动时让旧线程退出
+        let session_id = {
+            let mut session = self.session.lock().unwrap();
+            *session += 1;
+            *session
+        };
+
+        *self.status.lock().unwrap() = TimedCaptureStatus {
+            running: true,
+            config: Some(config.clone()),
+            ..Default::default()
+        };
+
+        let session = self.session.clone();
+        let status = self.status.clone();
+
+        thread::spawn(move || {
+            let interval = Duration::from_secs(config.interval_secs);
+            let is_current = || *session.lock().unwrap() == session_id;
+            let mut last_frames: HashMap<usize, LastFrame> = HashMap::new();
+
+            while is_current() {
+                let started = Instant::now();
+
+                match capture_once(&app_handle, &config, &mut last_frames) {
+                    Ok(events) => {
+                        let saved = events.iter().filter(|e| e.changed).count() as u64;
+                        let mut status = status.lock().unwrap();
+                        status.capture_count += saved;
+                        status.unchanged_count += events.len() as u64 - saved;
+                        status.last_capture_at = Some(chrono::Local::now().timestamp_millis());
+                        status.last_error = None;
+                        drop(status);
+
+                        for event in events {
+                            let _ = app_handle.emit("timed-capture", &event);
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("定时截图失败: {}", e);
+                        status.lock().unwrap().last_error = Some(e);
+                    }
+                }
+
+                // 分段休眠，保证停止命令能及时生效
+                while is_current() && started.elapsed() < interval {
+                    thread::sleep(Duration::from_millis(200).min(interval));
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        *self.session.lock().unwrap() += 1;
+        self.status.lock().unwrap().running = false;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.status.lock().unwrap().running
+    }
+
+    pub fn get_status(&self) -> TimedCaptureStatus {
+        self.status.lock().unwrap().clone()
+    }
+}
+
+// 某一天的定时截图目录（screenshots/timed/<日期>）
+fn day_dir(app: &AppHandle, date: chrono::NaiveDate) -> Result<PathBuf, String> {
+    Ok(screenshots_dir(app)?.join("timed").join(date.format("%Y-%m-%d").to_string()))
+}
+
+// 读取时间范围内（毫秒时间戳，含两端）指定显示器的索引记录，按时间排序
+// 索引中无法解析的行（如写入中途断电）直接跳过
+pub fn read_index(app: &AppHandle, start_ms: i64, end_ms: i64, monitor: usize) -> Result<Vec<TimedCaptureEvent>, String> {
+    let to_date = |ms: i64| {
+        chrono::Local
+            .timestamp_millis_opt(ms)
+            .single()
+            .map(|time| time.date_naive())
+            .ok_or_else(|| format!("Invalid timestamp: {}", ms))
+    };
+    let (first_day, last_day) = (to_date(start_ms)?, to_date(end_ms)?);
+
+    let mut events = Vec::new();
+    for date in first_day.iter_days().take_while(|date| *date <= last_day) {
+        let content = match fs::read_to_string(day_dir(app, date)?.join("index.jsonl")) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("Failed to read timed index for {}: {}", date, e)),
+        };
+        events.extend(
+            content
+                .lines()
+                .filter_map(|line| serde_json::from_str::<TimedCaptureEvent>(line).ok())
+                .filter(|event| event.monitor == monitor && (start_ms..=end_ms).contains(&event.timestamp)),
+        );
+    }
+    events.sort_by_key(|event| event.timestamp);
+    Ok(events)
+}
+
+// 执行一轮截图，按 screenshots/timed/<日期>/<时间>_m<显示器>.jpg 保存
+fn capture_once(
+    app: &AppHandle,
+    config: &TimedCaptureConfig,
+    last_frames: &mut HashMap<usize, LastFrame>,
+) -> Result<Vec<TimedCaptureEvent>, String> {
+    let screens = Screen::all().map_err(|e| format!("Failed to get screens: {}", e))?;
+    let targets: Vec<usize> = match config.monitor {
+        Some(index) if index < screens.len() => vec![index],
+        Some(index) => return Err(format!("Monitor {} not found", index)),
+        None => (0..screens.len()).collect(),
+    };
+
+    let now = chrono::Local::now();
+    let day_dir = day_dir(app, now.date_naive())?;
+    fs::create_dir_all(&day_dir)
+        .map_err(|e| format!("Failed to create timed screenshots directory: {}", e))?;
+
+    let mut events = Vec::with_capacity(targets.len());
+    for index in targets {
+        let metadata = capture_metadata(app, Some("timed"), Some(index.to_string()));
+        let image = capture_screen_image(&screens[index])?;
+        let thumb = diff_thumbnail(&image);
+
+        let diff = match last_frames.get(&index) {
+            Some(last) => changed_percent(&last.thumb, &thumb),
+            None => 100.0,
+        };
+
+        let event = if diff < config.change_threshold {
+            // 画面未变化，只记录心跳
+            TimedCaptureEvent {
+                monitor: index,
+                file_path: last_frames.get(&index).map(|last| last.file_path.clone()),
+                width: image.width(),
+                height: image.height(),
+                timestamp: now.timestamp_millis(),
+                changed: false,
+                diff,
+            }
+        } else {
+            let jpeg = encode_jpeg(&image, config.quality)?;
+            let file_stem = format!("{}_m{}", now.format("%H%M%S"), index);
+            let file_path = write_screenshot_file(app, &jpeg, &metadata, Some((&day_dir, &file_stem)))?;
+            last_frames.insert(index, LastFrame { thumb, file_path: file_path.clone() });
+
+            TimedCaptureEvent {
+                monitor: index,
+                file_path: Some(file_path),
+                width: image.width(),
+                height: image.height(),
+                timestamp: now.timestamp_millis(),
+                changed: true,
+                diff,
+            }
+        };
+
+        append_index(&day_dir.join("index.jsonl"), &event)?;
+        events.push(event);
+    }
+
+    Ok(events)
+}
+
+// 缩小为灰度缩略图，用于快速比较两帧
+fn diff_thumbnail(image: &RgbaImage) -> GrayImage {
+    let small = imageops::thumbnail(image, DIFF_THUMB_WIDTH, DIFF_THUMB_HEIGHT);
+    GrayImage::from_fn(small.width(), small.height(), |x, y| {
+        let p = small.get_pixel(x, y).0;
+        let luma = (p[0] as u32 * 299 + p[1] as u32 * 587 + p[2] as u32 * 114) / 1000;
+        Luma([luma as u8])
+    })
+}
+
+// 计算两张缩略图中亮度变化超过容差的像素占比（百分比）
+fn changed_percent(a: &GrayImage, b: &GrayImage) -> f32 {
+    if a.dimensions() != b.dimensions() {
+        return 100.0;
+    }
+
+    let changed = a
+        .as_raw()
+        .iter()
+        .zip(b.as_raw())
+        .filter(|(pa, pb)| pa.abs_diff(**pb) > DIFF_PIXEL_TOLERANCE)
+        .count();
+
+    changed as f32 * 100.0 / a.as_raw().len().max(1) as f32
+}
+
+// 追加一行记录到当天的索引文件
+fn append_index(path: &Path, event: &TimedCaptureEvent) -> Result<(), String> {
+    let line = serde_json::to_string(event)
+        .map_err(|e| format!("Failed to serialize index entry: {}", e))?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open index file: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write index file: {}", e))
+}
+
+// 从某天的索引中删除指向已移除文件的记录（包括引用这些文件的"未变化"心跳），记录全部删除时移除索引文件
+pub fn prune_index(day_dir: &Path, removed: &HashSet<String>) -> Result<(), String> {
+    let path = day_dir.join("index.jsonl");
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(format!("Failed to read timed index: {}", e)),
+    };
+
+    let kept: Vec<&str> = content
+        .lines()
+        .filter(|line| {
+            serde_json::from_str::<TimedCaptureEvent>(line)
+                .map(|event| !matches!(event.file_path, Some(ref file) if removed.contains(file)))
+                .unwrap_or(true)
+        })
+        .collect();
+
+    if kept.is_empty() {
+        return fs::remove_file(&path).map_err(|e| format!("Failed to remove timed index: {}", e));
+    }
+    let mut updated = kept.join("\n");
+    updated.push('\n');
+    fs::write(&path, updated).map_err(|e| format!("Failed to write timed index: {}", e))
+}
+
+// Tauri 命令
+#[command]
+pub fn start_timed_capture(
+    app_handle: AppHandle,
+    timed: State<TimedCapture>,
+    config: TimedCaptureConfig,
+) -> Result<(), String> {
+    timed.start(app_handle, config)
+}
+
+#[command]
+pub fn stop_timed_capture(timed: State<TimedCapture>) -> Result<(), String> {
+    timed.stop();
+    Ok(())
+}
+
+#[command]
+pub fn get_timed_capture_status(timed: State<TimedCapture>) -> Result<TimedCaptureStatus, String> {
+    Ok(timed.get_status())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn screen() -> RgbaImage {
+        RgbaImage::from_fn(1600, 900, |x, y| Rgba([(x / 8) as u8, (y / 4) as u8, 128, 255]))
+    }
+
+    fn fill(image: &mut RgbaImage, x: u32, y: u32, width: u32, height: u32, color: [u8; 4]) {
+        for py in y..y + height {
+            for px in x..x + width {
+                image.put_pixel(px, py, Rgba(color));
+            }
+        }
+    }
+
+    #[test]
+    fn thumbnail_is_scaled_grayscale() {
+        let thumb = diff_thumbnail(&screen());
+        assert_eq!(thumb.dimensions(), (DIFF_THUMB_WIDTH, DIFF_THUMB_HEIGHT));
+        let white = diff_thumbnail(&RgbaImage::from_pixel(320, 180, Rgba([255, 255, 255, 255])));
+        assert!(white.pixels().all(|pixel| pixel[0] == 255));
+    }
+
+    #[test]
+    fn identical_frames_and_noise_are_unchanged() {
+        let base = diff_thumbnail(&screen());
+        assert_eq!(changed_percent(&base, &base), 0.0);
+
+        // 低于容差的亮度抖动（如 JPEG 噪声）不算变化
+        let mut noisy = screen();
+        for pixel in noisy.pixels_mut() {
+            pixel[0] = pixel[0].saturating_add(8);
+        }
+        assert_eq!(changed_percent(&base, &diff_thumbnail(&noisy)), 0.0);
+    }
+
+    #[test]
+    fn small_change_stays_below_threshold() {
+        let base = diff_thumbnail(&screen());
+        // 右下角时钟大小的区域变化
+        let mut clock = screen();
+        fill(&mut clock, 1500, 870, 64, 24, [255, 255, 255, 255]);
+        let percent = changed_percent(&base, &diff_thumbnail(&clock));
+        assert!(percent > 0.0 && percent < default_change_threshold(), "{}", percent);
+    }
+
+    #[test]
+    fn large_change_exceeds_threshold() {
+        let base = diff_thumbnail(&screen());
+        // 打开一个新窗口
+        let mut window = screen();
+        fill(&mut window, 400, 200, 400, 300, [255, 255, 255, 255]);
+        let percent = changed_percent(&base, &diff_thumbnail(&window));
+        assert!(percent > default_change_threshold(), "{}", percent);
+        assert!((percent - 8.33).abs() < 1.0, "{}", percent);
+
+        // 尺寸不同（如切换分辨率）视为完全变化
+        let resized = diff_thumbnail(&RgbaImage::new(1280, 1024));
+        assert_eq!(changed_percent(&base, &resized), 100.0);
+    }
+}
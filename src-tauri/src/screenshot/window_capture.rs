@@ -0,0 +1,106 @@
+// 窗口截图：X11 上优先通过 Composite 读取窗口自身的离屏内容，窗口被遮挡或部分移出屏幕时仍然正确；
+// 其他平台或 Composite 不可用时，从窗口所在显示器的截图中裁剪可见部分
+use std::path::Path;
+use std::time::Instant;
+use image::{imageops, RgbaImage};
+use tauri::{command, AppHandle};
+
+use super::pipeline::{self, elapsed_ms, CaptureTimings};
+use super::window_list::{self, WindowInfo};
+use super::{capture_metadata, monitors, save_and_show, ScreenshotResult};
+
+// 按 ID 查找窗口，未指定时使用当前焦点窗口（没有焦点窗口时取最上层的可见窗口）
+fn find_window(window_id: Option<u64>) -> Result<WindowInfo, String> {
+    let windows = window_list::enumerate_windows()?;
+    match window_id {
+        Some(id) => windows
+            .into_iter()
+            .find(|window| window.id == id)
+            .ok_or_else(|| format!("Window {:#x} not found", id)),
+        None => {
+            let focused = windows.iter().position(|window| window.focused && !window.minimized);
+            let index = focused
+                .or_else(|| windows.iter().position(|window| !window.minimized))
+                .ok_or("No visible window to capture")?;
+            Ok(windows.into_iter().nth(index).unwrap())
+        }
+    }
+}
+
+// 从窗口所在显示器的截图中裁剪窗口的可见部分，被遮挡的区域会是上层窗口的内容
+fn crop_from_screen(window: &WindowInfo, timings: &mut CaptureTimings) -> Result<RgbaImage, String> {
+    if window.minimized {
+        return Err(format!("Window {:#x} is minimized", window.id));
+    }
+    let screens = monitors::list_screens()?;
+    let index = window.monitor.ok_or("Window is not on any monitor")?;
+    let screen = screens.get(index).ok_or_else(|| format!("Monitor {} not found", index))?;
+    let image = pipeline::capture_screen_timed(screen, timings)?;
+
+    // 窗口矩形与显示器求交
+    let (origin_x, origin_y) = monitors::physical_origin(screen);
+    let left = (window.x - origin_x).clamp(0, image.width() as i32) as u32;
+    let top = (window.y - origin_y).clamp(0, image.height() as i32) as u32;
+    let right = (window.x - origin_x + window.width as i32).clamp(0, image.width() as i32) as u32;
+    let bottom = (window.y - origin_y + window.height as i32).clamp(0, image.height() as i32) as u32;
+    if right <= left || bottom <= top {
+        return Err("Window is outside its monitor".to_string());
+    }
+
+    let start = Instant::now();
+    let cropped = imageops::crop_imm(&image, left, top, right - left, bottom - top).to_image();
+    timings.crop_ms = Some(elapsed_ms(start));
+    Ok(cropped)
+}
+
+#[cfg(target_os = "linux")]
+fn capture_window_contents(window: &WindowInfo, timings: &mut CaptureTimings) -> Result<RgbaImage, String> {
+    let start = Instant::now();
+    match super::xcomposite::capture_window(window.id as u32) {
+        Ok(image) => {
+            timings.capture_ms = elapsed_ms(start);
+            Ok(image)
+        }
+        Err(e) => {
+            eprintln!("Composite 窗口截图失败，改为从屏幕裁剪: {}", e);
+            crop_from_screen(window, timings)
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn capture_window_contents(window: &WindowInfo, timings: &mut CaptureTimings) -> Result<RgbaImage, String> {
+    crop_from_screen(window, timings)
+}
+
+// 截取指定窗口（阻塞），window_id 为 list_windows 返回的 id
+pub fn capture_window_image(window_id: Option<u64>) -> Result<(RgbaImage, WindowInfo, CaptureTimings), String> {
+    let mut timings = CaptureTimings::default();
+
+    let start = Instant::now();
+    let window = find_window(window_id)?;
+    timings.list_screens_ms = elapsed_ms(start);
+
+    let image = capture_window_contents(&window, &mut timings)?;
+    Ok((image, window, timings))
+}
+
+// 窗口截图：保存并打开贴图窗口
+#[command]
+pub async fn window_capture_and_show(app: AppHandle, window_id: Option<u64>) -> Result<ScreenshotResult, String> {
+    let total_start = Instant::now();
+    let (image, window, timings) = pipeline::run_blocking(move || capture_window_image(window_id)).await?;
+    // 记录被截取的窗口（不一定是前台窗口）
+    let mut metadata = capture_metadata(&app, Some("window"), window.monitor.map(|index| index.to_string()));
+    let app_name = window
+        .executable
+        .as_deref()
+        .and_then(|path| Path::new(path).file_stem())
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .or(window.class);
+    if app_name.is_some() {
+        metadata.app_name = app_name;
+    }
+    metadata.window_title = Some(window.title).filter(|title| !title.is_empty());
+    save_and_show(&app, image, timings, total_start, metadata).await
+}
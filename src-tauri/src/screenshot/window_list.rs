@@ -0,0 +1,369 @@
+// 窗口枚举：列出所有可见的顶层窗口及其位置、层级和状态，供区域截图吸附和窗口截图使用
+// X11 通过 EWMH 的 _NET_CLIENT_LIST_STACKING 获取窗口及层级，Windows 通过 EnumWindows
+use screenshots::Screen;
+use serde::Serialize;
+use tauri::command;
+
+use super::{monitors, pipeline};
+
+#[cfg(target_os = "linux")]
+use xcb::{x, Xid};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowInfo {
+    pub id: u64, // X11 窗口 ID / Windows HWND
+    pub pid: Option<u32>,
+    pub executable: Option<String>,
+    pub title: String,
+    pub class: Option<String>,
+    // 桌面上的物理像素坐标（不含窗口管理器添加的边框）
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub monitor: Option<usize>, // 窗口中心所在的显示器
+    pub z_order: usize,         // 0 为最上层
+    pub minimized: bool,
+    pub fullscreen: bool,
+    pub focused: bool,
+}
+
+// 窗口中心点所在的显示器（按物理像素比较）
+fn monitor_of(screens: &[Screen], x: i32, y: i32, width: u32, height: u32) -> Option<usize> {
+    let center_x = x + width as i32 / 2;
+    let center_y = y + height as i32 / 2;
+    screens.iter().position(|screen| {
+        let (left, top) = monitors::physical_origin(screen);
+        let info = &screen.display_info;
+        let (right, bottom) = if cfg!(windows) {
+            (left + info.width as i32, top + info.height as i32)
+        } else {
+            (
+                left + (info.width as f32 * info.scale_factor).round() as i32,
+                top + (info.height as f32 * info.scale_factor).round() as i32,
+            )
+        };
+        center_x >= left && center_x < right && center_y >= top && center_y < bottom
+    })
+}
+
+#[cfg(target_os = "linux")]
+struct Atoms {
+    client_list_stacking: x::Atom,
+    active_window: x::Atom,
+    wm_name: x::Atom,
+    utf8_string: x::Atom,
+    wm_pid: x::Atom,
+    wm_state: x::Atom,
+    state_hidden: x::Atom,
+    state_fullscreen: x::Atom,
+    window_type: x::Atom,
+    type_desktop: x::Atom,
+    type_dock: x::Atom,
+}
+
+#[cfg(target_os = "linux")]
+impl Atoms {
+    fn intern(conn: &xcb::Connection) -> Result<Self, String> {
+        let names: [&[u8]; 11] = [
+            b"_NET_CLIENT_LIST_STACKING",
+            b"_NET_ACTIVE_WINDOW",
+            b"_NET_WM_NAME",
+            b"UTF8_STRING",
+            b"_NET_WM_PID",
+            b"_NET_WM_STATE",
+            b"_NET_WM_STATE_HIDDEN",
+            b"_NET_WM_STATE_FULLSCREEN",
+            b"_NET_WM_WINDOW_TYPE",
+            b"_NET_WM_WINDOW_TYPE_DESKTOP",
+            b"_NET_WM_WINDOW_TYPE_DOCK",
+        ];
+        // 先发送全部请求再等待回复，减少往返
+        let cookies: Vec<_> = names
+            .iter()
+            .map(|&name| conn.send_request(&x::InternAtom { only_if_exists: false, name }))
+            .collect();
+        let atoms = cookies
+            .into_iter()
+            .map(|cookie| conn.wait_for_reply(cookie).map(|reply| reply.atom()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to intern atoms: {}", e))?;
+
+        Ok(Self {
+            client_list_stacking: atoms[0],
+            active_window: atoms[1],
+            wm_name: atoms[2],
+            utf8_string: atoms[3],
+            wm_pid: atoms[4],
+            wm_state: atoms[5],
+            state_hidden: atoms[6],
+            state_fullscreen: atoms[7],
+            window_type: atoms[8],
+            type_desktop: atoms[9],
+            type_dock: atoms[10],
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn get_property(
+    conn: &xcb::Connection,
+    window: x::Window,
+    property: x::Atom,
+    r#type: x::Atom,
+) -> Option<x::GetPropertyReply> {
+    let cookie = conn.send_request(&x::GetProperty {
+        delete: false,
+        window,
+        property,
+        r#type,
+        long_offset: 0,
+        long_length: u32::MAX / 4,
+    });
+    conn.wait_for_reply(cookie).ok()
+}
+
+#[cfg(target_os = "linux")]
+fn window_title(conn: &xcb::Connection, atoms: &Atoms, window: x::Window) -> String {
+    // 优先使用 UTF-8 的 _NET_WM_NAME，退回到 WM_NAME
+    get_property(conn, window, atoms.wm_name, atoms.utf8_string)
+        .filter(|reply| !reply.value::<u8>().is_empty())
+        .or_else(|| get_property(conn, window, x::ATOM_WM_NAME, x::ATOM_ANY))
+        .map(|reply| String::from_utf8_lossy(reply.value::<u8>()).into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "linux")]
+fn list_windows_impl(screens: &[Screen]) -> Result<Vec<WindowInfo>, String> {
+    let (conn, screen_num) = xcb::Connection::connect(None)
+        .map_err(|e| format!("Failed to connect to X server: {}", e))?;
+    let root = conn
+        .get_setup()
+        .roots()
+        .nth(screen_num as usize)
+        .ok_or("X screen not found")?
+        .root();
+    let atoms = Atoms::intern(&conn)?;
+
+    // 从下到上排列
+    let stacking: Vec<x::Window> = get_property(&conn, root, atoms.client_list_stacking, x::ATOM_WINDOW)
+        .map(|reply| reply.value::<x::Window>().to_vec())
+        .ok_or("Window manager does not support _NET_CLIENT_LIST_STACKING")?;
+    let active = get_property(&conn, root, atoms.active_window, x::ATOM_WINDOW)
+        .and_then(|reply| reply.value::<x::Window>().first().copied());
+
+    let mut windows = Vec::new();
+    for &window in stacking.iter().rev() {
+        let window_types = get_property(&conn, window, atoms.window_type, x::ATOM_ATOM)
+            .map(|reply| reply.value::<x::Atom>().to_vec())
+            .unwrap_or_default();
+        if window_types.iter().any(|t| *t == atoms.type_desktop || *t == atoms.type_dock) {
+            continue; // 跳过桌面和任务栏
+        }
+
+        let states = get_property(&conn, window, atoms.wm_state, x::ATOM_ATOM)
+            .map(|reply| reply.value::<x::Atom>().to_vec())
+            .unwrap_or_default();
+        let minimized = states.contains(&atoms.state_hidden);
+
+        let cookie = conn.send_request(&x::GetWindowAttributes { window });
+        let Ok(attributes) = conn.wait_for_reply(cookie) else {
+            continue; // 窗口已关闭
+        };
+        if attributes.map_state() != x::MapState::Viewable && !minimized {
+            continue;
+        }
+
+        let cookie = conn.send_request(&x::GetGeometry { drawable: x::Drawable::Window(window) });
+        let Ok(geometry) = conn.wait_for_reply(cookie) else {
+            continue;
+        };
+        let cookie = conn.send_request(&x::TranslateCoordinates {
+            src_window: window,
+            dst_window: root,
+            src_x: 0,
+            src_y: 0,
+        });
+        let Ok(position) = conn.wait_for_reply(cookie) else {
+            continue;
+        };
+
+        let pid = get_property(&conn, window, atoms.wm_pid, x::ATOM_CARDINAL)
+            .and_then(|reply| reply.value::<u32>().first().copied());
+        let executable = pid.and_then(|pid| {
+            std::fs::read_link(format!("/proc/{}/exe", pid))
+                .ok()
+                .map(|path| path.to_string_lossy().into_owned())
+        });
+        // WM_CLASS 为 "instance\0class\0"，取 class 部分
+        let class = get_property(&conn, window, x::ATOM_WM_CLASS, x::ATOM_STRING).and_then(|reply| {
+            reply
+                .value::<u8>()
+                .split(|&b| b == 0)
+                .rfind(|part| !part.is_empty())
+                .map(|part| String::from_utf8_lossy(part).into_owned())
+        });
+
+        let (x, y) = (position.dst_x() as i32, position.dst_y() as i32);
+        let (width, height) = (geometry.width() as u32, geometry.height() as u32);
+        // 在过滤后编号，与 Windows 路径一致为连续的 0..n
+        let z_order = windows.len();
+        windows.push(WindowInfo {
+            id: window.resource_id() as u64,
+            pid,
+            executable,
+            title: window_title(&conn, &atoms, window),
+            class,
+            x,
+            y,
+            width,
+            height,
+            monitor: monitor_of(screens, x, y, width, height),
+            z_order,
+            minimized,
+            fullscreen: states.contains(&atoms.state_fullscreen),
+            focused: active == Some(window),
+        });
+    }
+
+    Ok(windows)
+}
+
+#[cfg(windows)]
+fn list_windows_impl(screens: &[Screen]) -> Result<Vec<WindowInfo>, String> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{CloseHandle, BOOL, HWND, LPARAM, RECT};
+    use windows::Win32::Graphics::Dwm::{DwmGetWindowAttribute, DWMWA_CLOAKED, DWMWA_EXTENDED_FRAME_BOUNDS};
+    use windows::Win32::Graphics::Gdi::{
+        GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST,
+    };
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetClassNameW, GetForegroundWindow, GetWindowLongW, GetWindowRect, GetWindowTextW,
+        GetWindowThreadProcessId, IsIconic, IsWindowVisible, GWL_EXSTYLE, WS_EX_TOOLWINDOW,
+    };
+
+    // EnumWindows 按 Z 序从上到下回调
+    unsafe extern "system" fn collect(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let handles = &mut *(lparam.0 as *mut Vec<HWND>);
+        handles.push(hwnd);
+        BOOL(1)
+    }
+
+    let mut handles: Vec<HWND> = Vec::new();
+    unsafe {
+        EnumWindows(Some(collect), LPARAM(&mut handles as *mut Vec<HWND> as isize))
+            .map_err(|e| format!("Failed to enumerate windows: {}", e))?;
+    }
+
+    let foreground = unsafe { GetForegroundWindow() };
+    let mut windows = Vec::new();
+    for hwnd in handles {
+        unsafe {
+            if !IsWindowVisible(hwnd).as_bool() {
+                continue;
+            }
+            if GetWindowLongW(hwnd, GWL_EXSTYLE) as u32 & WS_EX_TOOLWINDOW.0 != 0 {
+                continue; // 工具窗口不出现在任务栏，也不参与吸附
+            }
+            // 被 DWM 隐藏的窗口（如其他虚拟桌面上的 UWP 窗口）
+            let mut cloaked = 0u32;
+            if DwmGetWindowAttribute(hwnd, DWMWA_CLOAKED, &mut cloaked as *mut u32 as *mut _, 4).is_ok()
+                && cloaked != 0
+            {
+                continue;
+            }
+
+            let mut title = [0u16; 512];
+            let len = GetWindowTextW(hwnd, &mut title);
+            if len == 0 {
+                continue;
+            }
+            let title = String::from_utf16_lossy(&title[..len as usize]);
+
+            let mut class = [0u16; 256];
+            let len = GetClassNameW(hwnd, &mut class);
+            let class = (len > 0).then(|| String::from_utf16_lossy(&class[..len as usize]));
+
+            // 优先使用不含阴影的实际边框
+            let mut rect = RECT::default();
+            if DwmGetWindowAttribute(
+                hwnd,
+                DWMWA_EXTENDED_FRAME_BOUNDS,
+                &mut rect as *mut RECT as *mut _,
+                std::mem::size_of::<RECT>() as u32,
+            )
+            .is_err()
+                && GetWindowRect(hwnd, &mut rect).is_err()
+            {
+                continue;
+            }
+
+            let mut pid = 0u32;
+            GetWindowThreadProcessId(hwnd, Some(&mut pid));
+            let executable = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid)
+                .ok()
+                .and_then(|process| {
+                    let mut path = [0u16; 1024];
+                    let mut size = path.len() as u32;
+                    let result =
+                        QueryFullProcessImageNameW(process, PROCESS_NAME_WIN32, PWSTR(path.as_mut_ptr()), &mut size)
+                            .ok()
+                            .map(|_| String::from_utf16_lossy(&path[..size as usize]));
+                    let _ = CloseHandle(process);
+                    result
+                });
+
+            // 窗口覆盖整个显示器时视为全屏
+            let hmonitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+            let mut monitor_info = MONITORINFO {
+                cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+                ..Default::default()
+            };
+            let fullscreen = GetMonitorInfoW(hmonitor, &mut monitor_info).as_bool()
+                && rect.left <= monitor_info.rcMonitor.left
+                && rect.top <= monitor_info.rcMonitor.top
+                && rect.right >= monitor_info.rcMonitor.right
+                && rect.bottom >= monitor_info.rcMonitor.bottom;
+
+            let (width, height) = ((rect.right - rect.left).max(0) as u32, (rect.bottom - rect.top).max(0) as u32);
+            let z_order = windows.len();
+            windows.push(WindowInfo {
+                id: hwnd.0 as u64,
+                pid: (pid != 0).then_some(pid),
+                executable,
+                title,
+                class,
+                x: rect.left,
+                y: rect.top,
+                width,
+                height,
+                monitor: monitor_of(screens, rect.left, rect.top, width, height),
+                z_order,
+                minimized: IsIconic(hwnd).as_bool(),
+                fullscreen,
+                focused: hwnd == foreground,
+            });
+        }
+    }
+
+    Ok(windows)
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn list_windows_impl(_screens: &[Screen]) -> Result<Vec<WindowInfo>, String> {
+    Err("Window enumeration is not supported on this platform".to_string())
+}
+
+// 列出所有可见的顶层窗口，按 Z 序从上到下排列（阻塞）
+pub fn enumerate_windows() -> Result<Vec<WindowInfo>, String> {
+    let screens = monitors::list_screens()?;
+    list_windows_impl(&screens)
+}
+
+#[command]
+pub async fn list_windows() -> Result<Vec<WindowInfo>, String> {
+    pipeline::run_blocking(enumerate_windows).await
+}
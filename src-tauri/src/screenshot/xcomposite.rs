@@ -0,0 +1,183 @@
+// X11 窗口截图：通过 Composite 扩展把窗口重定向到离屏 pixmap 后读取，
+// 窗口被遮挡或部分移出屏幕时也能得到完整内容
+use std::thread;
+use std::time::Duration;
+use image::RgbaImage;
+use xcb::{composite, x, XidNew};
+
+// 重定向后等待窗口响应 Expose 重绘的时间
+const REDIRECT_SETTLE_MS: u64 = 100;
+
+// 读取窗口自身的内容（不含边框），返回 RGBA 图片（阻塞）
+pub fn capture_window(window_id: u32) -> Result<RgbaImage, String> {
+    let (conn, _) = xcb::Connection::connect_with_extensions(None, &[xcb::Extension::Composite], &[])
+        .map_err(|e| format!("Failed to connect to X server with Composite: {}", e))?;
+
+    // NameWindowPixmap 需要 Composite 0.2 以上
+    let cookie = conn.send_request(&composite::QueryVersion {
+        client_major_version: 0,
+        client_minor_version: 4,
+    });
+    let version = conn
+        .wait_for_reply(cookie)
+        .map_err(|e| format!("Failed to query Composite version: {}", e))?;
+    if version.major_version() == 0 && version.minor_version() < 2 {
+        return Err(format!(
+            "Composite {}.{} does not support NameWindowPixmap",
+            version.major_version(),
+            version.minor_version()
+        ));
+    }
+
+    // 窗口 ID 来自 list_windows，由 X 服务器分配
+    let window = unsafe { x::Window::new(window_id) };
+    let cookie = conn.send_request(&x::GetWindowAttributes { window });
+    let attributes = conn
+        .wait_for_reply(cookie)
+        .map_err(|e| format!("Window {:#x} not found: {}", window_id, e))?;
+    if attributes.map_state() != x::MapState::Viewable {
+        // 未映射（如最小化）的窗口没有内容可读
+        return Err(format!("Window {:#x} is not mapped", window_id));
+    }
+
+    let cookie = conn.send_request(&x::GetGeometry { drawable: x::Drawable::Window(window) });
+    let geometry = conn
+        .wait_for_reply(cookie)
+        .map_err(|e| format!("Failed to get window geometry: {}", e))?;
+    let (width, height) = (geometry.width(), geometry.height());
+    if width == 0 || height == 0 {
+        return Err(format!("Window {:#x} is empty", window_id));
+    }
+
+    // 自动重定向：X 服务器继续负责合成到屏幕上，同时窗口获得自己的离屏 pixmap
+    // 已被合成管理器重定向的窗口可以再次自动重定向
+    conn.send_and_check_request(&composite::RedirectWindow {
+        window,
+        update: composite::Redirect::Automatic,
+    })
+    .map_err(|e| format!("Failed to redirect window: {}", e))?;
+    thread::sleep(Duration::from_millis(REDIRECT_SETTLE_MS));
+
+    let result = read_window_pixmap(&conn, window, &geometry);
+
+    let _ = conn.send_and_check_request(&composite::UnredirectWindow {
+        window,
+        update: composite::Redirect::Automatic,
+    });
+    result
+}
+
+fn read_window_pixmap(
+    conn: &xcb::Connection,
+    window: x::Window,
+    geometry: &x::GetGeometryReply,
+) -> Result<RgbaImage, String> {
+    let pixmap: x::Pixmap = conn.generate_id();
+    conn.send_and_check_request(&composite::NameWindowPixmap { window, pixmap })
+        .map_err(|e| format!("Failed to name window pixmap: {}", e))?;
+
+    // pixmap 包含边框，从边框内侧开始读取
+    let border = geometry.border_width() as i16;
+    let (width, height) = (geometry.width(), geometry.height());
+    let cookie = conn.send_request(&x::GetImage {
+        format: x::ImageFormat::ZPixmap,
+        drawable: x::Drawable::Pixmap(pixmap),
+        x: border,
+        y: border,
+        width,
+        height,
+        plane_mask: u32::MAX,
+    });
+    let reply = conn.wait_for_reply(cookie);
+    conn.send_request(&x::FreePixmap { pixmap });
+    let _ = conn.flush();
+    let reply = reply.map_err(|e| format!("Failed to read window pixmap: {}", e))?;
+
+    let depth = reply.depth();
+    let setup = conn.get_setup();
+    let bits_per_pixel = setup
+        .pixmap_formats()
+        .iter()
+        .find(|format| format.depth() == depth)
+        .map(|format| format.bits_per_pixel())
+        .unwrap_or(0);
+    if bits_per_pixel != 32 || setup.image_byte_order() != x::ImageOrder::LsbFirst {
+        return Err(format!("Unsupported pixmap format: depth {}, {} bpp", depth, bits_per_pixel));
+    }
+
+    bgrx_to_rgba(reply.data(), width as u32, height as u32, depth == 32)
+}
+
+// ZPixmap 数据为 BGRX（depth 32 时为预乘 alpha 的 BGRA）
+fn bgrx_to_rgba(data: &[u8], width: u32, height: u32, has_alpha: bool) -> Result<RgbaImage, String> {
+    let expected = width as usize * height as usize * 4;
+    if data.len() < expected {
+        return Err(format!("Window image too short: {} < {} bytes", data.len(), expected));
+    }
+
+    let mut rgba = Vec::with_capacity(expected);
+    for pixel in data[..expected].chunks_exact(4) {
+        let (b, g, r) = (pixel[0], pixel[1], pixel[2]);
+        if !has_alpha {
+            rgba.extend_from_slice(&[r, g, b, 255]);
+            continue;
+        }
+        let a = pixel[3];
+        let unpremultiply = |c: u8| if a == 0 { 0 } else { ((c as u32 * 255 + a as u32 / 2) / a as u32).min(255) as u8 };
+        rgba.extend_from_slice(&[unpremultiply(r), unpremultiply(g), unpremultiply(b), a]);
+    }
+
+    RgbaImage::from_raw(width, height, rgba).ok_or_else(|| "Failed to build window image".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::{Child, Command};
+
+    // 测试结束时关闭打开的窗口
+    struct Windows(Vec<Child>);
+
+    impl Drop for Windows {
+        fn drop(&mut self) {
+            for child in &mut self.0 {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+        }
+    }
+
+    fn open_xlogo(windows: &mut Windows, args: &[&str]) {
+        windows.0.push(Command::new("xlogo").args(args).spawn().unwrap());
+        thread::sleep(Duration::from_secs(1));
+    }
+
+    fn window_id(title: &str) -> u32 {
+        let output = Command::new("xwininfo").args(["-name", title]).output().unwrap();
+        let info = String::from_utf8_lossy(&output.stdout);
+        let id = info
+            .split("Window id: 0x")
+            .nth(1)
+            .and_then(|rest| rest.split_whitespace().next())
+            .unwrap();
+        u32::from_str_radix(id, 16).unwrap()
+    }
+
+    // 目标窗口被另一个窗口遮挡后，截图内容应与未遮挡时一致
+    // 需要带 Composite 扩展的 X 服务器以及 xlogo、xwininfo（x11-apps / x11-utils），例如：
+    // xvfb-run -s "-screen 0 1024x768x24 +extension Composite" cargo test -- --ignored occluded_window
+    #[test]
+    #[ignore]
+    fn occluded_window_matches_visible_capture() {
+        let mut windows = Windows(Vec::new());
+        open_xlogo(&mut windows, &["-title", "target", "-geometry", "300x300+50+50"]);
+        let target = window_id("target");
+        let visible = capture_window(target).unwrap();
+        assert_eq!(visible.dimensions(), (300, 300));
+
+        // 遮挡目标窗口的右下部分
+        open_xlogo(&mut windows, &["-title", "cover", "-bg", "red", "-geometry", "300x300+200+200"]);
+        let occluded = capture_window(target).unwrap();
+        assert!(occluded == visible);
+    }
+}
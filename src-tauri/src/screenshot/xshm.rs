@@ -0,0 +1,209 @@
+// X11 共享内存截图（MIT-SHM）：X 服务器把像素直接写入共享内存段，省去经过 socket 传输整帧数据，
+// 连接和共享内存段在多次截图之间复用，只在需要更大的区域时重新分配
+use std::ptr;
+use image::RgbaImage;
+use xcb::{shm, x};
+
+struct Segment {
+    seg: shm::Seg,
+    addr: *mut u8,
+    size: usize,
+}
+
+pub struct ShmCapturer {
+    conn: xcb::Connection,
+    root: x::Window,
+    segment: Option<Segment>,
+}
+
+// 共享内存段只在持有锁时访问
+unsafe impl Send for ShmCapturer {}
+
+impl ShmCapturer {
+    // 连接 X 服务器并确认支持 MIT-SHM；远程 X 或不支持该扩展时返回错误
+    pub fn connect() -> Result<Self, String> {
+        let (conn, screen_num) = xcb::Connection::connect_with_extensions(None, &[xcb::Extension::Shm], &[])
+            .map_err(|e| format!("Failed to connect to X server with MIT-SHM: {}", e))?;
+
+        let cookie = conn.send_request(&shm::QueryVersion {});
+        conn.wait_for_reply(cookie)
+            .map_err(|e| format!("Failed to query MIT-SHM version: {}", e))?;
+
+        let setup = conn.get_setup();
+        let screen = setup
+            .roots()
+            .nth(screen_num as usize)
+            .ok_or("X screen not found")?;
+        let root = screen.root();
+        // 只处理常见的 24/32 位深、每像素 4 字节、小端格式
+        let depth = screen.root_depth();
+        let bits_per_pixel = setup
+            .pixmap_formats()
+            .iter()
+            .find(|format| format.depth() == depth)
+            .map(|format| format.bits_per_pixel())
+            .unwrap_or(0);
+        if bits_per_pixel != 32 || setup.image_byte_order() != x::ImageOrder::LsbFirst {
+            return Err(format!("Unsupported root format: depth {}, {} bpp", depth, bits_per_pixel));
+        }
+
+        let mut capturer = Self { conn, root, segment: None };
+        // 先分配一个小段，确认共享内存可以挂载（远程连接时会失败）
+        capturer.ensure_segment(4)?;
+        Ok(capturer)
+    }
+
+    // 确保共享内存段至少为 size 字节
+    fn ensure_segment(&mut self, size: usize) -> Result<(), String> {
+        if self.segment.as_ref().is_some_and(|segment| segment.size >= size) {
+            return Ok(());
+        }
+        self.release_segment();
+
+        unsafe {
+            let id = libc::shmget(libc::IPC_PRIVATE, size, libc::IPC_CREAT | 0o600);
+            if id < 0 {
+                return Err(format!("shmget failed: {}", std::io::Error::last_os_error()));
+            }
+            let addr = libc::shmat(id, ptr::null(), 0);
+            if addr as isize == -1 {
+                let error = std::io::Error::last_os_error();
+                libc::shmctl(id, libc::IPC_RMID, ptr::null_mut());
+                return Err(format!("shmat failed: {}", error));
+            }
+
+            let seg: shm::Seg = self.conn.generate_id();
+            let attached = self.conn.send_and_check_request(&shm::Attach {
+                shmseg: seg,
+                shmid: id as u32,
+                read_only: false,
+            });
+            // X 服务器挂载后即可标记删除，双方都分离后由内核回收，进程崩溃也不会泄漏
+            libc::shmctl(id, libc::IPC_RMID, ptr::null_mut());
+            if let Err(e) = attached {
+                libc::shmdt(addr);
+                return Err(format!("Failed to attach shared memory: {}", e));
+            }
+
+            self.segment = Some(Segment { seg, addr: addr as *mut u8, size });
+        }
+        Ok(())
+    }
+
+    fn release_segment(&mut self) {
+        if let Some(segment) = self.segment.take() {
+            let _ = self.conn.send_and_check_request(&shm::Detach { shmseg: segment.seg });
+            unsafe {
+                libc::shmdt(segment.addr as *const libc::c_void);
+            }
+        }
+    }
+
+    // 截取根窗口上的区域（物理像素），结果写入 out，尺寸不同时重新分配
+    pub fn capture_into(&mut self, x: i32, y: i32, width: u32, height: u32, out: &mut RgbaImage) -> Result<(), String> {
+        if width == 0 || height == 0 || width > u16::MAX as u32 || height > u16::MAX as u32 {
+            return Err(format!("Invalid capture size {}x{}", width, height));
+        }
+        let size = width as usize * height as usize * 4;
+        self.ensure_segment(size)?;
+        let segment = self.segment.as_ref().unwrap();
+
+        let cookie = self.conn.send_request(&shm::GetImage {
+            drawable: x::Drawable::Window(self.root),
+            x: x as i16,
+            y: y as i16,
+            width: width as u16,
+            height: height as u16,
+            plane_mask: u32::MAX,
+            format: x::ImageFormat::ZPixmap as u8,
+            shmseg: segment.seg,
+            offset: 0,
+        });
+        self.conn
+            .wait_for_reply(cookie)
+            .map_err(|e| format!("MIT-SHM GetImage failed: {}", e))?;
+
+        if out.width() != width || out.height() != height {
+            *out = RgbaImage::new(width, height);
+        }
+        // 共享内存中为 BGRX，转换为 RGBA
+        let source = unsafe { std::slice::from_raw_parts(segment.addr, size) };
+        for (dst, src) in out.as_mut().chunks_exact_mut(4).zip(source.chunks_exact(4)) {
+            dst.copy_from_slice(&[src[2], src[1], src[0], 255]);
+        }
+        Ok(())
+    }
+
+    pub fn capture(&mut self, x: i32, y: i32, width: u32, height: u32) -> Result<RgbaImage, String> {
+        let mut image = RgbaImage::new(0, 0);
+        self.capture_into(x, y, width, height, &mut image)?;
+        Ok(image)
+    }
+}
+
+impl Drop for ShmCapturer {
+    fn drop(&mut self) {
+        self.release_segment();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+    use screenshots::Screen;
+
+    const FRAMES: u32 = 30;
+
+    // 平均每帧耗时（毫秒），第一帧包含连接和分配缓冲区的开销，不计入统计
+    fn measure(mut capture: impl FnMut()) -> f64 {
+        capture();
+        let start = Instant::now();
+        for _ in 0..FRAMES {
+            capture();
+        }
+        start.elapsed().as_secs_f64() * 1000.0 / FRAMES as f64
+    }
+
+    // 与 screenshots crate 截取相同的画面，并输出两种后端截取整个显示器的耗时
+    // 需要 X 服务器，没有显示器时可在 Xvfb 下运行：
+    // xvfb-run -s "-screen 0 1920x1080x24" cargo test --release -- --ignored --nocapture shm_capture
+    #[test]
+    #[ignore]
+    fn shm_capture_matches_screenshots_crate() {
+        let screens = Screen::all().unwrap();
+        let mut capturer = ShmCapturer::connect().unwrap();
+        for (index, screen) in screens.iter().enumerate() {
+            let info = &screen.display_info;
+            let x = (info.x as f32 * info.scale_factor) as i32;
+            let y = (info.y as f32 * info.scale_factor) as i32;
+            let width = (info.width as f32 * info.scale_factor) as u32;
+            let height = (info.height as f32 * info.scale_factor) as u32;
+
+            let expected = screen.capture().unwrap();
+            let expected = RgbaImage::from_raw(expected.width(), expected.height(), expected.into()).unwrap();
+            let image = capturer.capture(x, y, width, height).unwrap();
+            assert_eq!(image.dimensions(), expected.dimensions());
+            // 只比较颜色，透明通道由各后端自行填充
+            let same = image.pixels().zip(expected.pixels()).all(|(a, b)| a.0[..3] == b.0[..3]);
+            assert!(same, "monitor {} differs from screenshots crate", index);
+
+            // 复用输出缓冲区（录屏等连续截图的场景）得到相同的结果
+            let mut frame = RgbaImage::new(width, height);
+            capturer.capture_into(x, y, width, height, &mut frame).unwrap();
+            assert!(frame == image);
+
+            let crate_ms = measure(|| {
+                screen.capture().unwrap();
+            });
+            let shm_ms = measure(|| {
+                capturer.capture(x, y, width, height).unwrap();
+            });
+            let reuse_ms = measure(|| capturer.capture_into(x, y, width, height, &mut frame).unwrap());
+            println!(
+                "Monitor {} ({}x{}): screenshots {:.2} ms, xshm {:.2} ms, xshm-reuse {:.2} ms",
+                index, width, height, crate_ms, shm_ms, reuse_ms
+            );
+        }
+    }
+}
@@ -5,6 +5,8 @@ import Pomodoro from '../views/Pomodoro.vue'
 import WeekView from '../views/WeekView.vue'
 import ScreenshotView from '../views/ScreenshotView.vue'
 import ScreenshotWindowView from '../views/ScreenshotWindowView.vue'
+import ScreenshotCountdownView from '../views/ScreenshotCountdownView.vue'
+import ScreenshotRegionView from '../views/ScreenshotRegionView.vue'
 import ScreenRecordView from '../views/ScreenRecordView.vue'
 import ClipboardView from '../views/ClipboardView.vue'
 import SettingsView from '../views/SettingsView.vue'
@@ -81,6 +83,16 @@ const routes = [
     path: '/screenshot-window',
     name: 'ScreenshotWindowView',
     component: ScreenshotWindowView
+  },
+  {
+    path: '/screenshot-countdown',
+    name: 'ScreenshotCountdownView',
+    component: ScreenshotCountdownView
+  },
+  {
+    path: '/screenshot-region',
+    name: 'ScreenshotRegionView',
+    component: ScreenshotRegionView
   }
 ]
 
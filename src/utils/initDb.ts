@@ -42,11 +42,26 @@ export async function initializeTodoTable() {
             created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
         )
     `
+
+    // 创建录屏记录表（由 Rust 端在录制完成后写入）
+    const createRecordingsTableSQL = `
+        CREATE TABLE IF NOT EXISTS recordings (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            file_path TEXT NOT NULL,
+            format TEXT NOT NULL,
+            width INTEGER NOT NULL,
+            height INTEGER NOT NULL,
+            duration REAL NOT NULL,
+            size_bytes INTEGER NOT NULL DEFAULT 0,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )
+    `
     
     try {
         await execSQL(createGroupsTableSQL)
         await execSQL(createTableSQL)
         await execSQL(createScreenshotTableSQL)
+        await execSQL(createRecordingsTableSQL)
         
         // 检查是否需要添加 due_date 列（用于现有数据库的升级）
         const addDueDateColumnSQL = `
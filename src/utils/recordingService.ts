@@ -0,0 +1,167 @@
+/**
+ * 录屏服务
+ * 录制动画 GIF / APNG，或通过本机 ffmpeg 录制 MP4 / WebM（可同时录制系统声音和麦克风），
+ * 进度通过 recording-progress 事件推送
+ */
+
+import { invoke } from '@tauri-apps/api/core'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+
+export type RecordingFormat = 'gif' | 'apng' | 'mp4' | 'webm'
+
+export type AudioSourceKind = 'system' | 'microphone'
+
+export interface AudioSource {
+  kind: AudioSourceKind
+  device?: string // PulseAudio 设备名，为空时使用默认输出的 monitor / 默认输入
+  volume?: number // 0-2，默认 1
+  muted?: boolean
+}
+
+export interface RecordingConfig {
+  format?: RecordingFormat
+  monitor?: number // 为空时使用鼠标所在的显示器
+  region?: { x: number; y: number; width: number; height: number } // 显示器内的逻辑坐标
+  fps?: number // 动画 1-30，默认 10；视频 1-60，默认 30
+  duration_secs?: number // 为空时录制到 stopRecording 为止（动画最长 10 分钟，视频最长 4 小时）
+  gif_speed?: number // GIF 调色板量化速度 1-30，越小质量越好
+  ffmpeg_path?: string // 为空时使用 FFMPEG_PATH 环境变量或 PATH 中的 ffmpeg
+  codec?: string // 视频编码器，默认 MP4 为 libx264，WebM 为 libvpx-vp9
+  crf?: number // 视频质量，越小质量越好
+  audio?: AudioSource[] // 仅 MP4 / WebM，需要 PulseAudio 或 PipeWire
+}
+
+export type RecordingPhase = 'recording' | 'paused' | 'encoding' | 'finished'
+
+export interface RecordingProgress {
+  phase: RecordingPhase
+  elapsed_ms: number
+  captured_frames: number
+  stored_frames: number
+  encoded_frames: number
+}
+
+export interface RecordingResult {
+  id: number | null // recordings 表中的记录
+  file_path: string
+  format: RecordingFormat
+  width: number
+  height: number
+  frames: number
+  duration_ms: number
+  size_bytes: number
+  stop_reason: 'stopped' | 'duration' | 'memory_limit' | 'ffmpeg_error'
+  error: string | null // ffmpeg 出错时的错误信息，已保存出错前录制的部分
+  ffmpeg: string | null // 使用的 ffmpeg 路径和版本，GIF/APNG 录制时为空
+}
+
+export interface RecordingRecord {
+  id: number
+  file_path: string
+  format: string // 文件扩展名
+  width: number
+  height: number
+  duration: number // 秒
+  size_bytes: number
+  created_at: string
+}
+
+/**
+ * 开始录制，停止或达到时长并编码完成后返回结果
+ */
+export async function startRecording(config?: RecordingConfig): Promise<RecordingResult> {
+  return await invoke<RecordingResult>('start_recording', { config })
+}
+
+export async function pauseRecording(): Promise<boolean> {
+  return await invoke<boolean>('pause_recording')
+}
+
+export async function resumeRecording(): Promise<boolean> {
+  return await invoke<boolean>('resume_recording')
+}
+
+export async function stopRecording(): Promise<boolean> {
+  return await invoke<boolean>('stop_recording')
+}
+
+/**
+ * 录制中调节音源音量或静音
+ */
+export async function setRecordingAudio(
+  kind: AudioSourceKind,
+  options: { volume?: number; muted?: boolean }
+): Promise<boolean> {
+  return await invoke<boolean>('set_recording_audio', { kind, ...options })
+}
+
+export async function onRecordingProgress(
+  handler: (progress: RecordingProgress) => void
+): Promise<UnlistenFn> {
+  return await listen<RecordingProgress>('recording-progress', (event) => handler(event.payload))
+}
+
+/**
+ * 检查 ffmpeg 是否可用，返回版本信息
+ */
+export async function checkFfmpeg(ffmpegPath?: string): Promise<string> {
+  return await invoke<string>('check_ffmpeg', { ffmpegPath })
+}
+
+export async function listRecordings(): Promise<RecordingRecord[]> {
+  return await invoke<RecordingRecord[]>('list_recordings')
+}
+
+/**
+ * 删除录屏文件及其记录
+ */
+export async function deleteRecording(id: number): Promise<void> {
+  await invoke('delete_recording', { id })
+}
+
+export interface TimelapseConfig {
+  start: number // 毫秒时间戳，含两端
+  end: number
+  monitor?: number // 定时截图的显示器序号，默认 0
+  format?: RecordingFormat
+  fps?: number
+  speed?: number // 相对实际时间的倍速，如 600 为 10 分钟压缩到 1 秒；为空时每张截图一帧
+  show_timestamp?: boolean // 默认 true
+  show_app?: boolean // 叠加当时使用的应用（来自应用使用记录），默认 false
+  max_width?: number // 默认 1280
+  gif_speed?: number
+  ffmpeg_path?: string
+  codec?: string
+  crf?: number
+}
+
+export interface TimelapseProgress {
+  processed: number
+  total: number
+  encoding: boolean
+}
+
+export interface TimelapseResult {
+  id: number | null
+  file_path: string
+  format: RecordingFormat
+  width: number
+  height: number
+  screenshots: number
+  frames: number
+  duration_ms: number
+  size_bytes: number
+}
+
+/**
+ * 把时间范围内的定时截图合成为延时视频或动画，保存到录屏列表
+ */
+export async function createTimelapse(config: TimelapseConfig): Promise<TimelapseResult> {
+  return await invoke<TimelapseResult>('create_timelapse', { config })
+}
+
+export async function onTimelapseProgress(
+  handler: (progress: TimelapseProgress) => void
+): Promise<UnlistenFn> {
+  return await listen<TimelapseProgress>('timelapse-progress', (event) => handler(event.payload))
+}
@@ -11,17 +11,85 @@ export interface ScreenshotResult {
   width: number
   height: number
   file_path?: string
+  id?: string // 可通过 convertFileSrc(id, 'screenshot') 加载图片
+  timings?: Record<string, number | null> // 各阶段耗时（毫秒）
+  region?: RegionSelection // 区域截图的选区
+}
+
+// 选区，坐标为物理像素
+export interface RegionSelection {
+  monitor: number
+  x: number
+  y: number
+  width: number
+  height: number
+  desktop_x: number
+  desktop_y: number
+}
+
+// 截图目标：鼠标所在显示器、指定索引的显示器，或拼接全部显示器
+export type MonitorTarget = 'cursor' | 'all' | { index: number }
+
+export interface MonitorInfo {
+  index: number
+  id: number
+  name: string
+  x: number
+  y: number
+  width: number
+  height: number
+  physical_width: number
+  physical_height: number
+  scale_factor: number
+  rotation: number
+  frequency: number
+  is_primary: boolean
+}
+
+export interface WindowInfo {
+  id: number
+  pid?: number
+  executable?: string
+  title: string
+  class?: string
+  x: number // 物理像素
+  y: number
+  width: number
+  height: number
+  monitor?: number
+  z_order: number // 0 为最上层
+  minimized: boolean
+  fullscreen: boolean
+  focused: boolean
+}
+
+/**
+ * 列出所有可见的顶层窗口（按 Z 序从上到下）
+ */
+export async function listWindows(): Promise<WindowInfo[]> {
+  return await invoke<WindowInfo[]>('list_windows')
+}
+
+/**
+ * 列出所有显示器
+ */
+export async function listMonitors(): Promise<MonitorInfo[]> {
+  return await invoke<MonitorInfo[]>('list_monitors')
 }
 
 /**
  * 执行截图并保存到数据库
  * 这是统一的截图方法，所有入口都应该调用这个方法
  */
-export async function captureAndSave(): Promise<ScreenshotResult> {
+export async function captureAndSave(
+  monitor?: MonitorTarget,
+  includeCursor?: boolean,
+  delaySecs?: number
+): Promise<ScreenshotResult> {
   try {
     // 调用后端的 capture_and_show 命令
     // 这个命令会：1. 截图 2. 保存文件 3. 打开预览窗口
-    const result = await invoke<ScreenshotResult>('capture_and_show')
+    const result = await invoke<ScreenshotResult>('capture_and_show', { monitor, includeCursor, delaySecs })
     
     // 保存到数据库
     if (result.file_path) {
@@ -39,6 +107,245 @@ export async function captureAndSave(): Promise<ScreenshotResult> {
   }
 }
 
+/**
+ * 区域截图：冻结画面后框选，完成后保存并打开贴图窗口
+ */
+export async function captureRegionAndSave(): Promise<ScreenshotResult> {
+  const result = await invoke<ScreenshotResult>('region_capture_and_show')
+  if (result.file_path) {
+    await saveScreenshot(result.file_path, result.width, result.height)
+    refreshScreenshotList()
+  }
+  return result
+}
+
+/**
+ * 截取指定窗口并保存到数据库，windowId 为空时截取焦点窗口
+ * X11 下通过 Composite 读取窗口内容，被遮挡的窗口也能正确截取
+ */
+export async function captureWindowAndSave(windowId?: number): Promise<ScreenshotResult> {
+  const result = await invoke<ScreenshotResult>('window_capture_and_show', { windowId })
+  if (result.file_path) {
+    await saveScreenshot(result.file_path, result.width, result.height)
+    refreshScreenshotList()
+  }
+  return result
+}
+
+export interface ScrollCaptureConfig {
+  monitor?: number
+  region?: { x: number; y: number; width: number; height: number }
+  auto_scroll?: boolean
+  scroll_clicks?: number
+  interval_ms?: number
+  max_height?: number
+  max_frames?: number
+  end_after_unchanged?: number
+}
+
+/**
+ * 滚动截图：持续截取并拼接为长图，结束后保存并打开贴图窗口
+ * 进度通过 scroll-capture 事件推送，调用 stopScrollCapture 结束
+ */
+export async function captureScrollingAndSave(config?: ScrollCaptureConfig): Promise<ScreenshotResult> {
+  const result = await invoke<ScreenshotResult>('start_scroll_capture', { config })
+  if (result.file_path) {
+    await saveScreenshot(result.file_path, result.width, result.height)
+    refreshScreenshotList()
+  }
+  return result
+}
+
+export async function stopScrollCapture(): Promise<boolean> {
+  return await invoke<boolean>('stop_scroll_capture')
+}
+
+/**
+ * 取消正在进行的延时截图
+ */
+export async function cancelDelayedCapture(): Promise<boolean> {
+  return await invoke<boolean>('cancel_delayed_capture')
+}
+
+export interface CaptureMetadata {
+  captured_at: string | null // RFC 3339，带时区偏移
+  monitor: string | null // 显示器序号，拼接全部显示器时为 all
+  mode: string | null // fullscreen / window / region / scrolling
+  app_name: string | null // 截图时的前台应用（窗口截图为被截取的窗口）
+  window_title: string | null
+  app_version: string | null
+  software: string | null
+}
+
+export interface ImageMetadata {
+  format: 'png' | 'jpeg'
+  capture: CaptureMetadata
+  entries: Record<string, string> // 全部文本元数据，如 PNG.Software、EXIF.DateTimeOriginal、XMP.xmp:CreateDate
+}
+
+/**
+ * 读取图片文件中的元数据（PNG 文本块 / JPEG EXIF、XMP）
+ */
+export async function readImageMetadata(filePath: string): Promise<ImageMetadata> {
+  return await invoke<ImageMetadata>('read_image_metadata', { filePath })
+}
+
+export interface ReportConfig {
+  ids?: number[] // 按给定顺序导出，为空时使用时间范围
+  start?: number // 毫秒时间戳，含两端
+  end?: number
+  format?: 'pdf' | 'html'
+  title?: string
+  captions?: Record<number, string> // 截图 id 到说明文字
+  output_path?: string // 为空时保存到 screenshots/reports
+}
+
+export interface ReportResult {
+  file_path: string
+  format: 'pdf' | 'html'
+  screenshots: number
+  skipped: number[] // 记录不存在、文件缺失或无法解码的截图
+  size_bytes: number
+}
+
+/**
+ * 把截图连同截图时间、应用和窗口信息导出为 PDF（每页一张）或自包含的 HTML
+ */
+export async function exportScreenshotReport(config: ReportConfig): Promise<ReportResult> {
+  return await invoke<ReportResult>('export_screenshot_report', { config })
+}
+
+export type DiffSource =
+  | { library: number } // screenshots 表中的 id
+  | { store: string } // 内存中的截图（贴图窗口 id）
+  | { file: string }
+  | { timed: { monitor: number; timestamp: number } } // 该时间点之前最近的定时截图
+
+export interface DiffOptions {
+  threshold?: number // 颜色通道差值超过该值视为变化，默认 24
+  align?: boolean // 是否自动对齐平移，默认 true
+  max_shift?: number // 最大对齐像素，默认 32
+  min_pixels?: number // 忽略小于该像素数的区域
+  image?: boolean // 是否生成差异图，默认 true
+}
+
+export interface ChangedRegion {
+  x: number
+  y: number
+  width: number
+  height: number
+  changed_pixels: number
+}
+
+export interface DiffResult {
+  width: number
+  height: number
+  offset_x: number
+  offset_y: number
+  resized: boolean
+  similarity: number // 结构相似度 0-1
+  changed_pixels: number
+  changed_ratio: number
+  regions: ChangedRegion[]
+  image_id?: string // 差异图，可通过 convertFileSrc(id, 'screenshot') 加载，用完调用 releaseScreenshot
+  before_path?: string
+  after_path?: string
+}
+
+/**
+ * 对比两张截图，返回相似度、变化区域和高亮差异图
+ */
+export async function diffScreenshots(before: DiffSource, after: DiffSource, options?: DiffOptions): Promise<DiffResult> {
+  return await invoke<DiffResult>('diff_screenshots', { before, after, options })
+}
+
+/**
+ * 释放内存中的截图（如差异图）
+ */
+export async function releaseScreenshot(id: string): Promise<boolean> {
+  return await invoke<boolean>('release_screenshot', { windowId: id })
+}
+
+export type CodeFormat = 'qr' | 'ean13' | 'ean8' | 'upca' | 'code128' | 'code39'
+
+// 截图中的区域，物理像素
+export interface ScanRegion {
+  x: number
+  y: number
+  width: number
+  height: number
+}
+
+export interface DecodedCode {
+  format: CodeFormat
+  text: string
+  bounds: ScanRegion // 相对于整张截图
+  corners: [number, number][] // 左上、右上、右下、左下
+  version: number | null // QR 码版本
+  ec_level: string | null // QR 码纠错等级 L / M / Q / H
+}
+
+/**
+ * 识别截图中的二维码和条码（离线），id 为内存中的截图（贴图窗口 id），未指定时读取 filePath
+ */
+export async function scanCodes(source: { id?: string; filePath?: string }, region?: ScanRegion): Promise<DecodedCode[]> {
+  return await invoke<DecodedCode[]>('scan_codes', { id: source.id, filePath: source.filePath, region })
+}
+
+export interface PickOptions {
+  radius?: number // 放大镜显示目标点周围多少像素，默认 7（即 15 × 15）
+  zoom?: number // 每个像素放大的倍数，默认 10
+  grid?: boolean // 是否绘制像素网格，默认 true
+  loupe?: boolean // 是否生成放大镜图像，默认 true
+}
+
+export interface PickedColor {
+  x: number // 桌面物理像素
+  y: number
+  monitor: number
+  scale_factor: number
+  hex: string // #RRGGBB
+  rgb: [number, number, number]
+  hsl: [number, number, number] // 色相 0-360，饱和度和亮度 0-100
+  loupe: string | null // base64 编码的 PNG，目标像素位于正中
+  loupe_size: number
+}
+
+/**
+ * 屏幕取色：返回鼠标所在（或指定桌面物理坐标）像素的颜色和放大镜图像，可随鼠标移动频繁调用
+ */
+export async function pickColor(point?: { x: number; y: number }, options?: PickOptions): Promise<PickedColor> {
+  return await invoke<PickedColor>('pick_color', { x: point?.x, y: point?.y, options })
+}
+
+export type ResizeFilter = 'nearest' | 'triangle' | 'catmullrom' | 'gaussian' | 'lanczos3'
+
+// 颜色为 #RGB、#RRGGBB 或 #RRGGBBAA，旋转角度为顺时针
+export type EditOp =
+  | { crop: { x: number; y: number; width: number; height: number } }
+  | { rotate: { degrees: number; background?: string } } // 非 90 度倍数时画布扩大，空出的部分默认透明
+  | { flip: 'horizontal' | 'vertical' }
+  | { resize: { width?: number; height?: number; scale?: number; filter?: ResizeFilter } } // 默认 lanczos3
+  | { border: { width: number; color?: string } } // 默认白色
+  | { shadow: { blur?: number; offset_x?: number; offset_y?: number; color?: string } }
+  | { round_corners: { radius: number } }
+  | { pad_aspect: { width: number; height: number; color?: string } } // 补边到 width:height，默认透明
+
+export interface EditResult {
+  id: string // 新的内存截图，可通过 convertFileSrc(id, 'screenshot') 加载
+  source_id: string
+  width: number
+  height: number
+  mime: string // 编辑结果统一为无损 PNG
+}
+
+/**
+ * 编辑内存中的截图（贴图窗口 id），依次应用 ops，结果存为新的记录，原记录不变
+ */
+export async function editScreenshot(id: string, ops: EditOp[]): Promise<EditResult> {
+  return await invoke<EditResult>('edit_screenshot', { id, ops })
+}
+
 /**
  * 获取所有截图记录
  */